@@ -0,0 +1,123 @@
+//! Frames of the lists of a large space, drawn headless.
+//!
+//! Run with `cargo bench --bench lists`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use eframe::egui::{self, pos2, vec2, Rect};
+use storm_daenerys_common::types::{
+    acl::{AclEntry, Qualifier},
+    directory::Directory,
+    group::Group,
+};
+use storm_daenerys_front::ui::{
+    daenerys::DaenerysApp,
+    pages::main::{
+        panel_central::group::member::ui::render_show_edit_member,
+        panel_left::directory::render_directory_list, panel_right::group::render_group_list,
+    },
+};
+
+const DIRECTORIES: usize = 5_000;
+const GROUPS: usize = 2_000;
+const MEMBERS: usize = 1_000;
+
+fn uid(i: usize) -> String {
+    // Not in display name order.
+    format!("user{:04}", (i * 7919) % MEMBERS)
+}
+
+fn app() -> DaenerysApp {
+    let mut app = DaenerysApp::default();
+    app.group_prefix = Some("storm".to_string());
+
+    app.store.set_directories(Some(
+        (0..DIRECTORIES)
+            .map(|i| Directory {
+                name: format!("project-{:04}", i),
+                acls: (0..10)
+                    .map(|j| AclEntry {
+                        qualifier: Qualifier::User(j),
+                        qualifier_cn: Some(uid(i + j as usize)),
+                        qualifier_display: None,
+                        perm: 7,
+                    })
+                    .collect(),
+                valid: true,
+                quota: Some(1024 * 1024 * 1024),
+            })
+            .collect(),
+    ));
+
+    app.store.set_groups(Some(
+        (0..GROUPS)
+            .map(|i| Group {
+                cn: format!("storm-group-{:04}", i),
+                description: String::new(),
+                owner: None,
+                member: None,
+            })
+            .collect(),
+    ));
+
+    app.current_group = Some(Box::new(Group {
+        cn: "storm-large".to_string(),
+        description: String::new(),
+        owner: None,
+        member: Some((0..MEMBERS).map(uid).collect()),
+    }));
+
+    app
+}
+
+/// Draw one frame of `render` in a 1280x800 window.
+fn frame(
+    ctx: &egui::Context,
+    app: &mut DaenerysApp,
+    render: fn(&mut DaenerysApp, &egui::Context, &mut egui::Ui),
+) {
+    let input = egui::RawInput {
+        screen_rect: Some(Rect::from_min_size(pos2(0., 0.), vec2(1280., 800.))),
+        ..Default::default()
+    };
+
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| render(app, ctx, ui));
+    });
+}
+
+fn lists(c: &mut Criterion) {
+    let ctx = egui::Context::default();
+    let mut app = app();
+
+    c.bench_function("directory list frame", |b| {
+        b.iter(|| {
+            frame(&ctx, &mut app, |app, _, ui| {
+                render_directory_list(app, ui, 600.)
+            })
+        })
+    });
+
+    c.bench_function("group list frame", |b| {
+        b.iter(|| {
+            frame(&ctx, &mut app, |app, _, ui| {
+                render_group_list(app, ui, 600.)
+            })
+        })
+    });
+
+    c.bench_function("member list frame", |b| {
+        b.iter(|| frame(&ctx, &mut app, render_show_edit_member))
+    });
+
+    c.bench_function("member rows cached", |b| b.iter(|| app.member_rows().len()));
+
+    c.bench_function("member rows sorted", |b| {
+        b.iter(|| {
+            app.members_changed();
+            app.member_rows().len()
+        })
+    });
+}
+
+criterion_group!(benches, lists);
+criterion_main!(benches);
@@ -0,0 +1,134 @@
+use storm_daenerys_common::types::acl::{AclEntry, Qualifier};
+
+use crate::store::AclQualifier;
+
+// Bits of the rights of an entry.
+pub const READ: u32 = 4;
+pub const WRITE: u32 = 2;
+pub const EXECUTE: u32 = 1;
+
+/// The ACLs of a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AclKind {
+    // Rights on the directory.
+    Access,
+    // Rights given to the files and directories created in it.
+    Default,
+}
+
+/// Rights of `perm` in the `rwx` notation of `ls -l`.
+pub fn perm_string(perm: u32) -> String {
+    [(READ, 'r'), (WRITE, 'w'), (EXECUTE, 'x')]
+        .into_iter()
+        .map(|(bit, c)| if perm & bit != 0 { c } else { '-' })
+        .collect()
+}
+
+/// Rights of the mask entry of `acls`, `None` without mask.
+pub fn mask(acls: &[AclEntry]) -> Option<u32> {
+    acls.iter()
+        .find(|acl| acl.qualifier == Qualifier::Mask)
+        .map(|acl| acl.perm)
+}
+
+/// Rights granted by a user or group entry of `perm` once `mask` applied.
+pub fn effective(perm: u32, mask: Option<u32>) -> u32 {
+    mask.map_or(perm, |mask| perm & mask)
+}
+
+/// How the ACL of a directory compares with the one of its parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inheritance {
+    // Same users and groups with the same rights as the parent.
+    Inherited,
+    Changed {
+        // Users and groups added or whose rights changed.
+        own: Vec<String>,
+        // Users and groups of the parent missing.
+        removed: Vec<String>,
+    },
+}
+
+/// Compare the user and group entries of `acls` with the ones of the
+/// parent directory.
+pub fn inheritance(acls: &[AclEntry], parent: &[AclEntry]) -> Inheritance {
+    let entries = |acls: &[AclEntry]| -> Vec<(AclQualifier, u32)> {
+        acls.iter()
+            .filter_map(|acl| Some((AclQualifier::of(acl)?, acl.perm)))
+            .collect()
+    };
+    let entries_of_parent = entries(parent);
+    let entries = entries(acls);
+
+    let own: Vec<String> = entries
+        .iter()
+        .filter(|entry| !entries_of_parent.contains(entry))
+        .map(|(qualifier, _)| qualifier.name().to_string())
+        .collect();
+    let removed: Vec<String> = entries_of_parent
+        .iter()
+        .filter(|(qualifier, _)| !entries.iter().any(|(q, _)| q == qualifier))
+        .map(|(qualifier, _)| qualifier.name().to_string())
+        .collect();
+
+    if own.is_empty() && removed.is_empty() {
+        Inheritance::Inherited
+    } else {
+        Inheritance::Changed { own, removed }
+    }
+}
+
+/// Rights of `perm` written in the `rwx` notation, `None` if invalid.
+pub fn parse_perm(perm: &str) -> Option<u32> {
+    let chars: Vec<char> = perm.chars().collect();
+    let [r, w, x] = chars[..] else {
+        return None;
+    };
+
+    [(r, 'r', READ), (w, 'w', WRITE), (x, 'x', EXECUTE)]
+        .into_iter()
+        .try_fold(0, |perm, (c, granted, bit)| match c {
+            '-' => Some(perm),
+            _ if c == granted => Some(perm | bit),
+            _ => None,
+        })
+}
+
+/// Change of a user or group entry between two ACLs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AclChange {
+    Added(AclQualifier, u32),
+    Removed(AclQualifier, u32),
+    // Rights before and after.
+    Changed(AclQualifier, u32, u32),
+}
+
+/// Changes of the user and group entries from `before` to `after`, in the
+/// order of `before`, the added ones last.
+pub fn diff(before: &[AclEntry], after: &[AclEntry]) -> Vec<AclChange> {
+    let entries = |acls: &[AclEntry]| -> Vec<(AclQualifier, u32)> {
+        acls.iter()
+            .filter_map(|acl| Some((AclQualifier::of(acl)?, acl.perm)))
+            .collect()
+    };
+    let before = entries(before);
+    let after = entries(after);
+
+    let mut changes = Vec::new();
+    for (qualifier, perm) in &before {
+        match after.iter().find(|(q, _)| q == qualifier) {
+            Some((_, new_perm)) if new_perm != perm => {
+                changes.push(AclChange::Changed(qualifier.clone(), *perm, *new_perm))
+            }
+            Some(_) => (),
+            None => changes.push(AclChange::Removed(qualifier.clone(), *perm)),
+        }
+    }
+    for (qualifier, perm) in &after {
+        if !before.iter().any(|(q, _)| q == qualifier) {
+            changes.push(AclChange::Added(qualifier.clone(), *perm));
+        }
+    }
+
+    changes
+}
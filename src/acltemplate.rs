@@ -0,0 +1,119 @@
+use serde::Deserialize;
+use storm_daenerys_common::types::acl::{AclEntry, Qualifier};
+
+use crate::acl::{parse_perm, EXECUTE, READ};
+
+// Placeholders of the user and group names of a template.
+const PLACEHOLDERS: [&str; 3] = ["{prefix}", "{name}", "{admin}"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateKind {
+    User,
+    Group,
+}
+
+/// An entry of a template, its name with placeholders.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateEntry {
+    pub kind: TemplateKind,
+    pub name: String,
+    // Rights in the `rwx` notation.
+    pub perm: String,
+}
+
+/// A named ACL applied to a directory in one step, such as the one of
+/// every new project.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AclTemplate {
+    pub name: String,
+    pub entries: Vec<TemplateEntry>,
+}
+
+/// Values of the placeholders for a directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateValues {
+    // Prefix of the STORM groups.
+    pub prefix: String,
+    // Name of the directory, without its parents.
+    pub name: String,
+    // Admin of the STORM space.
+    pub admin: String,
+}
+
+impl AclTemplate {
+    /// The template used when none is configured: the admin and the
+    /// project group with all the rights, the invite group read only.
+    pub fn project() -> Self {
+        let entry = |kind, name: &str, perm: &str| TemplateEntry {
+            kind,
+            name: name.to_string(),
+            perm: perm.to_string(),
+        };
+
+        Self {
+            name: "project".to_string(),
+            entries: vec![
+                entry(TemplateKind::User, "{admin}", "rwx"),
+                entry(TemplateKind::Group, "{prefix}-{name}", "rwx"),
+                entry(TemplateKind::Group, "{prefix}-{name}-invite", "r-x"),
+            ],
+        }
+    }
+
+    /// Check the rights and placeholders of the entries.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.entries.is_empty() {
+            return Err("a template needs at least one entry");
+        }
+
+        for entry in &self.entries {
+            if parse_perm(&entry.perm).is_none() {
+                return Err("expected rights such as rwx or r-x");
+            }
+
+            // What remains once the known placeholders removed.
+            let rest = PLACEHOLDERS
+                .iter()
+                .fold(entry.name.clone(), |name, placeholder| {
+                    name.replace(placeholder, "")
+                });
+            if rest.contains(['{', '}']) {
+                return Err("unknown placeholder, expected {prefix}, {name} or {admin}");
+            }
+            if entry.name.trim().is_empty() {
+                return Err("empty user or group name");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Entries of the template for `values`, without gid: the groups are
+    /// `Group(0)` and the users `User(0)`.
+    pub fn resolve(&self, values: &TemplateValues) -> Vec<AclEntry> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let name = entry
+                    .name
+                    .replace("{prefix}", &values.prefix)
+                    .replace("{name}", &values.name)
+                    .replace("{admin}", &values.admin);
+
+                AclEntry {
+                    qualifier: match entry.kind {
+                        TemplateKind::User => Qualifier::User(0),
+                        TemplateKind::Group => Qualifier::Group(0),
+                    },
+                    qualifier_cn: Some(name.clone()),
+                    qualifier_display: Some(name),
+                    // Validated when loaded.
+                    perm: parse_perm(&entry.perm).unwrap_or(READ | EXECUTE),
+                }
+            })
+            .collect()
+    }
+}
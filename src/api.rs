@@ -1,6 +1,17 @@
 pub mod acl;
+pub mod acljob;
+pub mod auth;
+pub mod cancel;
+pub mod client;
+pub mod conditional;
 pub mod directory;
 pub mod group;
+pub mod inspector;
 pub mod quota;
+pub mod retry;
 pub mod root;
+pub mod tls;
+pub mod transport;
 pub mod user;
+pub mod userdisplay;
+pub mod version;
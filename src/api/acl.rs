@@ -1,69 +1,36 @@
-use eframe::egui;
 use log::debug;
 use poll_promise::Promise;
 
-use storm_daenerys_common::types::{acl::SetAcl, error::CommonError};
+use storm_daenerys_common::types::acl::{AclEntry, SetAcl};
 
-#[cfg(target_arch = "wasm32")]
-use ehttp::Mode;
+use crate::error::apierror::ApiError;
 
-pub fn save_acl(
-    ctx: &egui::Context,
-    set_acl: SetAcl,
-    api_url: String,
-) -> Promise<Result<(), std::string::String>> {
-    debug!("Save ACL: {:#?}", set_acl);
+use super::client::{form_encode, parse_empty, parse_json, ApiClient};
 
-    let ctx = ctx.clone();
-    let (sender, promise) = Promise::new();
+impl ApiClient {
+    pub fn save_acl(&self, set_acl: SetAcl) -> Promise<Result<(), ApiError>> {
+        debug!("Save ACL: {:#?}", set_acl);
 
-    let request_payload = match serde_json::to_string(&set_acl) {
-        Ok(request_payload) => request_payload,
-        Err(e) => {
-            sender.send(Err(e.to_string()));
-            return promise;
-        }
-    };
-
-    let request = ehttp::Request {
-        method: "POST".to_owned(),
-        url: format!("{}/acls", api_url),
-        body: request_payload.as_bytes().to_vec(),
-        headers: ehttp::Headers::new(&[("Accept", "*/*"), ("Content-Type", "application/json")]),
-        #[cfg(target_arch = "wasm32")]
-        mode: Mode::default(),
-    };
-
-    ehttp::fetch(request, move |response| {
-        let save_directory_acl_result = response.and_then(parse_save_acl_response);
-        sender.send(save_directory_acl_result);
-        ctx.request_repaint(); // wake up UI thread
-    });
-
-    promise
-}
+        self.send("POST", "/acls", &set_acl, parse_empty)
+    }
 
-fn parse_save_acl_response(response: ehttp::Response) -> Result<(), String> {
-    let status = &response.status;
-    let status_text = &response.status_text;
-    let maybe_text_response = response.text();
+    /// Default ACL of the directory `name`, given to the files and
+    /// directories created in it.
+    pub fn get_default_acl(&self, name: &str) -> Promise<Result<Vec<AclEntry>, ApiError>> {
+        debug!("Get default ACL of {}.", name);
+
+        self.get(
+            &format!("/defaultacls?name={}", form_encode(name)),
+            |response| {
+                // An empty body means no default ACL.
+                parse_json(response).map(Option::unwrap_or_default)
+            },
+        )
+    }
 
-    debug!("{:?}", status);
-    debug!("{:?}", status_text);
-    debug!("{:?}", maybe_text_response);
+    pub fn save_default_acl(&self, set_acl: SetAcl) -> Promise<Result<(), ApiError>> {
+        debug!("Save default ACL: {:#?}", set_acl);
 
-    match status {
-        200 => Ok(()),
-        _ => match maybe_text_response {
-            Some(text_response) => {
-                let common_error: CommonError =
-                    match serde_json::from_str::<CommonError>(text_response) {
-                        Ok(common_error) => common_error,
-                        Err(e) => CommonError::InternalServerError(e.to_string()),
-                    };
-                Err(common_error.to_string())
-            }
-            None => Err(status.to_string()),
-        },
+        self.send("POST", "/defaultacls", &set_acl, parse_empty)
     }
 }
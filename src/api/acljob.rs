@@ -0,0 +1,136 @@
+use std::{fs, io, path::Path};
+
+use log::debug;
+use poll_promise::Promise;
+use serde::{Deserialize, Serialize};
+use storm_daenerys_common::types::acl::AclEntry;
+
+use crate::error::apierror::ApiError;
+
+use super::client::{form_encode, parse_empty, parse_json_required, ApiClient};
+
+/// Payload of POST /acls/recursive: the ACLs of a directory, applied to
+/// everything below it too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecursiveAcl {
+    pub name: String,
+    pub acls: Vec<AclEntry>,
+    // Default ACL of the directories, `None` to keep theirs.
+    pub default_acls: Option<Vec<AclEntry>>,
+}
+
+/// Backend job started by POST /acls/recursive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AclJob {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AclJobState {
+    Running,
+    Done,
+    Cancelled,
+}
+
+/// A path whose ACL could not be set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AclJobFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Answer of GET /acljobs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AclJobStatus {
+    pub id: String,
+    // Directory the ACLs are applied from.
+    pub name: String,
+    pub state: AclJobState,
+    // Files and directories processed so far, failed ones included.
+    pub processed: u64,
+    // `None` while the backend is still counting them.
+    pub total: Option<u64>,
+    pub failures: Vec<AclJobFailure>,
+}
+
+impl AclJobStatus {
+    /// Status of the job `job` on the directory `name`, before the first
+    /// poll.
+    pub fn started(job: AclJob, name: &str) -> Self {
+        Self {
+            id: job.id,
+            name: name.to_string(),
+            state: AclJobState::Running,
+            processed: 0,
+            total: None,
+            failures: Vec::new(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state == AclJobState::Running
+    }
+
+    /// Done fraction, `None` until the total is known.
+    pub fn fraction(&self) -> Option<f32> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| self.processed.min(total) as f32 / total as f32)
+    }
+
+    /// The failed paths and their error, one per line separated by a tab.
+    pub fn report(&self) -> String {
+        self.failures
+            .iter()
+            .map(|failure| format!("{}\t{}\n", failure.path, failure.error))
+            .collect()
+    }
+
+    /// Write the report to `path`.
+    pub fn save_report(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.report())
+    }
+
+    /// File name of the report, unique for the job.
+    pub fn report_file_name(&self) -> String {
+        format!("acl-report-{}-{}.tsv", self.name.replace('/', "_"), self.id)
+    }
+}
+
+impl ApiClient {
+    /// Set `recursive_acl` on the directory and everything below it, in a
+    /// backend job.
+    pub fn start_recursive_acl(
+        &self,
+        recursive_acl: RecursiveAcl,
+    ) -> Promise<Result<AclJob, ApiError>> {
+        debug!("Start recursive ACL: {:#?}", recursive_acl);
+
+        self.send("POST", "/acls/recursive", &recursive_acl, |response| {
+            parse_json_required(response, "acl job")
+        })
+    }
+
+    pub fn get_acl_job(&self, id: &str) -> Promise<Result<AclJobStatus, ApiError>> {
+        debug!("Get acl job {}.", id);
+
+        self.get(&format!("/acljobs?id={}", form_encode(id)), |response| {
+            parse_json_required(response, "acl job status")
+        })
+    }
+
+    /// Stop the job `id`, the paths already processed keep their new ACL.
+    pub fn cancel_acl_job(&self, id: &str) -> Promise<Result<(), ApiError>> {
+        debug!("Cancel acl job {}.", id);
+
+        self.send_empty(
+            "DELETE",
+            &format!("/acljobs?id={}", form_encode(id)),
+            parse_empty,
+        )
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::warn;
+
+use super::device;
+use crate::api::transport::Transport;
+
+// A token expiring within this delay is refreshed before being sent.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Authentication used to call the backend API.
+#[derive(Clone, Default, PartialEq)]
+pub enum AuthConfig {
+    // No credentials are sent.
+    #[default]
+    None,
+    // Static bearer token.
+    Bearer(String),
+    // Token obtained with the OIDC device authorization flow.
+    Oidc(OidcConfig),
+}
+
+// Never print the token.
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AuthConfig::None => write!(f, "None"),
+            AuthConfig::Bearer(_) => write!(f, "Bearer(<redacted>)"),
+            AuthConfig::Oidc(config) => write!(f, "Oidc({:?})", config),
+        }
+    }
+}
+
+/// OIDC identity provider used by the device authorization flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OidcConfig {
+    // Issuer URL, the discovery document is fetched from
+    // `<issuer>/.well-known/openid-configuration`.
+    pub issuer: String,
+    pub client_id: String,
+    // Space separated scopes.
+    pub scope: String,
+}
+
+/// OAuth2 access token.
+#[derive(Clone)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    // `None` if the token never expires.
+    pub expires_at: Option<Instant>,
+}
+
+impl Token {
+    /// A token without expiry nor refresh token, such as a static bearer token.
+    pub fn new(access_token: String) -> Self {
+        Self {
+            access_token,
+            refresh_token: None,
+            expires_at: None,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Instant::now() + EXPIRY_MARGIN >= expires_at)
+    }
+}
+
+// Never print the tokens.
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("refresh_token", &self.refresh_token.is_some())
+            .field("expires_at", &self.expires_at)
+            .finish_non_exhaustive()
+    }
+}
+
+type Waiter = Box<dyn FnOnce(Option<String>) + Send>;
+
+#[derive(Default)]
+struct State {
+    // Current token, `None` when logged out.
+    token: Option<Token>,
+    // Endpoint used to refresh the token.
+    token_endpoint: Option<String>,
+    // Requests waiting for the token refresh in progress, if any.
+    refresh_waiters: Option<Vec<Waiter>>,
+}
+
+/// Credentials sent with every API request.
+///
+/// Shared by all the clones of the `ApiClient`, so that a login or a token
+/// refresh is seen by every request.
+#[derive(Clone, Default)]
+pub struct Credentials {
+    config: Arc<AuthConfig>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Credentials {
+    pub fn new(config: AuthConfig) -> Self {
+        let token = match &config {
+            AuthConfig::Bearer(token) => Some(Token::new(token.clone())),
+            AuthConfig::None | AuthConfig::Oidc(_) => None,
+        };
+
+        Self {
+            config: Arc::new(config),
+            state: Arc::new(Mutex::new(State {
+                token,
+                ..Default::default()
+            })),
+        }
+    }
+
+    pub fn is_logged_in(&self) -> bool {
+        self.state.lock().unwrap().token.is_some()
+    }
+
+    /// True if the user must log in before calling the API.
+    pub fn needs_login(&self) -> bool {
+        matches!(*self.config, AuthConfig::Oidc(_)) && !self.is_logged_in()
+    }
+
+    /// Use `token` for the next requests.
+    /// `token_endpoint` is used to refresh it when it expires.
+    pub fn set_token(&self, token: Token, token_endpoint: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.token = Some(token);
+        state.token_endpoint = token_endpoint;
+    }
+
+    /// Forget the token, the next requests are sent without credentials.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.token = None;
+        state.token_endpoint = None;
+    }
+
+    /// Call `on_ready` with the access token to send, refreshing it first
+    /// with `transport` if it has expired.
+    pub(crate) fn with_access_token(
+        &self,
+        transport: &Transport,
+        on_ready: impl FnOnce(Option<String>) + Send + 'static,
+    ) {
+        let mut state = self.state.lock().unwrap();
+
+        // A refresh is in progress, wait for it.
+        if let Some(waiters) = state.refresh_waiters.as_mut() {
+            waiters.push(Box::new(on_ready));
+            return;
+        }
+
+        let refresh = match (&*self.config, &state.token, &state.token_endpoint) {
+            (AuthConfig::Oidc(oidc), Some(token), Some(token_endpoint)) if token.is_expired() => {
+                token.refresh_token.as_ref().map(|refresh_token| {
+                    (oidc.clone(), token_endpoint.clone(), refresh_token.clone())
+                })
+            }
+            _ => None,
+        };
+
+        // Without refresh token an expired token is sent anyway,
+        // the backend answers 401 and the user is asked to log in again.
+        let Some((oidc, token_endpoint, refresh_token)) = refresh else {
+            let access_token = state.token.as_ref().map(|t| t.access_token.clone());
+            drop(state);
+            on_ready(access_token);
+            return;
+        };
+
+        state.refresh_waiters = Some(vec![Box::new(on_ready)]);
+        drop(state);
+
+        let credentials = self.clone();
+        device::refresh(
+            transport,
+            &oidc,
+            &token_endpoint,
+            refresh_token,
+            move |result| {
+                let mut state = credentials.state.lock().unwrap();
+
+                let access_token = match result {
+                    Ok(token) => {
+                        let access_token = token.access_token.clone();
+                        state.token = Some(token);
+                        Some(access_token)
+                    }
+                    Err(e) => {
+                        warn!("token refresh failed: {}", e);
+                        state.token = None;
+                        None
+                    }
+                };
+
+                let waiters = state.refresh_waiters.take().unwrap_or_default();
+                drop(state);
+
+                for waiter in waiters {
+                    waiter(access_token.clone());
+                }
+            },
+        );
+    }
+}
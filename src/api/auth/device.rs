@@ -0,0 +1,306 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+use log::debug;
+use poll_promise::Promise;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::credentials::{OidcConfig, Token};
+use crate::api::client::form_encode;
+use crate::api::transport::Transport;
+use crate::error::apierror::ApiError;
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+// Polling interval when the identity provider does not give one (RFC 8628).
+const DEFAULT_INTERVAL: u64 = 5;
+// Added to the polling interval on a `slow_down` answer (RFC 8628).
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+/// Device code waiting for the user to log in, shown on the login page.
+#[derive(Debug, Clone)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    // Code the user types on the verification page.
+    pub user_code: String,
+    pub verification_uri: String,
+    // Verification page with the user code already filled in, if supported.
+    pub verification_uri_complete: Option<String>,
+    pub expires_at: Instant,
+    // Delay between two token requests.
+    pub interval: Duration,
+    // Endpoint polled for the token, also used to refresh it.
+    pub token_endpoint: String,
+}
+
+impl DeviceAuthorization {
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    /// Increase the polling interval as asked by the identity provider.
+    pub fn slow_down(&mut self) {
+        self.interval += SLOW_DOWN_INCREMENT;
+    }
+}
+
+/// Result of a token request while the user logs in.
+#[derive(Debug)]
+pub enum PollStatus {
+    // The user has not logged in yet.
+    Pending,
+    // Polling too fast, the interval must be increased.
+    SlowDown,
+    // The user logged in.
+    Granted(Token),
+}
+
+#[derive(Deserialize)]
+struct Discovery {
+    device_authorization_endpoint: Option<String>,
+    token_endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct DeviceAuthorizationResponse {
+    device_code: String,
+    user_code: String,
+    // Some providers use the draft name `verification_url`.
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+impl TokenResponse {
+    fn into_token(self) -> Token {
+        Token {
+            access_token: self.access_token,
+            refresh_token: self.refresh_token,
+            expires_at: self
+                .expires_in
+                .map(|expires_in| Instant::now() + Duration::from_secs(expires_in)),
+        }
+    }
+}
+
+// Error body of the OAuth2 endpoints.
+#[derive(Deserialize)]
+struct OAuthError {
+    error: String,
+    error_description: Option<String>,
+}
+
+/// OAuth2 device authorization grant (RFC 8628) against an OIDC identity
+/// provider, for applications that can not receive a redirect.
+#[derive(Clone)]
+pub struct DeviceFlow {
+    // egui context, repainted on each response.
+    ctx: egui::Context,
+    config: OidcConfig,
+    // Sends the requests to the identity provider.
+    transport: Transport,
+}
+
+impl DeviceFlow {
+    pub fn new(ctx: &egui::Context, config: OidcConfig) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            config,
+            transport: Transport::default(),
+        }
+    }
+
+    /// Send every request with `transport`.
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn config(&self) -> &OidcConfig {
+        &self.config
+    }
+
+    /// Discover the identity provider endpoints and ask for a device code.
+    pub fn start(&self) -> Promise<Result<DeviceAuthorization, ApiError>> {
+        let ctx = self.ctx.clone();
+        let config = self.config.clone();
+        let transport = self.transport.clone();
+        let (sender, promise) = Promise::new();
+
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            config.issuer.trim_end_matches('/')
+        );
+
+        fetch(
+            &self.transport,
+            ehttp::Request::get(discovery_url),
+            move |response| {
+                let discovery = match response.and_then(parse_oauth::<Discovery>) {
+                    Ok(discovery) => discovery,
+                    Err(e) => {
+                        sender.send(Err(e));
+                        ctx.request_repaint();
+                        return;
+                    }
+                };
+
+                let Some(device_endpoint) = discovery.device_authorization_endpoint else {
+                    sender.send(Err(ApiError::Auth {
+                        error: "unsupported_grant_type".to_string(),
+                        description: Some(
+                            "the identity provider does not support the device flow".to_string(),
+                        ),
+                    }));
+                    ctx.request_repaint();
+                    return;
+                };
+
+                let request = post_form(
+                    &device_endpoint,
+                    &[("client_id", &config.client_id), ("scope", &config.scope)],
+                );
+                let token_endpoint = discovery.token_endpoint;
+
+                fetch(&transport, request, move |response| {
+                    let result = response
+                        .and_then(parse_oauth::<DeviceAuthorizationResponse>)
+                        .map(|authorization| DeviceAuthorization {
+                            device_code: authorization.device_code,
+                            user_code: authorization.user_code,
+                            verification_uri: authorization.verification_uri,
+                            verification_uri_complete: authorization.verification_uri_complete,
+                            expires_at: Instant::now()
+                                + Duration::from_secs(authorization.expires_in),
+                            interval: Duration::from_secs(
+                                authorization.interval.unwrap_or(DEFAULT_INTERVAL),
+                            ),
+                            token_endpoint,
+                        });
+
+                    sender.send(result);
+                    ctx.request_repaint();
+                });
+            },
+        );
+
+        promise
+    }
+
+    /// Ask for the token once, the caller waits for `authorization.interval`
+    /// between two calls.
+    pub fn poll(
+        &self,
+        authorization: &DeviceAuthorization,
+    ) -> Promise<Result<PollStatus, ApiError>> {
+        let ctx = self.ctx.clone();
+        let (sender, promise) = Promise::new();
+
+        let request = post_form(
+            &authorization.token_endpoint,
+            &[
+                ("grant_type", DEVICE_CODE_GRANT_TYPE),
+                ("device_code", &authorization.device_code),
+                ("client_id", &self.config.client_id),
+            ],
+        );
+
+        fetch(&self.transport, request, move |response| {
+            let result = match response.and_then(parse_oauth::<TokenResponse>) {
+                Ok(token) => Ok(PollStatus::Granted(token.into_token())),
+                Err(ApiError::Auth { error, .. }) if error == "authorization_pending" => {
+                    Ok(PollStatus::Pending)
+                }
+                Err(ApiError::Auth { error, .. }) if error == "slow_down" => {
+                    Ok(PollStatus::SlowDown)
+                }
+                Err(e) => Err(e),
+            };
+
+            sender.send(result);
+            ctx.request_repaint();
+        });
+
+        promise
+    }
+}
+
+/// Exchange `refresh_token` for a new token.
+/// `refresh_token` is kept if the identity provider does not rotate it.
+pub(crate) fn refresh(
+    transport: &Transport,
+    config: &OidcConfig,
+    token_endpoint: &str,
+    refresh_token: String,
+    on_done: impl FnOnce(Result<Token, ApiError>) + Send + 'static,
+) {
+    let request = post_form(
+        token_endpoint,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+            ("client_id", &config.client_id),
+        ],
+    );
+
+    fetch(transport, request, move |response| {
+        on_done(
+            response
+                .and_then(parse_oauth::<TokenResponse>)
+                .map(|token| Token {
+                    refresh_token: token.refresh_token.clone().or(Some(refresh_token)),
+                    ..token.into_token()
+                }),
+        )
+    });
+}
+
+fn fetch(
+    transport: &Transport,
+    request: ehttp::Request,
+    on_done: impl FnOnce(Result<ehttp::Response, ApiError>) + Send + 'static,
+) {
+    debug!("{} {}", request.method, request.url);
+
+    transport.fetch(request, move |response| {
+        on_done(response.map_err(ApiError::Network))
+    });
+}
+
+fn post_form(url: &str, params: &[(&str, &str)]) -> ehttp::Request {
+    let body = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", form_encode(key), form_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut request = ehttp::Request::post(url, body.into_bytes());
+    request
+        .headers
+        .insert("Content-Type", "application/x-www-form-urlencoded");
+    request.headers.insert("Accept", "application/json");
+    request
+}
+
+/// Parse a response of the identity provider, a non 200 response is
+/// turned into an `ApiError::Auth` if it has an OAuth2 error body.
+fn parse_oauth<T: DeserializeOwned>(response: ehttp::Response) -> Result<T, ApiError> {
+    match response.status {
+        200 => serde_json::from_slice(&response.bytes).map_err(ApiError::Decode),
+        _ => match serde_json::from_slice::<OAuthError>(&response.bytes) {
+            Ok(error) => Err(ApiError::Auth {
+                error: error.error,
+                description: error.error_description,
+            }),
+            Err(_) => Err(ApiError::from_response(&response)),
+        },
+    }
+}
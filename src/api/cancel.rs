@@ -0,0 +1,24 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Cancels the requests of a client built with
+/// [`ApiClient::with_cancel`](super::client::ApiClient::with_cancel).
+///
+/// A cancelled request is not sent, or not retried, and its response is
+/// dropped without waking up the UI.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,498 @@
+use std::time::Duration;
+
+use eframe::egui;
+use log::{debug, warn};
+use poll_promise::Promise;
+use serde::{de::DeserializeOwned, Serialize};
+use storm_daenerys_common::types::error::CommonError;
+
+use super::auth::credentials::Credentials;
+use super::cancel::CancelToken;
+use super::conditional::{Conditional, ValidatorStore, Validators};
+use super::inspector::Inspector;
+use super::retry::{RetryCounter, RetryPolicy};
+use super::transport::Transport;
+use crate::error::apierror::ApiError;
+
+#[cfg(target_arch = "wasm32")]
+use ehttp::Mode;
+
+/// Client of the STORM backend REST API.
+///
+/// Owns the API base URL, the headers and credentials sent with every request
+/// and the egui context to wake up when a response arrives.
+/// Every endpoint goes through [`ApiClient::fetch`].
+#[derive(Clone)]
+pub struct ApiClient {
+    // egui context, repainted on each response.
+    ctx: egui::Context,
+    // API base URL, without trailing slash.
+    api_url: String,
+    // Headers sent with every request.
+    default_headers: Vec<(String, String)>,
+    // Credentials sent with every request.
+    credentials: Credentials,
+    // Sends the requests.
+    transport: Transport,
+    // Validators of the conditional GETs.
+    validators: ValidatorStore,
+    // Records the calls for the developer panel.
+    inspector: Inspector,
+    // Retries of the failed GETs.
+    retry: RetryPolicy,
+    // GETs waiting for a retry.
+    retries: RetryCounter,
+    // Time given to GET /du, `None` for the transport timeout.
+    du_timeout: Option<Duration>,
+    // Cancels the requests of this clone, if any.
+    cancel: Option<CancelToken>,
+}
+
+impl ApiClient {
+    pub fn new(ctx: &egui::Context, api_url: String) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            default_headers: vec![("Accept".to_string(), "*/*".to_string())],
+            credentials: Credentials::default(),
+            transport: Transport::default(),
+            validators: ValidatorStore::default(),
+            inspector: Inspector::default(),
+            retry: RetryPolicy::default(),
+            retries: RetryCounter::default(),
+            du_timeout: None,
+            cancel: None,
+        }
+    }
+
+    /// Retry the failed GETs with `retry`.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Give `timeout` to GET /du, computing the disk usage may be long.
+    pub fn with_du_timeout(mut self, timeout: Duration) -> Self {
+        self.du_timeout = Some(timeout);
+        self
+    }
+
+    /// Time given to GET /du.
+    pub(crate) fn du_timeout(&self) -> Duration {
+        self.du_timeout.unwrap_or(self.transport.timeout())
+    }
+
+    /// Number of requests waiting to be sent again after a failure.
+    pub fn retrying(&self) -> usize {
+        self.retries.get()
+    }
+
+    /// Send every request with `transport`.
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// A client of `api_url` with the same credentials and transport.
+    pub fn with_api_url(&self, api_url: &str) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            validators: ValidatorStore::default(),
+            retries: RetryCounter::default(),
+            ..self.clone()
+        }
+    }
+
+    /// A clone of the client whose requests are cancelled by `cancel`.
+    pub fn with_cancel(&self, cancel: &CancelToken) -> Self {
+        Self {
+            cancel: Some(cancel.clone()),
+            ..self.clone()
+        }
+    }
+
+    /// Authenticate every request with `credentials`.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    /// Forget the validators of the conditional GETs, such as when the
+    /// user changes: the next GETs fetch the full resources.
+    pub fn clear_validators(&self) {
+        self.validators.clear();
+    }
+
+    /// Recent calls of the client and its clones.
+    pub fn inspector(&self) -> &Inspector {
+        &self.inspector
+    }
+
+    /// Send conditional GETs or not, depending on the backend support.
+    /// Shared with the clones of the client.
+    pub fn set_conditional(&self, enabled: bool) {
+        self.validators.set_enabled(enabled);
+    }
+
+    /// Add a header sent with every request.
+    #[allow(dead_code)]
+    pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.default_headers
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Send a GET request to `path`.
+    pub(crate) fn get<T, F>(&self, path: &str, parse: F) -> Promise<Result<T, ApiError>>
+    where
+        T: Send + 'static,
+        F: FnOnce(ehttp::Response) -> Result<T, ApiError> + Send + 'static,
+    {
+        self.fetch("GET", path, None, Vec::new(), None, parse)
+    }
+
+    /// Send a GET request to `path`, giving up after `timeout`.
+    pub(crate) fn get_with_timeout<T, F>(
+        &self,
+        path: &str,
+        timeout: Duration,
+        parse: F,
+    ) -> Promise<Result<T, ApiError>>
+    where
+        T: Send + 'static,
+        F: FnOnce(ehttp::Response) -> Result<T, ApiError> + Send + 'static,
+    {
+        self.fetch("GET", path, None, Vec::new(), Some(timeout), parse)
+    }
+
+    /// Send a GET request to `path` with the validators of its previous
+    /// response, answering `Conditional::NotModified` on a 304.
+    /// The validators of a response are forgotten once the request is
+    /// cancelled, its response was then not applied.
+    pub(crate) fn get_conditional<T, F>(
+        &self,
+        path: &str,
+        parse: F,
+    ) -> Promise<Result<Conditional<T>, ApiError>>
+    where
+        T: Send + 'static,
+        F: FnOnce(ehttp::Response) -> Result<T, ApiError> + Send + 'static,
+    {
+        let headers = self.validators.request_headers(path);
+        let conditional = !headers.is_empty();
+        let validators = self.validators.clone();
+        let path_key = path.to_string();
+        let cancel = self.cancel.clone();
+
+        self.fetch("GET", path, None, headers, None, move |response| {
+            // Without validators a 304 is an error.
+            if conditional && response.status == 304 {
+                log_response(&response);
+                return Ok(Conditional::NotModified);
+            }
+
+            let response_validators = Validators::from_response(&response);
+            let value = parse(response)?;
+            validators.store(&path_key, response_validators, cancel);
+
+            Ok(Conditional::Modified(value))
+        })
+    }
+
+    /// Send a request to `path` with `payload` serialized as JSON.
+    pub(crate) fn send<P, T, F>(
+        &self,
+        method: &str,
+        path: &str,
+        payload: &P,
+        parse: F,
+    ) -> Promise<Result<T, ApiError>>
+    where
+        P: Serialize,
+        T: Send + 'static,
+        F: FnOnce(ehttp::Response) -> Result<T, ApiError> + Send + 'static,
+    {
+        match serde_json::to_vec(payload) {
+            Ok(body) => self.fetch(method, path, Some(body), Vec::new(), None, parse),
+            Err(e) => Promise::from_ready(Err(ApiError::Encode(e))),
+        }
+    }
+
+    /// Send a request to `path` without payload.
+    pub(crate) fn send_empty<T, F>(
+        &self,
+        method: &str,
+        path: &str,
+        parse: F,
+    ) -> Promise<Result<T, ApiError>>
+    where
+        T: Send + 'static,
+        F: FnOnce(ehttp::Response) -> Result<T, ApiError> + Send + 'static,
+    {
+        self.fetch(method, path, None, Vec::new(), None, parse)
+    }
+
+    fn fetch<T, F>(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<Vec<u8>>,
+        extra_headers: Vec<(&str, String)>,
+        timeout: Option<Duration>,
+        parse: F,
+    ) -> Promise<Result<T, ApiError>>
+    where
+        T: Send + 'static,
+        F: FnOnce(ehttp::Response) -> Result<T, ApiError> + Send + 'static,
+    {
+        let mut headers = ehttp::Headers::default();
+        for (key, value) in &self.default_headers {
+            headers.insert(key, value);
+        }
+        for (key, value) in extra_headers {
+            headers.insert(key, value);
+        }
+        if body.is_some() {
+            headers.insert("Content-Type", "application/json");
+        }
+
+        let mut request = ehttp::Request {
+            method: method.to_owned(),
+            url: format!("{}{}", self.api_url, path),
+            body: body.unwrap_or_default(),
+            headers,
+            #[cfg(target_arch = "wasm32")]
+            mode: Mode::default(),
+        };
+
+        debug!("{} {}", request.method, request.url);
+
+        // `ehttp` works both in WASM and on native.
+        // We use the `poll-promise` library to communicate with the UI thread.
+        let ctx = self.ctx.clone();
+        let (sender, promise) = Promise::new();
+
+        let cancel = self.cancel.clone().unwrap_or_default();
+        let attempts = Attempts {
+            transport: self.transport.clone(),
+            timeout: timeout.unwrap_or(self.transport.timeout()),
+            inspector: self.inspector.clone(),
+            retry: self.retry,
+            retries: self.retries.clone(),
+            cancel: cancel.clone(),
+            ctx: ctx.clone(),
+        };
+        let inspector = self.inspector.clone();
+        self.credentials
+            .with_access_token(&self.transport, move |access_token| {
+                if let Some(access_token) = access_token {
+                    request
+                        .headers
+                        .insert("Authorization", format!("Bearer {}", access_token));
+                }
+
+                attempts.send(request, 0, move |response, call| {
+                    // Kept before the response is consumed by `parse`.
+                    let recorded_response = call.and_then(|_| recorded(&response));
+
+                    // Not parsed, such as the validators of a superseded GET.
+                    let cancelled = cancel.is_cancelled();
+                    let result = if cancelled {
+                        Err(ApiError::Cancelled)
+                    } else {
+                        response.map_err(ApiError::Network).and_then(parse)
+                    };
+
+                    if let Some(call) = call {
+                        inspector.finish(call, recorded_response, result.as_ref().err());
+                    }
+
+                    sender.send(result);
+                    if !cancelled {
+                        ctx.request_repaint(); // wake up UI thread
+                    }
+                });
+            });
+
+        promise
+    }
+}
+
+/// Sends a request, again after a delay while it fails and the retry
+/// policy allows it.
+struct Attempts {
+    transport: Transport,
+    timeout: Duration,
+    inspector: Inspector,
+    retry: RetryPolicy,
+    retries: RetryCounter,
+    cancel: CancelToken,
+    ctx: egui::Context,
+}
+
+impl Attempts {
+    /// Send `request` for the retry `retry`, 0 for the first attempt, and
+    /// call `on_done` with the last response and its inspector call id.
+    fn send<F>(self, request: ehttp::Request, retry: u32, on_done: F)
+    where
+        F: FnOnce(ehttp::Result<ehttp::Response>, Option<u64>) + Send + 'static,
+    {
+        // Neither sent nor recorded.
+        if self.cancel.is_cancelled() {
+            on_done(Err("cancelled".to_string()), None);
+            return;
+        }
+
+        let call = self.inspector.start(&request);
+        let transport = self.transport.clone();
+        let next_request = request.clone();
+
+        transport.fetch_with_timeout(request, self.timeout, move |response| {
+            if self.cancel.is_cancelled()
+                || !self
+                    .retry
+                    .should_retry(&next_request.method, &response, retry)
+            {
+                on_done(response, call);
+                return;
+            }
+
+            let error = match &response {
+                Ok(response) => ApiError::from_response(response),
+                Err(e) => ApiError::Network(e.clone()),
+            };
+            let delay = self.retry.delay(retry);
+
+            warn!(
+                "{} {} failed, retry {} in {:?}: {}",
+                next_request.method,
+                next_request.url,
+                retry + 1,
+                delay,
+                error
+            );
+
+            if let Some(call) = call {
+                self.inspector
+                    .finish(call, recorded(&response), Some(&error));
+            }
+
+            // Show the retry.
+            self.ctx.request_repaint();
+            self.retries.waiting(|| std::thread::sleep(delay));
+
+            self.send(next_request, retry + 1, on_done);
+        });
+    }
+}
+
+/// Status and body of `response` for the inspector.
+fn recorded(response: &ehttp::Result<ehttp::Response>) -> Option<(u16, Vec<u8>)> {
+    let response = response.as_ref().ok()?;
+    Some((response.status, response.bytes.clone()))
+}
+
+/// Return the response body, `None` if empty or not UTF-8.
+fn body(response: &ehttp::Response) -> Option<&str> {
+    response.text().filter(|text| !text.is_empty())
+}
+
+fn log_response(response: &ehttp::Response) {
+    debug!("{:?}", response.status);
+    debug!("{:?}", response.status_text);
+    debug!("{:?}", response.text());
+}
+
+/// Parse a response without body.
+pub(crate) fn parse_empty(response: ehttp::Response) -> Result<(), ApiError> {
+    log_response(&response);
+
+    match response.status {
+        200 => Ok(()),
+        _ => Err(ApiError::from_response(&response)),
+    }
+}
+
+/// Parse a JSON response, `None` if the body is empty.
+pub(crate) fn parse_json<T: DeserializeOwned>(
+    response: ehttp::Response,
+) -> Result<Option<T>, ApiError> {
+    log_response(&response);
+
+    match response.status {
+        200 => match body(&response) {
+            Some(text_response) => match serde_json::from_str(text_response) {
+                Ok(json_response) => Ok(json_response),
+                Err(e) => Err(ApiError::Decode(e)),
+            },
+            None => Ok(None),
+        },
+        _ => Err(ApiError::from_response(&response)),
+    }
+}
+
+/// Parse a JSON response, the body is mandatory.
+/// `what` names the expected content in the error message.
+pub(crate) fn parse_json_required<T: DeserializeOwned>(
+    response: ehttp::Response,
+    what: &str,
+) -> Result<T, ApiError> {
+    log_response(&response);
+
+    match response.status {
+        200 => match body(&response) {
+            Some(text_response) => match serde_json::from_str(text_response) {
+                Ok(json_response) => Ok(json_response),
+                Err(e) => Err(ApiError::Decode(e)),
+            },
+            None => Err(ApiError::UnexpectedResponse(
+                CommonError::UnexpectedResponse(format!("empty {}", what)),
+            )),
+        },
+        _ => Err(ApiError::from_response(&response)),
+    }
+}
+
+/// Parse a plain text response, the body is mandatory.
+pub(crate) fn parse_text_required(
+    response: ehttp::Response,
+    what: &str,
+) -> Result<String, ApiError> {
+    log_response(&response);
+
+    match response.status {
+        200 => match body(&response) {
+            Some(text_response) => Ok(text_response.to_string()),
+            None => Err(ApiError::UnexpectedResponse(
+                CommonError::UnexpectedResponse(format!("empty {}", what)),
+            )),
+        },
+        _ => Err(ApiError::from_response(&response)),
+    }
+}
+
+/// Percent-encode `value` for an `application/x-www-form-urlencoded` body
+/// or a query string.
+pub(crate) fn form_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
@@ -0,0 +1,113 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use super::cancel::CancelToken;
+
+/// Result of a conditional GET.
+#[derive(Debug, PartialEq)]
+pub enum Conditional<T> {
+    // The resource changed, or was fetched for the first time.
+    Modified(T),
+    // 304: the previous response is still valid.
+    NotModified,
+}
+
+impl<T> Conditional<T> {
+    /// The new value, `None` if not modified.
+    #[allow(dead_code)]
+    pub fn modified(self) -> Option<T> {
+        match self {
+            Conditional::Modified(value) => Some(value),
+            Conditional::NotModified => None,
+        }
+    }
+}
+
+/// Conditional GET of a list, `None` for an empty body.
+pub type ConditionalList<T> = Conditional<Option<Vec<T>>>;
+
+/// `ETag` and `Last-Modified` of the last response of a resource.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    pub(crate) fn from_response(response: &ehttp::Response) -> Self {
+        Self {
+            etag: response.headers.get("ETag").map(|v| v.to_string()),
+            last_modified: response.headers.get("Last-Modified").map(|v| v.to_string()),
+        }
+    }
+}
+
+/// Validators of a path, with the token of the request that got them: a
+/// cancelled or superseded request never applied its response.
+struct Stored {
+    validators: Validators,
+    cancel: Option<CancelToken>,
+}
+
+/// Validators of the last successful conditional GET of each path, shared by
+/// the clones of the `ApiClient`.
+#[derive(Clone, Default)]
+pub(crate) struct ValidatorStore {
+    validators: Arc<Mutex<HashMap<String, Stored>>>,
+    // Set for a backend without conditional GETs.
+    disabled: Arc<AtomicBool>,
+}
+
+impl ValidatorStore {
+    /// `If-None-Match` and `If-Modified-Since` headers of a GET of `path`.
+    pub(crate) fn request_headers(&self, path: &str) -> Vec<(&'static str, String)> {
+        if self.disabled.load(Ordering::Relaxed) {
+            return Vec::new();
+        }
+
+        let mut store = self.validators.lock().unwrap();
+        let Some(Stored { validators, cancel }) = store.get(path) else {
+            return Vec::new();
+        };
+        if cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            store.remove(path);
+            return Vec::new();
+        }
+
+        let mut headers = Vec::new();
+        if let Some(etag) = &validators.etag {
+            headers.push(("If-None-Match", etag.clone()));
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            headers.push(("If-Modified-Since", last_modified.clone()));
+        }
+        headers
+    }
+
+    /// Keep the `validators` of the last successful GET of `path`, until
+    /// `cancel` is cancelled.
+    pub(crate) fn store(&self, path: &str, validators: Validators, cancel: Option<CancelToken>) {
+        let mut store = self.validators.lock().unwrap();
+        if validators == Validators::default() || self.disabled.load(Ordering::Relaxed) {
+            store.remove(path);
+        } else {
+            store.insert(path.to_string(), Stored { validators, cancel });
+        }
+    }
+
+    /// Forget every validator, the next GETs are unconditional.
+    pub(crate) fn clear(&self) {
+        self.validators.lock().unwrap().clear();
+    }
+
+    /// Send conditional GETs or not, forgetting the validators.
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.disabled.store(!enabled, Ordering::Relaxed);
+        self.clear();
+    }
+}
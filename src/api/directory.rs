@@ -1,199 +1,58 @@
-use eframe::egui;
 use log::debug;
 use poll_promise::Promise;
 
-use storm_daenerys_common::types::{
-    directory::{CreateDirectory, Directory, RenameDirectory},
-    error::CommonError,
-};
+use storm_daenerys_common::types::directory::{CreateDirectory, Directory, RenameDirectory};
 
-#[cfg(target_arch = "wasm32")]
-use ehttp::Mode;
+use crate::error::apierror::ApiError;
 
-pub fn delete_directory(
-    ctx: &egui::Context,
-    delete_directory: CreateDirectory,
-    api_url: String,
-) -> Promise<Result<(), String>> {
-    debug!("Delete directory: {:?}", &delete_directory);
+use super::client::{form_encode, parse_empty, parse_json, ApiClient};
+use super::conditional::ConditionalList;
 
-    let ctx = ctx.clone();
-    let (sender, promise) = Promise::new();
+impl ApiClient {
+    pub fn delete_directory(
+        &self,
+        delete_directory: CreateDirectory,
+    ) -> Promise<Result<(), ApiError>> {
+        debug!("Delete directory: {:?}", &delete_directory);
 
-    let request_payload = match serde_json::to_string(&delete_directory) {
-        Ok(request_payload) => request_payload,
-        Err(e) => {
-            sender.send(Err(e.to_string()));
-            return promise;
-        }
-    };
-
-    let request = ehttp::Request {
-        method: "DELETE".to_owned(),
-        url: format!("{}/directories", api_url),
-        body: request_payload.as_bytes().to_vec(),
-        headers: ehttp::Headers::new(&[("Accept", "*/*"), ("Content-Type", "application/json")]),
-        #[cfg(target_arch = "wasm32")]
-        mode: Mode::default(),
-    };
-
-    ehttp::fetch(request, move |response| {
-        let delete_directory_result = response.and_then(parse_create_rename_directory_response);
-        sender.send(delete_directory_result);
-        ctx.request_repaint(); // wake up UI thread
-    });
-
-    promise
-}
-
-pub fn rename_directory(
-    ctx: &egui::Context,
-    rename_directory: RenameDirectory,
-    api_url: String,
-) -> Promise<Result<(), String>> {
-    debug!("Rename directory: {:?}", &rename_directory);
-
-    let ctx = ctx.clone();
-    let (sender, promise) = Promise::new();
-
-    let request_payload = match serde_json::to_string(&rename_directory) {
-        Ok(request_payload) => request_payload,
-        Err(e) => {
-            sender.send(Err(e.to_string()));
-            return promise;
-        }
-    };
-
-    let request = ehttp::Request {
-        method: "PUT".to_owned(),
-        url: format!("{}/directories", api_url),
-        body: request_payload.as_bytes().to_vec(),
-        headers: ehttp::Headers::new(&[("Accept", "*/*"), ("Content-Type", "application/json")]),
-        #[cfg(target_arch = "wasm32")]
-        mode: Mode::default(),
-    };
-
-    ehttp::fetch(request, move |response| {
-        let rename_directory_result = response.and_then(parse_create_rename_directory_response);
-        sender.send(rename_directory_result);
-        ctx.request_repaint(); // wake up UI thread
-    });
-
-    promise
-}
-
-pub fn create_directory(
-    ctx: &egui::Context,
-    create_directory: CreateDirectory,
-    api_url: String,
-) -> Promise<Result<(), String>> {
-    debug!("Create directory: {:?}", &create_directory);
-
-    let ctx = ctx.clone();
-    let (sender, promise) = Promise::new();
-
-    let request_payload = match serde_json::to_string(&create_directory) {
-        Ok(request_payload) => request_payload,
-        Err(e) => {
-            sender.send(Err(e.to_string()));
-            return promise;
-        }
-    };
-
-    let request = ehttp::Request {
-        method: "POST".to_owned(),
-        url: format!("{}/directories", api_url),
-        body: request_payload.as_bytes().to_vec(),
-        headers: ehttp::Headers::new(&[("Accept", "*/*"), ("Content-Type", "application/json")]),
-        #[cfg(target_arch = "wasm32")]
-        mode: Mode::default(),
-    };
-
-    ehttp::fetch(request, move |response| {
-        let create_directory_result = response.and_then(parse_create_rename_directory_response);
-        sender.send(create_directory_result);
-        ctx.request_repaint(); // wake up UI thread
-    });
-
-    promise
-}
+        self.send("DELETE", "/directories", &delete_directory, parse_empty)
+    }
 
-pub fn get_root_directories(
-    ctx: &egui::Context,
-    api_url: String,
-) -> Promise<Result<Option<Vec<Directory>>, String>> {
-    debug!("Get directory list.");
+    pub fn rename_directory(
+        &self,
+        rename_directory: RenameDirectory,
+    ) -> Promise<Result<(), ApiError>> {
+        debug!("Rename directory: {:?}", &rename_directory);
 
-    // Begin download.
-    // We download the image using `ehttp`, a library that works both in WASM and on native.
-    // We use the `poll-promise` library to communicate with the UI thread.
-    let ctx = ctx.clone();
-    let (sender, promise) = Promise::new();
-    let request = ehttp::Request::get(format!("{}/directories", api_url));
+        self.send("PUT", "/directories", &rename_directory, parse_empty)
+    }
 
-    ehttp::fetch(request, move |response| {
-        let folders = response.and_then(parse_get_directories_response);
-        sender.send(folders);
-        ctx.request_repaint(); // wake up UI thread
-    });
+    pub fn create_directory(
+        &self,
+        create_directory: CreateDirectory,
+    ) -> Promise<Result<(), ApiError>> {
+        debug!("Create directory: {:?}", &create_directory);
 
-    promise
-}
-
-fn parse_get_directories_response(
-    response: ehttp::Response,
-) -> Result<Option<Vec<Directory>>, String> {
-    let status = &response.status;
-    let status_text = &response.status_text;
-    let maybe_text_response = response.text();
+        self.send("POST", "/directories", &create_directory, parse_empty)
+    }
 
-    debug!("{:?}", status);
-    debug!("{:?}", status_text);
-    debug!("{:?}", maybe_text_response);
+    pub fn get_root_directories(&self) -> Promise<Result<ConditionalList<Directory>, ApiError>> {
+        debug!("Get directory list.");
 
-    match status {
-        200 => match maybe_text_response {
-            Some(text_response) => match serde_json::from_str(text_response) {
-                Ok(json_response) => Ok(json_response),
-                Err(e) => Err(e.to_string()),
-            },
-            None => Ok(None),
-        },
-        _ => match maybe_text_response {
-            Some(text_response) => {
-                let common_error: CommonError =
-                    match serde_json::from_str::<CommonError>(text_response) {
-                        Ok(common_error) => common_error,
-                        Err(e) => CommonError::InternalServerError(e.to_string()),
-                    };
-                Err(common_error.to_string())
-            }
-            None => Err(status.to_string()),
-        },
+        self.get_conditional("/directories", parse_json)
     }
-}
-
-fn parse_create_rename_directory_response(response: ehttp::Response) -> Result<(), String> {
-    let status = &response.status;
-    let status_text = &response.status_text;
-    let maybe_text_response = response.text();
 
-    debug!("{:?}", status);
-    debug!("{:?}", status_text);
-    debug!("{:?}", maybe_text_response);
+    /// Subdirectories of the directory `path`, named by their path from the
+    /// STORM root such as `project-a/data/raw`.
+    pub fn get_subdirectories(&self, path: &str) -> Promise<Result<Vec<Directory>, ApiError>> {
+        debug!("Get subdirectories of {}.", path);
 
-    match status {
-        200 => Ok(()),
-        _ => match maybe_text_response {
-            Some(text_response) => {
-                let common_error: CommonError =
-                    match serde_json::from_str::<CommonError>(text_response) {
-                        Ok(common_error) => common_error,
-                        Err(e) => CommonError::InternalServerError(e.to_string()),
-                    };
-                Err(common_error.to_string())
-            }
-            None => Err(status.to_string()),
-        },
+        self.get(
+            &format!("/subdirectories?path={}", form_encode(path)),
+            |response| {
+                // An empty body means no subdirectory.
+                parse_json(response).map(Option::unwrap_or_default)
+            },
+        )
     }
 }
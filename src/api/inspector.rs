@@ -0,0 +1,260 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::error::apierror::ApiError;
+
+// Calls kept by default, the oldest ones are dropped.
+pub const DEFAULT_CAPACITY: usize = 200;
+// Bodies are truncated to this many bytes.
+const MAX_BODY_LEN: usize = 64 * 1024;
+// Header, query parameter and JSON field names whose values are hidden.
+const SECRET_NAMES: [&str; 8] = [
+    "authorization",
+    "cookie",
+    "password",
+    "secret",
+    "token",
+    "access_token",
+    "refresh_token",
+    "client_secret",
+];
+const REDACTED: &str = "<redacted>";
+
+/// An API call recorded by the [`Inspector`], secrets redacted.
+#[derive(Debug, Clone)]
+pub struct CallRecord {
+    pub id: u64,
+    pub method: String,
+    pub url: String,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: Option<String>,
+    // Set once the call is over.
+    pub latency: Option<Duration>,
+    // `None` while in flight or if no response was received.
+    pub status: Option<u16>,
+    pub response_body: Option<String>,
+    // Error returned to the caller, if any.
+    pub error: Option<String>,
+    started_at: Instant,
+}
+
+impl CallRecord {
+    pub fn is_pending(&self) -> bool {
+        self.latency.is_none()
+    }
+
+    /// The call as a curl command, the access token read from the
+    /// `STORM_DAENERYS_AUTH_TOKEN` environment variable.
+    pub fn curl(&self) -> String {
+        let mut command = format!("curl -X {} {}", self.method, shell_quote(&self.url));
+
+        for (key, value) in &self.request_headers {
+            if key.eq_ignore_ascii_case("authorization") {
+                command.push_str(" -H \"Authorization: Bearer $STORM_DAENERYS_AUTH_TOKEN\"");
+            } else {
+                command.push_str(&format!(
+                    " -H {}",
+                    shell_quote(&format!("{}: {}", key, value))
+                ));
+            }
+        }
+
+        if let Some(body) = &self.request_body {
+            command.push_str(&format!(" --data-raw {}", shell_quote(body)));
+        }
+
+        command
+    }
+}
+
+#[derive(Default)]
+struct Calls {
+    enabled: bool,
+    next_id: u64,
+    records: VecDeque<CallRecord>,
+}
+
+/// Recent API calls, shared by the clones of the `ApiClient`.
+///
+/// Nothing is recorded until enabled, such as when the developer panel
+/// is shown.
+#[derive(Clone)]
+pub struct Inspector {
+    capacity: usize,
+    calls: Arc<Mutex<Calls>>,
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Inspector {
+    /// An inspector keeping the last `capacity` calls.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            calls: Arc::new(Mutex::new(Calls::default())),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.calls.lock().unwrap().enabled = enabled;
+    }
+
+    /// Recorded calls, the most recent last.
+    pub fn calls(&self) -> Vec<CallRecord> {
+        self.calls.lock().unwrap().records.iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.calls.lock().unwrap().records.clear();
+    }
+
+    /// Record the start of `request`, returns its id if enabled.
+    pub(crate) fn start(&self, request: &ehttp::Request) -> Option<u64> {
+        let mut calls = self.calls.lock().unwrap();
+        if !calls.enabled {
+            return None;
+        }
+
+        let id = calls.next_id;
+        calls.next_id += 1;
+
+        let record = CallRecord {
+            id,
+            method: request.method.clone(),
+            url: redact_url(&request.url),
+            request_headers: request
+                .headers
+                .headers
+                .iter()
+                .map(|(key, value)| (key.clone(), redact_header(key, value)))
+                .collect(),
+            request_body: body_text(&request.body),
+            latency: None,
+            status: None,
+            response_body: None,
+            error: None,
+            started_at: Instant::now(),
+        };
+
+        calls.records.push_back(record);
+        while calls.records.len() > self.capacity {
+            calls.records.pop_front();
+        }
+
+        Some(id)
+    }
+
+    /// Record the end of the call `id`.
+    pub(crate) fn finish(
+        &self,
+        id: u64,
+        response: Option<(u16, Vec<u8>)>,
+        error: Option<&ApiError>,
+    ) {
+        let mut calls = self.calls.lock().unwrap();
+
+        // Dropped meanwhile, or cleared.
+        let Some(record) = calls.records.iter_mut().find(|record| record.id == id) else {
+            return;
+        };
+
+        record.latency = Some(record.started_at.elapsed());
+        if let Some((status, bytes)) = response {
+            record.status = Some(status);
+            record.response_body = body_text(&bytes);
+        }
+        record.error = error.map(|e| e.to_string());
+    }
+}
+
+/// `bytes` as redacted text, `None` if empty.
+fn body_text(bytes: &[u8]) -> Option<String> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(bytes);
+
+    let text = match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => text.into_owned(),
+    };
+
+    Some(truncate(text))
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_BODY_LEN {
+        let mut end = MAX_BODY_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push('…');
+    }
+    text
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_NAMES.contains(&name.as_str())
+}
+
+fn redact_header(key: &str, value: &str) -> String {
+    if !is_secret(key) {
+        return value.to_string();
+    }
+
+    // Keep the scheme, such as `Bearer`.
+    match value.split_once(' ') {
+        Some((scheme, _)) => format!("{} {}", scheme, REDACTED),
+        None => REDACTED.to_string(),
+    }
+}
+
+fn redact_url(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+
+    let query: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((key, _)) if is_secret(key) => format!("{}={}", key, REDACTED),
+            _ => param.to_string(),
+        })
+        .collect();
+
+    format!("{}?{}", base, query.join("&"))
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_secret(key) {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => (),
+    }
+}
+
+/// `text` in single quotes for a POSIX shell.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
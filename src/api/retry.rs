@@ -0,0 +1,106 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(8);
+
+/// Retries of the idempotent requests failing on a network error or a
+/// 502, 503 or 504 response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    // Retries after the first attempt, 0 to never retry.
+    pub max_retries: u32,
+    // Delay before the first retry, doubled for each next one.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    #[allow(dead_code)]
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before the retry `retry`, starting at 0, between half and the
+    /// whole exponential delay so that clients do not retry together.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+
+        delay.mul_f64(0.5 + jitter() / 2.0)
+    }
+
+    /// True if a request of `method` failing with `response` should be
+    /// sent again after `retries` retries.
+    pub(crate) fn should_retry(
+        &self,
+        method: &str,
+        response: &ehttp::Result<ehttp::Response>,
+        retries: u32,
+    ) -> bool {
+        // Sending twice a POST or a DELETE may apply it twice.
+        let idempotent = matches!(method, "GET" | "HEAD");
+
+        let failed = match response {
+            Err(_) => true,
+            Ok(response) => matches!(response.status, 502..=504),
+        };
+
+        // No way to wait before retrying in the browser.
+        let can_wait = cfg!(not(target_arch = "wasm32"));
+
+        can_wait && idempotent && failed && retries < self.max_retries
+    }
+}
+
+/// Number of requests waiting for a retry, shared by the clones of the
+/// `ApiClient`.
+#[derive(Clone, Default)]
+pub(crate) struct RetryCounter {
+    waiting: Arc<AtomicUsize>,
+}
+
+impl RetryCounter {
+    pub(crate) fn get(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed)
+    }
+
+    /// Count a request waiting during `wait`.
+    pub(crate) fn waiting<T>(&self, wait: impl FnOnce() -> T) -> T {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let result = wait();
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        result
+    }
+}
+
+/// Random number in [0, 1), good enough to spread the retries.
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
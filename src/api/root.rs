@@ -1,103 +1,37 @@
-use eframe::egui;
 use log::debug;
 use poll_promise::Promise;
-use storm_daenerys_common::types::{config::Config, error::CommonError};
+use storm_daenerys_common::types::config::Config;
 
-pub fn get_config(ctx: &egui::Context, api_url: String) -> Promise<Result<Config, String>> {
-    debug!("Get config.");
+use crate::error::apierror::ApiError;
 
-    // Begin download.
-    // We download the image using `ehttp`, a library that works both in WASM and on native.
-    // We use the `poll-promise` library to communicate with the UI thread.
-    let ctx = ctx.clone();
-    let (sender, promise) = Promise::new();
-    let request = ehttp::Request::get(format!("{}/config", api_url));
+use super::client::{parse_json_required, parse_text_required, ApiClient};
+use super::version::BackendVersion;
 
-    ehttp::fetch(request, move |response| {
-        let config = response.and_then(parse_get_config_response);
-        sender.send(config);
-        ctx.request_repaint(); // wake up UI thread
-    });
+impl ApiClient {
+    pub fn get_config(&self) -> Promise<Result<Config, ApiError>> {
+        debug!("Get config.");
 
-    promise
-}
-
-pub fn get_du(ctx: &egui::Context, api_url: String) -> Promise<Result<Option<String>, String>> {
-    debug!("Get du.");
-
-    // Begin download.
-    // We download the image using `ehttp`, a library that works both in WASM and on native.
-    // We use the `poll-promise` library to communicate with the UI thread.
-    let ctx = ctx.clone();
-    let (sender, promise) = Promise::new();
-    let request = ehttp::Request::get(format!("{}/du", api_url));
-
-    ehttp::fetch(request, move |response| {
-        let du = response.and_then(parse_get_du_response);
-        sender.send(du);
-        ctx.request_repaint(); // wake up UI thread
-    });
-
-    promise
-}
-
-fn parse_get_config_response(response: ehttp::Response) -> Result<Config, String> {
-    let status = &response.status;
-    let status_text = &response.status_text;
-    let maybe_text_response = response.text();
-
-    debug!("{:?}", status);
-    debug!("{:?}", status_text);
-    debug!("{:?}", maybe_text_response);
+        // TODO: check Config
+        self.get("/config", |response| {
+            parse_json_required(response, "config")
+        })
+    }
 
-    // TODO: check Config
+    /// Version of the backend, `None` for a backend without this endpoint.
+    pub fn get_version(&self) -> Promise<Result<Option<BackendVersion>, ApiError>> {
+        debug!("Get version.");
 
-    match status {
-        200 => match maybe_text_response {
-            Some(text_response) => match serde_json::from_str(text_response) {
-                Ok(json_response) => Ok(json_response),
-                Err(e) => Err(e.to_string()),
-            },
-            None => Err(CommonError::UnexpectedResponse("empty config".to_string()).to_string()),
-        },
-        _ => match maybe_text_response {
-            Some(text_response) => {
-                let common_error: CommonError =
-                    match serde_json::from_str::<CommonError>(text_response) {
-                        Ok(common_error) => common_error,
-                        Err(e) => CommonError::InternalServerError(e.to_string()),
-                    };
-                Err(common_error.to_string())
-            }
-            None => Err(status.to_string()),
-        },
+        self.get("/version", |response| match response.status {
+            404 | 405 => Ok(None),
+            _ => parse_json_required(response, "version").map(Some),
+        })
     }
-}
-
-fn parse_get_du_response(response: ehttp::Response) -> Result<Option<String>, String> {
-    let status = &response.status;
-    let status_text = &response.status_text;
-    let maybe_text_response = response.text();
 
-    debug!("{:?}", status);
-    debug!("{:?}", status_text);
-    debug!("{:?}", maybe_text_response);
+    pub fn get_du(&self) -> Promise<Result<Option<String>, ApiError>> {
+        debug!("Get du.");
 
-    match status {
-        200 => match maybe_text_response {
-            Some(text_response) => Ok(Some(text_response.to_string())),
-            None => Err(CommonError::UnexpectedResponse("empty du".to_string()).to_string()),
-        },
-        _ => match maybe_text_response {
-            Some(text_response) => {
-                let common_error: CommonError =
-                    match serde_json::from_str::<CommonError>(text_response) {
-                        Ok(common_error) => common_error,
-                        Err(e) => CommonError::InternalServerError(e.to_string()),
-                    };
-                Err(common_error.to_string())
-            }
-            None => Err(status.to_string()),
-        },
+        self.get_with_timeout("/du", self.du_timeout(), |response| {
+            parse_text_required(response, "du").map(Some)
+        })
     }
 }
@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    DigitallySignedStruct, SignatureScheme,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::tlserror::TlsError;
+
+/// Trust settings of the connections to the backend and the identity provider.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TlsConfig {
+    // PEM bundle of CAs trusted in addition to the bundled Mozilla roots.
+    pub ca_bundle: Option<PathBuf>,
+    // PEM client certificate chain and private key, for mutual TLS.
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    // Do not verify the server certificate, for development only.
+    pub insecure: bool,
+}
+
+impl TlsConfig {
+    /// True if nothing differs from the `ehttp` defaults.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// One line summary of the trust settings.
+    pub fn summary(&self) -> String {
+        let trust = if self.insecure {
+            "certificates not verified".to_string()
+        } else {
+            match &self.ca_bundle {
+                Some(ca_bundle) => format!("trusted CA {}", file_name(ca_bundle)),
+                None => "default CAs".to_string(),
+            }
+        };
+
+        match &self.client_cert {
+            Some(client_cert) => {
+                format!("{}, client certificate {}", trust, file_name(client_cert))
+            }
+            None => trust,
+        }
+    }
+
+    /// Build the rustls configuration of the native transport.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn client_config(&self) -> Result<Arc<rustls::ClientConfig>, TlsError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| TlsError::Config(e.to_string()))?;
+
+        let builder = if self.insecure {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoVerification(provider)))
+        } else {
+            let mut roots = rustls::RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            };
+
+            if let Some(ca_bundle) = &self.ca_bundle {
+                for certificate in load_certificates(ca_bundle)? {
+                    roots.add(certificate).map_err(|e| TlsError::Pem {
+                        path: ca_bundle.clone(),
+                        error: e.to_string(),
+                    })?;
+                }
+            }
+
+            builder.with_root_certificates(roots)
+        };
+
+        let config = match (&self.client_cert, &self.client_key) {
+            (Some(client_cert), Some(client_key)) => builder
+                .with_client_auth_cert(
+                    load_certificates(client_cert)?,
+                    load_private_key(client_key)?,
+                )
+                .map_err(|e| TlsError::Config(e.to_string()))?,
+            _ => builder.with_no_client_auth(),
+        };
+
+        Ok(Arc::new(config))
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let pem_error = |e: rustls::pki_types::pem::Error| TlsError::Pem {
+        path: path.to_path_buf(),
+        error: e.to_string(),
+    };
+
+    let certificates = CertificateDer::pem_file_iter(path)
+        .map_err(pem_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pem_error)?;
+
+    if certificates.is_empty() {
+        return Err(TlsError::NoCertificate {
+            path: path.to_path_buf(),
+        });
+    }
+
+    Ok(certificates)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, TlsError> {
+    PrivateKeyDer::from_pem_file(path).map_err(|e| match e {
+        rustls::pki_types::pem::Error::NoItemsFound => TlsError::NoPrivateKey {
+            path: path.to_path_buf(),
+        },
+        e => TlsError::Pem {
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        },
+    })
+}
+
+/// Accept any server certificate, only the handshake signatures are checked.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
@@ -0,0 +1,192 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::tls::TlsConfig;
+use crate::error::tlserror::TlsError;
+
+// Time given to a request to complete, unless set otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends the HTTP requests of the `ApiClient` and of the login flow.
+///
+/// Requests go through a `ureq` agent on native, with the custom TLS
+/// configuration if any, and through `ehttp` in the browser.
+#[derive(Clone)]
+pub struct Transport {
+    #[cfg(not(target_arch = "wasm32"))]
+    agent: ureq::Agent,
+    // Default time given to a request to complete.
+    timeout: Duration,
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            agent: ureq::AgentBuilder::new().build(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl Transport {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(tls: &TlsConfig) -> Result<Self, TlsError> {
+        if tls.is_default() {
+            return Ok(Self::default());
+        }
+
+        let agent = ureq::AgentBuilder::new()
+            .tls_config(tls.client_config()?)
+            .build();
+
+        Ok(Self {
+            agent,
+            ..Self::default()
+        })
+    }
+
+    /// The browser handles TLS, the settings are ignored.
+    #[cfg(target_arch = "wasm32")]
+    pub fn new(tls: &TlsConfig) -> Result<Self, TlsError> {
+        if !tls.is_default() {
+            log::warn!("TLS settings are ignored in the browser");
+        }
+
+        Ok(Self::default())
+    }
+
+    /// Give up the requests not completed within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Send `request` in the background and call `on_done` with the response.
+    pub(crate) fn fetch(
+        &self,
+        request: ehttp::Request,
+        on_done: impl FnOnce(ehttp::Result<ehttp::Response>) + Send + 'static,
+    ) {
+        self.fetch_with_timeout(request, self.timeout, on_done)
+    }
+
+    /// Same as `fetch`, giving up after `timeout`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn fetch_with_timeout(
+        &self,
+        request: ehttp::Request,
+        timeout: Duration,
+        on_done: impl FnOnce(ehttp::Result<ehttp::Response>) + Send + 'static,
+    ) {
+        let agent = self.agent.clone();
+        // Taken back if the thread can not be spawned.
+        let on_done = Arc::new(Mutex::new(Some(on_done)));
+        let thread_on_done = on_done.clone();
+
+        let spawned = std::thread::Builder::new()
+            .name("storm_daenerys_http".to_string())
+            .spawn(move || {
+                let response = fetch_blocking(&agent, &request, timeout);
+                if let Some(on_done) = thread_on_done.lock().unwrap().take() {
+                    on_done(response);
+                }
+            });
+
+        if let Err(e) = spawned {
+            if let Some(on_done) = on_done.lock().unwrap().take() {
+                on_done(Err(format!("can not spawn the HTTP thread: {}", e)));
+            }
+        }
+    }
+
+    /// The browser has its own timeouts, `timeout` is ignored.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn fetch_with_timeout(
+        &self,
+        request: ehttp::Request,
+        _timeout: Duration,
+        on_done: impl FnOnce(ehttp::Result<ehttp::Response>) + Send + 'static,
+    ) {
+        ehttp::fetch(request, on_done)
+    }
+}
+
+/// Same as `ehttp::fetch_blocking` with our own agent and a timeout.
+#[cfg(not(target_arch = "wasm32"))]
+fn fetch_blocking(
+    agent: &ureq::Agent,
+    request: &ehttp::Request,
+    timeout: Duration,
+) -> ehttp::Result<ehttp::Response> {
+    use std::io::Read as _;
+
+    let mut req = agent
+        .request(&request.method, &request.url)
+        .timeout(timeout);
+    for (key, value) in &request.headers {
+        req = req.set(key, value);
+    }
+
+    let response = if request.body.is_empty() {
+        req.call()
+    } else {
+        req.send_bytes(&request.body)
+    };
+
+    // Still read the body of non 2xx responses.
+    let (ok, response) = match response {
+        Ok(response) => (true, response),
+        Err(ureq::Error::Status(_, response)) => (false, response),
+        Err(ureq::Error::Transport(e)) if is_timeout(&e) => {
+            return Err(format!("timed out after {}s", timeout.as_secs_f32()))
+        }
+        Err(ureq::Error::Transport(e)) => return Err(e.to_string()),
+    };
+
+    let url = response.get_url().to_owned();
+    let status = response.status();
+    let status_text = response.status_text().to_owned();
+
+    let mut headers = ehttp::Headers::default();
+    for key in &response.headers_names() {
+        if let Some(value) = response.header(key) {
+            headers.insert(key, value);
+        }
+    }
+    headers.sort();
+
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("failed to read response body: {}", e))?;
+
+    Ok(ehttp::Response {
+        url,
+        ok,
+        status,
+        status_text,
+        headers,
+        bytes,
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_timeout(e: &ureq::Transport) -> bool {
+    use std::error::Error as _;
+
+    e.source()
+        .and_then(|source| source.downcast_ref::<std::io::Error>())
+        .is_some_and(|e| {
+            matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            )
+        })
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+// Cache file name in the cache directory.
+pub const FILE_NAME: &str = "user_displays.json";
+pub const DEFAULT_TTL: Duration = Duration::from_secs(7 * 24 * 3600);
+pub const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(3600);
+// Entries unused for this many TTLs are dropped when the file is loaded.
+const PURGE_AFTER_TTLS: u32 = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    // `None` for an unknown account.
+    display: Option<String>,
+    // Unix time of the resolution, in seconds.
+    resolved_at: u64,
+}
+
+// Content of the cache file.
+#[derive(Default, Serialize, Deserialize)]
+struct CacheFile {
+    // Backend the names were resolved from.
+    api_url: String,
+    entries: HashMap<String, Entry>,
+}
+
+/// User display names resolved so far, optionally persisted to a JSON file.
+///
+/// Entries older than their TTL are stale: they are still returned, but
+/// should be resolved again. Unknown accounts have a shorter TTL as they
+/// are usually created soon after.
+pub struct UserDisplayCache {
+    api_url: String,
+    // Cache file, `None` to keep the entries in memory only.
+    path: Option<PathBuf>,
+    ttl: Duration,
+    negative_ttl: Duration,
+    entries: HashMap<String, Entry>,
+    // Entries changed since the last save.
+    dirty: bool,
+}
+
+impl UserDisplayCache {
+    /// An empty in memory cache for the names of the `api_url` backend.
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            api_url: api_url.to_string(),
+            path: None,
+            ttl: DEFAULT_TTL,
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+            entries: HashMap::new(),
+            dirty: false,
+        }
+    }
+
+    /// Keep display names for `ttl` and unknown accounts for `negative_ttl`.
+    pub fn with_ttl(mut self, ttl: Duration, negative_ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self.negative_ttl = negative_ttl;
+        self
+    }
+
+    /// Persist the cache to `path`, loading the entries it already has.
+    /// An unreadable file is ignored, the cache starts empty.
+    pub fn with_file(mut self, path: PathBuf) -> Self {
+        match load(&path) {
+            Ok(Some(file)) if file.api_url == self.api_url => {
+                let now = now();
+                self.entries = file
+                    .entries
+                    .into_iter()
+                    .filter(|(_, entry)| {
+                        let ttl = self.entry_ttl(entry);
+                        now.saturating_sub(entry.resolved_at)
+                            < ttl.as_secs() * PURGE_AFTER_TTLS as u64
+                    })
+                    .collect();
+
+                debug!(
+                    "{} user displays loaded from {}",
+                    self.entries.len(),
+                    path.display()
+                );
+            }
+            Ok(Some(_)) => debug!("{} belongs to another backend, ignored", path.display()),
+            Ok(None) => (),
+            Err(e) => warn!("can not load user display cache {}: {}", path.display(), e),
+        }
+
+        self.path = Some(path);
+        self
+    }
+
+    /// An empty cache of the `api_url` backend with the same TTLs and file,
+    /// loading the entries the file has for it.
+    pub fn reopen(&self, api_url: &str) -> Self {
+        let cache = Self::new(api_url).with_ttl(self.ttl, self.negative_ttl);
+
+        match &self.path {
+            Some(path) => cache.with_file(path.clone()),
+            None => cache,
+        }
+    }
+
+    /// Display name of `uid`, stale or not, `Some(None)` for an unknown account.
+    pub fn get(&self, uid: &str) -> Option<&Option<String>> {
+        self.entries.get(uid).map(|entry| &entry.display)
+    }
+
+    /// True if `uid` is missing or older than its TTL.
+    pub fn is_stale(&self, uid: &str) -> bool {
+        match self.entries.get(uid) {
+            Some(entry) => {
+                now().saturating_sub(entry.resolved_at) >= self.entry_ttl(entry).as_secs()
+            }
+            None => true,
+        }
+    }
+
+    /// Time left before `uid` is stale, zero if it is, `None` if missing.
+    pub fn expires_in(&self, uid: &str) -> Option<Duration> {
+        self.entries.get(uid).map(|entry| {
+            let age = now().saturating_sub(entry.resolved_at);
+            Duration::from_secs(self.entry_ttl(entry).as_secs().saturating_sub(age))
+        })
+    }
+
+    pub fn insert(&mut self, uid: String, display: Option<String>) {
+        self.entries.insert(
+            uid,
+            Entry {
+                display,
+                resolved_at: now(),
+            },
+        );
+        self.dirty = true;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forget every entry and delete the cache file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.entries.clear();
+        self.dirty = false;
+
+        match &self.path {
+            Some(path) => match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Write the cache file if entries changed since the last save.
+    pub fn save(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = CacheFile {
+            api_url: self.api_url.clone(),
+            entries: self.entries.clone(),
+        };
+        let content = serde_json::to_vec(&file).map_err(io::Error::other)?;
+
+        // Replace the file at once, another instance may read it.
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, path)?;
+
+        debug!(
+            "{} user displays saved to {}",
+            self.entries.len(),
+            path.display()
+        );
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    fn entry_ttl(&self, entry: &Entry) -> Duration {
+        match entry.display {
+            Some(_) => self.ttl,
+            None => self.negative_ttl,
+        }
+    }
+}
+
+/// Content of the cache file, `None` if it does not exist.
+fn load(path: &Path) -> io::Result<Option<CacheFile>> {
+    match fs::read(path) {
+        Ok(content) => serde_json::from_slice(&content)
+            .map(Some)
+            .map_err(io::Error::other),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
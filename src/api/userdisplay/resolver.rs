@@ -0,0 +1,292 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io,
+    time::Duration,
+};
+
+use log::{debug, warn};
+use poll_promise::Promise;
+
+use super::cache::UserDisplayCache;
+use crate::api::client::ApiClient;
+use crate::error::apierror::ApiError;
+
+// Uids sent in a single GET /userdisplays request.
+const DEFAULT_CHUNK_SIZE: usize = 50;
+// Requests running at the same time.
+const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+// A request running for some uids.
+enum Lookup {
+    Batch {
+        uids: Vec<String>,
+        promise: Promise<Result<HashMap<String, Option<String>>, ApiError>>,
+    },
+    Single {
+        uid: String,
+        promise: Promise<Result<Option<String>, ApiError>>,
+    },
+}
+
+/// Resolves user display names in the background.
+///
+/// The uids requested during a frame are queued and sent by `update` in
+/// chunks to GET /userdisplays, with a limited number of requests running
+/// at the same time. A backend without this endpoint is queried uid by uid
+/// with GET /userdisplay.
+/// A failure only affects the uids of the request, their error is kept
+/// until `clear_errors`.
+/// Stale cache entries are shown while they are resolved again, the cache
+/// is saved once every queued uid is resolved.
+pub struct UserDisplayResolver {
+    client: ApiClient,
+    chunk_size: usize,
+    max_in_flight: usize,
+    // Resolved display names.
+    cache: UserDisplayCache,
+    // Error of the uids that could not be resolved.
+    errors: HashMap<String, String>,
+    // Uids waiting to be sent in a batch.
+    batch_queue: VecDeque<String>,
+    // Uids waiting to be sent alone.
+    single_queue: VecDeque<String>,
+    // Queued or in flight uids.
+    pending: HashSet<String>,
+    in_flight: Vec<Lookup>,
+    // Cleared once the backend answered it has no batch endpoint.
+    batch_supported: bool,
+}
+
+impl UserDisplayResolver {
+    pub fn new(client: ApiClient) -> Self {
+        Self {
+            cache: UserDisplayCache::new(client.api_url()),
+            client,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            errors: HashMap::new(),
+            batch_queue: VecDeque::new(),
+            single_queue: VecDeque::new(),
+            pending: HashSet::new(),
+            in_flight: Vec::new(),
+            batch_supported: true,
+        }
+    }
+
+    /// Send at most `chunk_size` uids per request and `max_in_flight`
+    /// requests at the same time.
+    pub fn with_limits(mut self, chunk_size: usize, max_in_flight: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Start from `cache`, such as a persisted one.
+    pub fn with_cache(mut self, cache: UserDisplayCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Resolve the names of another backend with `client`, dropping the
+    /// names, errors and requests of the current one.
+    pub fn switch_backend(&mut self, client: ApiClient) {
+        if let Err(e) = self.cache.save() {
+            warn!("can not save the user display cache: {}", e);
+        }
+
+        *self = Self::new(client.clone())
+            .with_limits(self.chunk_size, self.max_in_flight)
+            .with_cache(self.cache.reopen(client.api_url()));
+    }
+
+    /// Use GET /userdisplays or not, depending on the backend support.
+    pub fn set_batch_supported(&mut self, supported: bool) {
+        self.batch_supported = supported;
+
+        if !supported {
+            self.single_queue.extend(self.batch_queue.drain(..));
+        }
+    }
+
+    /// Display name of `uid` if resolved, `Some(None)` for an unknown account.
+    pub fn get(&self, uid: &str) -> Option<&Option<String>> {
+        self.cache.get(uid)
+    }
+
+    /// Time left before the display name of `uid` is stale, `None` if
+    /// unknown.
+    pub fn expires_in(&self, uid: &str) -> Option<Duration> {
+        self.cache.expires_in(uid)
+    }
+
+    /// Error of the last resolution of `uid`, if it failed.
+    pub fn error(&self, uid: &str) -> Option<&str> {
+        self.errors.get(uid).map(|e| e.as_str())
+    }
+
+    /// Queue `uid` unless it is resolved and not stale, pending or failed.
+    pub fn request(&mut self, uid: &str) {
+        if !self.cache.is_stale(uid) || self.errors.contains_key(uid) || self.pending.contains(uid)
+        {
+            return;
+        }
+
+        self.pending.insert(uid.to_string());
+
+        if self.batch_supported {
+            self.batch_queue.push_back(uid.to_string());
+        } else {
+            self.single_queue.push_back(uid.to_string());
+        }
+    }
+
+    /// Forget the failures, the failed uids are requested again on demand.
+    pub fn clear_errors(&mut self) {
+        self.errors.clear();
+    }
+
+    /// Forget every display name, including the persisted ones.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.errors.clear();
+        self.cache.flush()
+    }
+
+    /// Number of display names known, stale or not.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// True if `uid` is queued or in flight.
+    pub fn is_pending(&self, uid: &str) -> bool {
+        self.pending.contains(uid)
+    }
+
+    /// True if nothing is queued nor in flight.
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Number of requests in flight.
+    #[allow(dead_code)]
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Collect the finished requests and send the queued uids.
+    /// Returns true if display names were resolved.
+    pub fn update(&mut self) -> bool {
+        let mut resolved = false;
+
+        for lookup in std::mem::take(&mut self.in_flight) {
+            match lookup {
+                Lookup::Batch { uids, promise } => match promise.try_take() {
+                    Ok(result) => resolved |= self.batch_done(uids, result),
+                    Err(promise) => self.in_flight.push(Lookup::Batch { uids, promise }),
+                },
+                Lookup::Single { uid, promise } => match promise.try_take() {
+                    Ok(result) => resolved |= self.single_done(uid, result),
+                    Err(promise) => self.in_flight.push(Lookup::Single { uid, promise }),
+                },
+            }
+        }
+
+        self.send();
+
+        if self.pending.is_empty() {
+            if let Err(e) = self.cache.save() {
+                warn!("can not save the user display cache: {}", e);
+            }
+        }
+
+        resolved
+    }
+
+    fn send(&mut self) {
+        while self.in_flight.len() < self.max_in_flight {
+            // Uids retried alone first, they come from failed batches.
+            if let Some(uid) = self.single_queue.pop_front() {
+                let promise = self.client.get_user_display(uid.clone());
+                self.in_flight.push(Lookup::Single { uid, promise });
+            } else if !self.batch_queue.is_empty() {
+                let count = self.chunk_size.min(self.batch_queue.len());
+                let uids: Vec<String> = self.batch_queue.drain(..count).collect();
+
+                debug!("resolving {} user displays", uids.len());
+
+                let promise = self.client.get_user_displays(&uids);
+                self.in_flight.push(Lookup::Batch { uids, promise });
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn batch_done(
+        &mut self,
+        uids: Vec<String>,
+        result: Result<HashMap<String, Option<String>>, ApiError>,
+    ) -> bool {
+        match result {
+            Ok(mut displays) => {
+                for uid in uids {
+                    // A uid missing from the response is unknown.
+                    let display = displays.remove(&uid).flatten();
+                    self.pending.remove(&uid);
+                    self.cache.insert(uid, display);
+                }
+                true
+            }
+            Err(e) => {
+                if matches!(
+                    e,
+                    ApiError::Http {
+                        status: 404 | 405,
+                        ..
+                    }
+                ) {
+                    if self.batch_supported {
+                        debug!("no batch user display endpoint, resolving uid by uid");
+                    }
+                    self.batch_supported = false;
+                    self.single_queue.extend(self.batch_queue.drain(..));
+                } else {
+                    warn!("user displays resolution failed: {}", e);
+                }
+
+                // Retry alone, so that a single faulty uid does not fail
+                // the whole chunk.
+                if uids.len() == 1 && self.batch_supported {
+                    self.fail(uids[0].clone(), &e);
+                } else {
+                    self.single_queue.extend(uids);
+                }
+                false
+            }
+        }
+    }
+
+    fn single_done(&mut self, uid: String, result: Result<Option<String>, ApiError>) -> bool {
+        match result {
+            Ok(display) => {
+                self.pending.remove(&uid);
+                self.cache.insert(uid, display);
+                true
+            }
+            Err(e) => {
+                warn!("user display resolution of {} failed: {}", uid, e);
+                self.fail(uid, &e);
+                false
+            }
+        }
+    }
+
+    fn fail(&mut self, uid: String, e: &ApiError) {
+        self.pending.remove(&uid);
+        self.errors.insert(uid, e.to_string());
+    }
+}
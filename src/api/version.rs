@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+// Protocol versions this application can talk.
+// The protocol 1 is the one of the backends without GET /version.
+pub const PROTOCOL_MIN: u32 = 1;
+pub const PROTOCOL_MAX: u32 = 3;
+
+/// Answer of GET /version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackendVersion {
+    // Backend release, shown to the user.
+    pub version: String,
+    // API version, increased each time the shared types change.
+    pub protocol: u32,
+}
+
+/// Features depending on the backend protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    // GET /userdisplays, resolving several display names at once.
+    BatchUserDisplays,
+    // ETag and Last-Modified on the directory and group lists.
+    ConditionalGet,
+    // GET /subdirectories, browsing below the root directories.
+    Subdirectories,
+    // GET /gid, the gid of the groups added to an ACL.
+    GroupGids,
+    // GET and POST /defaultacls, the ACLs inherited by new files.
+    DefaultAcls,
+    // POST /acls/recursive and GET /acljobs, the ACLs applied below a
+    // directory.
+    RecursiveAcls,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 6] = [
+        Feature::BatchUserDisplays,
+        Feature::ConditionalGet,
+        Feature::Subdirectories,
+        Feature::GroupGids,
+        Feature::DefaultAcls,
+        Feature::RecursiveAcls,
+    ];
+
+    /// First protocol with the feature.
+    pub fn min_protocol(self) -> u32 {
+        match self {
+            Feature::BatchUserDisplays => 2,
+            Feature::ConditionalGet => 2,
+            Feature::Subdirectories => 3,
+            Feature::GroupGids => 3,
+            Feature::DefaultAcls => 3,
+            Feature::RecursiveAcls => 3,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::BatchUserDisplays => "batch user display resolution",
+            Feature::ConditionalGet => "conditional list refresh",
+            Feature::Subdirectories => "subdirectory browsing",
+            Feature::GroupGids => "group gid resolution",
+            Feature::DefaultAcls => "default ACL edition",
+            Feature::RecursiveAcls => "recursive ACL application",
+        }
+    }
+}
+
+/// How well the backend matches this application.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Compatibility {
+    // Every feature is available.
+    Full,
+    // Usable, without some features.
+    Degraded(Vec<Feature>),
+    // The shared types differ, nothing can be trusted.
+    Incompatible,
+}
+
+/// Protocol of the backend compared with the range of this application.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Negotiation {
+    // `None` for a backend without GET /version.
+    pub backend: Option<BackendVersion>,
+    pub compatibility: Compatibility,
+}
+
+impl Negotiation {
+    pub fn new(backend: Option<BackendVersion>) -> Self {
+        let protocol = backend
+            .as_ref()
+            .map(|backend| backend.protocol)
+            .unwrap_or(PROTOCOL_MIN);
+
+        let compatibility = if !(PROTOCOL_MIN..=PROTOCOL_MAX).contains(&protocol) {
+            Compatibility::Incompatible
+        } else {
+            let missing: Vec<Feature> = Feature::ALL
+                .into_iter()
+                .filter(|feature| feature.min_protocol() > protocol)
+                .collect();
+
+            if missing.is_empty() {
+                Compatibility::Full
+            } else {
+                Compatibility::Degraded(missing)
+            }
+        };
+
+        Self {
+            backend,
+            compatibility,
+        }
+    }
+
+    /// Protocol of the backend, the oldest one if it does not tell.
+    pub fn protocol(&self) -> u32 {
+        self.backend
+            .as_ref()
+            .map(|backend| backend.protocol)
+            .unwrap_or(PROTOCOL_MIN)
+    }
+
+    pub fn supports(&self, feature: Feature) -> bool {
+        match &self.compatibility {
+            Compatibility::Full => true,
+            Compatibility::Degraded(missing) => !missing.contains(&feature),
+            Compatibility::Incompatible => false,
+        }
+    }
+
+    pub fn is_incompatible(&self) -> bool {
+        self.compatibility == Compatibility::Incompatible
+    }
+
+    /// Backend release, or a placeholder if it does not tell.
+    pub fn backend_version(&self) -> &str {
+        self.backend
+            .as_ref()
+            .map(|backend| backend.version.as_str())
+            .unwrap_or("unknown version")
+    }
+
+    /// Explanation shown to the user, `None` if fully compatible.
+    pub fn message(&self) -> Option<String> {
+        match &self.compatibility {
+            Compatibility::Full => None,
+            Compatibility::Degraded(missing) => Some(format!(
+                "backend {} (protocol {}) is older than this application, disabled: {}",
+                self.backend_version(),
+                self.protocol(),
+                missing
+                    .iter()
+                    .map(|feature| feature.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            Compatibility::Incompatible => Some(format!(
+                "backend {} speaks protocol {}, this application supports {} to {}: \
+                 upgrade the {}, changes are disabled",
+                self.backend_version(),
+                self.protocol(),
+                PROTOCOL_MIN,
+                PROTOCOL_MAX,
+                if self.protocol() > PROTOCOL_MAX {
+                    "application"
+                } else {
+                    "backend"
+                }
+            )),
+        }
+    }
+}
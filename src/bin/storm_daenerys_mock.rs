@@ -0,0 +1,49 @@
+use std::{env, path::PathBuf, process::ExitCode};
+
+use log::error;
+use storm_daenerys_front::mock::{fixture::Fixture, server::MockServer};
+
+const USAGE: &str = "usage: storm_daenerys_mock [--listen ADDR] [--fixture FILE.json]";
+
+fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    // Parse command line.
+    let mut listen = "127.0.0.1:3000".to_string();
+    let mut fixture_path: Option<PathBuf> = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--listen", Some(value)) => listen = value,
+            ("--fixture", Some(value)) => fixture_path = Some(PathBuf::from(value)),
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    // Load fixture.
+    let fixture = match fixture_path {
+        Some(path) => match Fixture::from_file(&path) {
+            Ok(fixture) => fixture,
+            Err(e) => {
+                error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => Fixture::default(),
+    };
+
+    match MockServer::start(&listen, fixture) {
+        Ok(server) => {
+            server.wait();
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            error!("can not listen on {}: {}", listen, e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -23,3 +23,8 @@ pub const AF_WARNING_CODE: char = '\u{f071}';
 pub const AF_QUOTA_CODE: char = '\u{51}';
 pub const AF_EYE_CODE: char = '\u{f06e}';
 pub const AF_TOGGLE_OFF_CODE: char = '\u{f204}';
+pub const AF_BUG_CODE: char = '\u{f188}';
+pub const AF_EXPAND_CODE: char = '\u{f054}';
+pub const AF_COLLAPSE_CODE: char = '\u{f078}';
+pub const AF_COPY_CODE: char = '\u{f0c5}';
+pub const AF_TEMPLATE_CODE: char = '\u{f15c}';
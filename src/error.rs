@@ -1 +1,4 @@
-pub mod apperror;
\ No newline at end of file
+pub mod apierror;
+pub mod apperror;
+pub mod settingserror;
+pub mod tlserror;
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use storm_daenerys_common::types::error::CommonError;
+
+/// Broad category of an API error, used by the UI to decide how to react.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    // The backend could not be reached.
+    Network,
+    // 401: the user must authenticate again.
+    Unauthorized,
+    // 403: the user is not allowed to perform the action.
+    Forbidden,
+    // 404: the resource does not exist.
+    NotFound,
+    // 409: the resource already exists or was modified.
+    Conflict,
+    // 5xx: the backend failed.
+    Server,
+    // Any other non 200 status.
+    Client,
+    // The request or the response could not be (de)serialized.
+    Protocol,
+    // The request was cancelled or superseded by a newer one.
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    // Transport failure: connection refused, DNS, TLS...
+    Network(String),
+    // Cancelled before its response was handled.
+    Cancelled,
+    // The request payload could not be serialized.
+    Encode(serde_json::Error),
+    // The response body could not be deserialized.
+    Decode(serde_json::Error),
+    // A 200 response without the expected content.
+    UnexpectedResponse(CommonError),
+    // OAuth2 error returned by the identity provider, such as `access_denied`.
+    Auth {
+        error: String,
+        description: Option<String>,
+    },
+    // A non 200 response.
+    // `error` is the `CommonError` sent by the backend, if the body could be parsed.
+    // `body` is the raw body otherwise.
+    Http {
+        status: u16,
+        status_text: String,
+        error: Option<CommonError>,
+        body: Option<String>,
+    },
+}
+
+impl ApiError {
+    /// Build the error of a non 200 response from its `CommonError` body.
+    pub fn from_response(response: &ehttp::Response) -> Self {
+        let body = response
+            .text()
+            .filter(|text| !text.is_empty())
+            .map(|text| text.to_string());
+
+        let error = body
+            .as_ref()
+            .and_then(|text| serde_json::from_str::<CommonError>(text).ok());
+
+        ApiError::Http {
+            status: response.status,
+            status_text: response.status_text.clone(),
+            body: if error.is_none() { body } else { None },
+            error,
+        }
+    }
+
+    pub fn kind(&self) -> ApiErrorKind {
+        match self {
+            ApiError::Network(_) => ApiErrorKind::Network,
+            ApiError::Cancelled => ApiErrorKind::Cancelled,
+            ApiError::Auth { .. } => ApiErrorKind::Unauthorized,
+            ApiError::Encode(_) | ApiError::Decode(_) | ApiError::UnexpectedResponse(_) => {
+                ApiErrorKind::Protocol
+            }
+            ApiError::Http { status, .. } => match status {
+                401 => ApiErrorKind::Unauthorized,
+                403 => ApiErrorKind::Forbidden,
+                404 => ApiErrorKind::NotFound,
+                409 => ApiErrorKind::Conflict,
+                500..=599 => ApiErrorKind::Server,
+                _ => ApiErrorKind::Client,
+            },
+        }
+    }
+
+    /// True if the backend could not be reached, directly or through a proxy.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(
+            self,
+            ApiError::Network(_)
+                | ApiError::Http {
+                    status: 502..=504,
+                    ..
+                }
+        )
+    }
+
+    /// Short advice shown to the user next to the error.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.kind() {
+            ApiErrorKind::Network => Some("the backend is unreachable, check your connection"),
+            ApiErrorKind::Unauthorized => Some("your session has expired, please log in again"),
+            ApiErrorKind::Forbidden => Some("you are not allowed to do this"),
+            ApiErrorKind::NotFound => Some("it may have been deleted, try reloading"),
+            ApiErrorKind::Conflict => Some("it already exists or was modified, try reloading"),
+            ApiErrorKind::Server => Some("the backend failed, retry later"),
+            ApiErrorKind::Protocol => Some("the backend version may not match this application"),
+            ApiErrorKind::Client | ApiErrorKind::Cancelled => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::Network(e) => write!(f, "network error: {}", e),
+            ApiError::Cancelled => write!(f, "request cancelled"),
+            ApiError::Encode(e) => write!(f, "can not encode request: {}", e),
+            ApiError::Decode(e) => write!(f, "can not decode response: {}", e),
+            ApiError::UnexpectedResponse(e) => write!(f, "{}", e),
+            ApiError::Auth { error, description } => match description {
+                Some(description) => {
+                    write!(f, "authentication failed: {} ({})", error, description)
+                }
+                None => write!(f, "authentication failed: {}", error),
+            },
+            ApiError::Http {
+                status,
+                status_text,
+                error,
+                body,
+            } => match (error, body) {
+                (Some(error), _) => write!(f, "{} {}: {}", status, status_text, error),
+                (None, Some(body)) => write!(f, "{} {}: {}", status, status_text, body),
+                (None, None) => write!(f, "{} {}", status, status_text),
+            },
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::Encode(e) | ApiError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use std::fmt;
+use std::{error::Error, fmt};
+
+use super::apierror::ApiError;
 
 #[allow(dead_code)]
 pub enum AppError {
@@ -7,6 +9,23 @@ pub enum AppError {
     ChannelReceiveError,
     ChannelSendError,
     InternalError(String),
+    Api(ApiError),
+}
+
+impl AppError {
+    /// The API error at the origin of this error, if any.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            AppError::Api(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ApiError> for AppError {
+    fn from(e: ApiError) -> Self {
+        AppError::Api(e)
+    }
 }
 
 // Implement std::fmt::Display for AppError
@@ -14,17 +33,51 @@ impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             AppError::TestError => write!(f, "just a test error, nothing anormal"),
-            AppError::ChannelClosed => write!(f, "channel closed"),
-            AppError::ChannelReceiveError => write!(f, "channel receive error"),
-            AppError::ChannelSendError => write!(f, "channel send error"),
+            AppError::ChannelClosed => write!(
+                f,
+                "background worker stopped, the lists are not reloaded in the background anymore"
+            ),
+            AppError::ChannelReceiveError => {
+                write!(f, "can not receive a message from the background worker")
+            }
+            AppError::ChannelSendError => {
+                write!(f, "can not send a message to the background worker")
+            }
             AppError::InternalError(e) => write!(f, "error: {}", e),
+            AppError::Api(e) => write!(f, "{}", e),
         }
     }
 }
 
 // Implement std::fmt::Debug for AppError
+// Print the variant and the chain of its sources.
 impl fmt::Debug for AppError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{{ file: {}, line: {} }}", file!(), line!()) // programmer-facing output
+        match self {
+            AppError::TestError => write!(f, "TestError")?,
+            AppError::ChannelClosed => write!(f, "ChannelClosed")?,
+            AppError::ChannelReceiveError => write!(f, "ChannelReceiveError")?,
+            AppError::ChannelSendError => write!(f, "ChannelSendError")?,
+            AppError::InternalError(e) => write!(f, "InternalError({:?})", e)?,
+            AppError::Api(e) => write!(f, "Api({:?})", e)?,
+        }
+
+        // The wrapped error is already printed, from its own source on.
+        let mut source = self.source().and_then(Error::source);
+        while let Some(e) = source {
+            write!(f, "\n  caused by: {}", e)?;
+            source = e.source();
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Api(e) => Some(e),
+            _ => None,
+        }
     }
 }
@@ -0,0 +1,56 @@
+use std::{fmt, io, path::PathBuf};
+
+/// Error raised at startup when the settings can not be loaded.
+#[derive(Debug)]
+pub enum SettingsError {
+    // The configuration file could not be read.
+    Read {
+        path: PathBuf,
+        error: io::Error,
+    },
+    // The configuration file is not valid TOML or has unknown keys.
+    Parse {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    // A setting has an invalid value.
+    Invalid {
+        name: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Read { path, error } => write!(
+                f,
+                "can not read configuration file {}: {}",
+                path.display(),
+                error
+            ),
+            SettingsError::Parse { path, error } => write!(
+                f,
+                "invalid configuration file {}: {}",
+                path.display(),
+                error
+            ),
+            SettingsError::Invalid {
+                name,
+                value,
+                reason,
+            } => write!(f, "invalid {} \"{}\": {}", name, value, reason),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SettingsError::Read { error, .. } => Some(error),
+            SettingsError::Parse { error, .. } => Some(error),
+            SettingsError::Invalid { .. } => None,
+        }
+    }
+}
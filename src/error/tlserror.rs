@@ -0,0 +1,33 @@
+use std::{fmt, path::PathBuf};
+
+/// Error raised at startup when the TLS configuration can not be built.
+#[derive(Debug)]
+pub enum TlsError {
+    // A PEM file could not be read or parsed.
+    Pem { path: PathBuf, error: String },
+    // The PEM file has no certificate.
+    NoCertificate { path: PathBuf },
+    // The PEM file has no private key.
+    NoPrivateKey { path: PathBuf },
+    // rustls rejected the configuration, such as a key not matching its certificate.
+    Config(String),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TlsError::Pem { path, error } => {
+                write!(f, "can not load {}: {}", path.display(), error)
+            }
+            TlsError::NoCertificate { path } => {
+                write!(f, "no certificate found in {}", path.display())
+            }
+            TlsError::NoPrivateKey { path } => {
+                write!(f, "no private key found in {}", path.display())
+            }
+            TlsError::Config(e) => write!(f, "invalid TLS configuration: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
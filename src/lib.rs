@@ -1,5 +1,13 @@
-mod api;
+pub mod acl;
+pub mod acltemplate;
+pub mod api;
 mod defines;
-mod error;
+pub mod error;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod settings;
+pub mod snapshot;
+pub mod store;
+pub mod tasks;
 pub mod ui;
-mod worker;
+pub mod worker;
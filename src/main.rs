@@ -1,30 +1,65 @@
+mod acl;
+mod acltemplate;
 mod api;
 mod defines;
 mod error;
+mod settings;
+mod snapshot;
+mod store;
+mod tasks;
 mod ui;
 mod worker;
+use api::transport::Transport;
 use eframe::egui;
-use log::info;
-use std::env;
+use log::{info, warn};
+use settings::Settings;
+use std::{env, process::ExitCode};
 use ui::daenerys::DaenerysApp;
 
-fn main() -> Result<(), eframe::Error> {
-    env_logger::init();
+fn main() -> ExitCode {
+    // Load settings, before the logger as they set its level.
+    let settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut logger = env_logger::Builder::from_default_env();
+    if let Some(log_level) = settings.log_level {
+        logger.filter_level(log_level);
+    }
+    logger.init();
+
+    // HTTP transport with the TLS settings.
+    let transport = match Transport::new(&settings.tls) {
+        Ok(transport) => transport.with_timeout(settings.request_timeout),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    if settings.tls.insecure {
+        warn!("TLS certificates are not verified, do not use in production");
+    }
 
     // Get application version.
     let app_version = env!("CARGO_PKG_VERSION");
     info!("app_version: {app_version}");
+    info!("settings: {:?}", settings);
 
     // Set window options.
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([1024.0, 768.0]),
+        viewport: egui::ViewportBuilder::default().with_inner_size(settings.window_size),
         ..Default::default()
     };
 
     info!("Creating app.");
 
     // Create GUI.
-    eframe::run_native(
+    let result = eframe::run_native(
         "STORM Daenerys (Mésocentre UCA)",
         options,
         Box::new(|cc| {
@@ -32,9 +67,18 @@ fn main() -> Result<(), eframe::Error> {
 
             Ok(Box::new(DaenerysApp::new(
                 cc,
-                "http://localhost:3000".to_string(),
+                settings,
+                transport,
                 app_version.to_string(),
             )))
         }),
-    )
+    );
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
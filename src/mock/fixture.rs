@@ -0,0 +1,55 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::Deserialize;
+use storm_daenerys_common::types::{
+    acl::AclEntry, config::Config, directory::Directory, group::Group, user::User,
+};
+
+use crate::api::acljob::AclJobStatus;
+
+// Fixture used when none is given.
+const DEFAULT_FIXTURE: &str = include_str!("fixture.json");
+
+/// Data served by the mock backend.
+#[derive(Deserialize)]
+pub struct Fixture {
+    // Returned by GET /config.
+    pub config: Config,
+    // Returned by GET /du.
+    pub du: String,
+    // Root directories and the ones below, named by their path.
+    pub directories: Vec<Directory>,
+    // STORM groups.
+    pub groups: Vec<Group>,
+    // Returned by GET /gid, by group cn.
+    #[serde(default)]
+    pub gids: HashMap<String, u32>,
+    // Default ACLs by directory name, none if missing.
+    #[serde(default)]
+    pub default_acls: HashMap<String, Vec<AclEntry>>,
+    // Paths the recursive ACL application fails on.
+    #[serde(default)]
+    pub acl_failures: Vec<String>,
+    // Users known by the directory service.
+    pub users: Vec<User>,
+    // Recursive ACL jobs by id, processing one path per status request.
+    #[serde(skip)]
+    pub acl_jobs: HashMap<String, AclJobStatus>,
+}
+
+impl Fixture {
+    /// Load a fixture from a JSON file.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("can not read {}: {}", path.display(), e))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| format!("invalid fixture {}: {}", path.display(), e))
+    }
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        serde_json::from_str(DEFAULT_FIXTURE).expect("invalid embedded fixture")
+    }
+}
@@ -0,0 +1,588 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use log::{debug, info};
+use serde::{de::DeserializeOwned, Serialize};
+use storm_daenerys_common::types::{
+    acl::SetAcl,
+    directory::{CreateDirectory, Directory, RenameDirectory},
+    error::CommonError,
+    group::{AddDelUserToGroup, Group},
+    quota::SetQuota,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use super::fixture::Fixture;
+use crate::api::{
+    acljob::{AclJob, AclJobFailure, AclJobState, AclJobStatus, RecursiveAcl},
+    version::{BackendVersion, PROTOCOL_MAX},
+};
+
+type MockResponse = Response<std::io::Cursor<Vec<u8>>>;
+
+/// In-memory STORM backend serving the REST API used by the front.
+///
+/// The server runs in its own thread and stops when dropped.
+pub struct MockServer {
+    server: Arc<Server>,
+    addr: SocketAddr,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Start serving `fixture` on `addr`, use port 0 for a random port.
+    pub fn start(addr: &str, fixture: Fixture) -> Result<Self, String> {
+        let server = Arc::new(Server::http(addr).map_err(|e| e.to_string())?);
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or("mock server is not listening on an IP address")?;
+
+        info!("Mock backend listening on {}.", addr);
+
+        let state = Mutex::new(fixture);
+        let thread_server = server.clone();
+        let handle = thread::spawn(move || {
+            for request in thread_server.incoming_requests() {
+                handle_request(&state, request);
+            }
+        });
+
+        Ok(Self {
+            server,
+            addr,
+            handle: Some(handle),
+        })
+    }
+
+    /// Base URL of the API.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Block until the server stops.
+    pub fn wait(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_request(state: &Mutex<Fixture>, mut request: Request) {
+    let mut body = String::new();
+    let response = match request.as_reader().read_to_string(&mut body) {
+        Ok(_) => {
+            let if_none_match = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("If-None-Match"))
+                .map(|h| h.value.to_string());
+
+            let mut fixture = state.lock().unwrap();
+            route(
+                &mut fixture,
+                request.method(),
+                request.url(),
+                &body,
+                if_none_match.as_deref(),
+            )
+        }
+        Err(e) => error(400, e.to_string()),
+    };
+
+    debug!(
+        "{} {} -> {}",
+        request.method(),
+        request.url(),
+        response.status_code().0
+    );
+
+    let _ = request.respond(response);
+}
+
+fn route(
+    fixture: &mut Fixture,
+    method: &Method,
+    url: &str,
+    body: &str,
+    if_none_match: Option<&str>,
+) -> MockResponse {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+
+    match (method, path) {
+        (Method::Get, "/config") => json(&fixture.config),
+        (Method::Get, "/version") => json(&BackendVersion {
+            version: format!("mock {}", env!("CARGO_PKG_VERSION")),
+            protocol: PROTOCOL_MAX,
+        }),
+        (Method::Get, "/du") => Response::from_string(fixture.du.clone()),
+        (Method::Get, "/directories") => {
+            let roots: Vec<_> = fixture
+                .directories
+                .iter()
+                .filter(|d| !d.name.contains('/'))
+                .collect();
+            json_with_etag(&roots, if_none_match)
+        }
+        (Method::Get, "/subdirectories") => {
+            let path = &query_param(query, "path");
+            if !fixture.directories.iter().any(|d| d.name.eq(path)) {
+                return error(404, format!("directory {} not found", path));
+            }
+
+            let subdirectories: Vec<_> = fixture
+                .directories
+                .iter()
+                .filter(|d| d.name.rsplit_once('/').map(|(parent, _)| parent) == Some(path))
+                .collect();
+            json(&subdirectories)
+        }
+        (Method::Post, "/directories") => with_payload(body, |create: CreateDirectory| {
+            create_directory(fixture, create)
+        }),
+        (Method::Put, "/directories") => with_payload(body, |rename: RenameDirectory| {
+            rename_directory(fixture, rename)
+        }),
+        (Method::Delete, "/directories") => with_payload(body, |delete: CreateDirectory| {
+            delete_directory(fixture, delete)
+        }),
+        (Method::Post, "/acls") => with_payload(body, |set_acl: SetAcl| save_acl(fixture, set_acl)),
+        (Method::Post, "/acls/recursive") => with_payload(body, |recursive_acl: RecursiveAcl| {
+            start_recursive_acl(fixture, recursive_acl)
+        }),
+        (Method::Get, "/acljobs") => poll_acl_job(fixture, &query_param(query, "id")),
+        (Method::Delete, "/acljobs") => {
+            let id = &query_param(query, "id");
+            match fixture.acl_jobs.get_mut(id) {
+                Some(acl_job) => {
+                    if acl_job.is_running() {
+                        acl_job.state = AclJobState::Cancelled;
+                    }
+                    ok()
+                }
+                None => error(404, format!("acl job {} not found", id)),
+            }
+        }
+        (Method::Get, "/defaultacls") => {
+            let name = &query_param(query, "name");
+            if !fixture.directories.iter().any(|d| d.name.eq(name)) {
+                return error(404, format!("directory {} not found", name));
+            }
+
+            json(
+                fixture
+                    .default_acls
+                    .get(name)
+                    .map_or(&[][..], Vec::as_slice),
+            )
+        }
+        (Method::Post, "/defaultacls") => {
+            with_payload(body, |set_acl: SetAcl| save_default_acl(fixture, set_acl))
+        }
+        (Method::Post, "/quota") => {
+            with_payload(body, |set_quota: SetQuota| save_quota(fixture, set_quota))
+        }
+        (Method::Get, "/groups") => json_with_etag(&fixture.groups, if_none_match),
+        (Method::Post, "/groups") => {
+            with_payload(body, |group: Group| create_group(fixture, group))
+        }
+        (Method::Patch, "/groups/user") => with_payload(body, |add: AddDelUserToGroup| {
+            add_user_to_group(fixture, add)
+        }),
+        (Method::Delete, "/groups/user") => with_payload(body, |del: AddDelUserToGroup| {
+            del_user_from_group(fixture, del)
+        }),
+        (Method::Delete, _) if path.starts_with("/groups/") => {
+            delete_group(fixture, &path["/groups/".len()..])
+        }
+        (Method::Get, "/gid") => {
+            let q = &query_param(query, "q");
+            match fixture.gids.get(q) {
+                Some(gid) => json(gid),
+                None => error(404, format!("group {} not found", q)),
+            }
+        }
+        (Method::Get, "/users") => {
+            let q = query_param(query, "q").to_lowercase();
+            let users: Vec<_> = fixture
+                .users
+                .iter()
+                .filter(|user| {
+                    user.id.to_lowercase().contains(&q) || user.display.to_lowercase().contains(&q)
+                })
+                .collect();
+            json(&users)
+        }
+        (Method::Get, "/userdisplay") => {
+            let q = &query_param(query, "q");
+            let display = fixture
+                .users
+                .iter()
+                .find(|user| user.id.eq(q))
+                .map(|user| user.display.clone());
+            json(&display)
+        }
+        (Method::Get, "/userdisplays") => {
+            let uids = query_param(query, "q");
+            let displays: HashMap<&str, Option<&String>> = uids
+                .split(',')
+                .filter(|uid| !uid.is_empty())
+                .map(|uid| {
+                    let display = fixture
+                        .users
+                        .iter()
+                        .find(|user| user.id.eq(uid))
+                        .map(|user| &user.display);
+                    (uid, display)
+                })
+                .collect();
+            json(&displays)
+        }
+        _ => error(404, format!("no route for {} {}", method, path)),
+    }
+}
+
+fn create_directory(fixture: &mut Fixture, create: CreateDirectory) -> MockResponse {
+    if fixture.directories.iter().any(|d| d.name.eq(&create.name)) {
+        return error(409, format!("directory {} already exists", create.name));
+    }
+
+    let directory: Directory = match serde_json::from_value(serde_json::json!({
+        "name": create.name,
+        "acls": [],
+        "valid": true,
+        "quota": null,
+    })) {
+        Ok(directory) => directory,
+        Err(e) => return error(500, e.to_string()),
+    };
+
+    fixture.directories.push(directory);
+    ok()
+}
+
+fn rename_directory(fixture: &mut Fixture, rename: RenameDirectory) -> MockResponse {
+    if fixture
+        .directories
+        .iter()
+        .any(|d| d.name.eq(&rename.new_name))
+    {
+        return error(409, format!("directory {} already exists", rename.new_name));
+    }
+
+    match fixture
+        .directories
+        .iter_mut()
+        .find(|d| d.name.eq(&rename.name))
+    {
+        Some(directory) => directory.name.clone_from(&rename.new_name),
+        None => return error(404, format!("directory {} not found", rename.name)),
+    }
+
+    // And the directories below.
+    let prefix = format!("{}/", rename.name);
+    for directory in fixture.directories.iter_mut() {
+        if let Some(path) = directory.name.strip_prefix(&prefix) {
+            directory.name = format!("{}/{}", rename.new_name, path);
+        }
+    }
+
+    // With their default ACLs.
+    fixture.default_acls = std::mem::take(&mut fixture.default_acls)
+        .into_iter()
+        .map(|(name, acls)| {
+            let name = match name.strip_prefix(&prefix) {
+                Some(path) => format!("{}/{}", rename.new_name, path),
+                None if name == rename.name => rename.new_name.clone(),
+                None => name,
+            };
+            (name, acls)
+        })
+        .collect();
+    ok()
+}
+
+fn delete_directory(fixture: &mut Fixture, delete: CreateDirectory) -> MockResponse {
+    if !fixture.directories.iter().any(|d| d.name.eq(&delete.name)) {
+        return error(404, format!("directory {} not found", delete.name));
+    }
+
+    // And the directories below.
+    let prefix = format!("{}/", delete.name);
+    fixture
+        .directories
+        .retain(|d| d.name.ne(&delete.name) && !d.name.starts_with(&prefix));
+    fixture
+        .default_acls
+        .retain(|name, _| name.ne(&delete.name) && !name.starts_with(&prefix));
+    ok()
+}
+
+fn save_acl(fixture: &mut Fixture, set_acl: SetAcl) -> MockResponse {
+    match fixture
+        .directories
+        .iter_mut()
+        .find(|d| d.name.eq(&set_acl.name))
+    {
+        Some(directory) => {
+            directory.acls = set_acl.acls;
+            ok()
+        }
+        None => error(404, format!("directory {} not found", set_acl.name)),
+    }
+}
+
+/// Set the ACLs below the directory at once, the job only reports them as
+/// processed one status request after the other.
+fn start_recursive_acl(fixture: &mut Fixture, recursive_acl: RecursiveAcl) -> MockResponse {
+    if !fixture
+        .directories
+        .iter()
+        .any(|d| d.name.eq(&recursive_acl.name))
+    {
+        return error(404, format!("directory {} not found", recursive_acl.name));
+    }
+
+    let below = |path: &str| is_below(path, &recursive_acl.name);
+
+    let mut processed = 0;
+    for directory in fixture.directories.iter_mut().filter(|d| below(&d.name)) {
+        directory.acls.clone_from(&recursive_acl.acls);
+        if let Some(default_acls) = &recursive_acl.default_acls {
+            fixture
+                .default_acls
+                .insert(directory.name.clone(), default_acls.clone());
+        }
+        processed += 1;
+    }
+    let failed = fixture
+        .acl_failures
+        .iter()
+        .filter(|path| below(path))
+        .count();
+
+    let id = (fixture.acl_jobs.len() + 1).to_string();
+    let mut acl_job = AclJobStatus::started(AclJob { id: id.clone() }, &recursive_acl.name);
+    acl_job.total = Some(processed + failed as u64);
+    fixture.acl_jobs.insert(id.clone(), acl_job);
+
+    json(&AclJob { id })
+}
+
+/// True if `path` is the directory `name` or below it.
+fn is_below(path: &str, name: &str) -> bool {
+    path.strip_prefix(name)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+fn poll_acl_job(fixture: &mut Fixture, id: &str) -> MockResponse {
+    let Some(acl_job) = fixture.acl_jobs.get_mut(id) else {
+        return error(404, format!("acl job {} not found", id));
+    };
+
+    if acl_job.is_running() {
+        acl_job.processed += 1;
+
+        if Some(acl_job.processed) >= acl_job.total {
+            acl_job.failures = fixture
+                .acl_failures
+                .iter()
+                .filter(|path| is_below(path, &acl_job.name))
+                .map(|path| AclJobFailure {
+                    path: path.clone(),
+                    error: "Operation not permitted".to_string(),
+                })
+                .collect();
+            acl_job.state = AclJobState::Done;
+        }
+    }
+
+    json(acl_job)
+}
+
+fn save_default_acl(fixture: &mut Fixture, set_acl: SetAcl) -> MockResponse {
+    if !fixture.directories.iter().any(|d| d.name.eq(&set_acl.name)) {
+        return error(404, format!("directory {} not found", set_acl.name));
+    }
+
+    fixture.default_acls.insert(set_acl.name, set_acl.acls);
+    ok()
+}
+
+fn save_quota(fixture: &mut Fixture, set_quota: SetQuota) -> MockResponse {
+    match fixture
+        .directories
+        .iter_mut()
+        .find(|d| d.name.eq(&set_quota.name))
+    {
+        Some(directory) => {
+            directory.quota = if set_quota.quota == 0 {
+                None
+            } else {
+                Some(set_quota.quota)
+            };
+            ok()
+        }
+        None => error(404, format!("directory {} not found", set_quota.name)),
+    }
+}
+
+fn create_group(fixture: &mut Fixture, mut group: Group) -> MockResponse {
+    // The backend prefixes the group name.
+    group.cn = format!("{}-{}", fixture.config.users_dsi_api_group_prefix, group.cn);
+
+    if fixture.groups.iter().any(|g| g.cn.eq(&group.cn)) {
+        return error(409, format!("group {} already exists", group.cn));
+    }
+
+    fixture.groups.push(group);
+    ok()
+}
+
+fn delete_group(fixture: &mut Fixture, cn: &str) -> MockResponse {
+    let count = fixture.groups.len();
+    fixture.groups.retain(|g| g.cn.ne(cn));
+
+    if fixture.groups.len() == count {
+        return error(404, format!("group {} not found", cn));
+    }
+    ok()
+}
+
+fn add_user_to_group(fixture: &mut Fixture, add: AddDelUserToGroup) -> MockResponse {
+    match fixture.groups.iter_mut().find(|g| g.cn.eq(&add.group_cn)) {
+        Some(group) => {
+            let members = group.member.get_or_insert_with(Vec::new);
+            if !members.contains(&add.user_cn) {
+                members.push(add.user_cn);
+            }
+            ok()
+        }
+        None => error(404, format!("group {} not found", add.group_cn)),
+    }
+}
+
+fn del_user_from_group(fixture: &mut Fixture, del: AddDelUserToGroup) -> MockResponse {
+    match fixture.groups.iter_mut().find(|g| g.cn.eq(&del.group_cn)) {
+        Some(group) => {
+            if let Some(members) = group.member.as_mut() {
+                members.retain(|m| m.ne(&del.user_cn));
+            }
+            ok()
+        }
+        None => error(404, format!("group {} not found", del.group_cn)),
+    }
+}
+
+/// Deserialize the request payload and pass it to `handler`.
+fn with_payload<T: DeserializeOwned>(
+    body: &str,
+    handler: impl FnOnce(T) -> MockResponse,
+) -> MockResponse {
+    match serde_json::from_str(body) {
+        Ok(payload) => handler(payload),
+        Err(e) => error(400, e.to_string()),
+    }
+}
+
+/// Return the decoded value of `key` in the query string, empty if absent.
+fn query_param(query: &str, key: &str) -> String {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| k.eq(&key))
+        .map(|(_, v)| form_decode(v))
+        .unwrap_or_default()
+}
+
+/// Decode a percent-encoded query value, `+` being a space.
+fn form_decode(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+
+    while let [byte, tail @ ..] = rest {
+        match (byte, tail) {
+            (b'+', _) => bytes.push(b' '),
+            (b'%', [high, low, ..]) => {
+                match std::str::from_utf8(&[*high, *low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        rest = &tail[2..];
+                        continue;
+                    }
+                    None => bytes.push(b'%'),
+                }
+            }
+            _ => bytes.push(*byte),
+        }
+        rest = tail;
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn json<T: Serialize + ?Sized>(value: &T) -> MockResponse {
+    match serde_json::to_string(value) {
+        Ok(body) => Response::from_string(body).with_header(json_header()),
+        Err(e) => error(500, e.to_string()),
+    }
+}
+
+/// A JSON response with an ETag, 304 if the client already has it.
+fn json_with_etag<T: Serialize + ?Sized>(value: &T, if_none_match: Option<&str>) -> MockResponse {
+    let body = match serde_json::to_string(value) {
+        Ok(body) => body,
+        Err(e) => return error(500, e.to_string()),
+    };
+
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    let etag = format!("\"{:016x}\"", hasher.finish());
+    let etag_header = Header::from_bytes("ETag", etag.as_bytes()).unwrap();
+
+    if if_none_match == Some(etag.as_str()) {
+        return Response::from_string("")
+            .with_status_code(304)
+            .with_header(etag_header);
+    }
+
+    Response::from_string(body)
+        .with_header(json_header())
+        .with_header(etag_header)
+}
+
+fn ok() -> MockResponse {
+    Response::from_string("")
+}
+
+/// A `CommonError` response.
+fn error(status: u16, message: String) -> MockResponse {
+    let body =
+        serde_json::to_string(&CommonError::InternalServerError(message)).unwrap_or_default();
+
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(json_header())
+}
+
+fn json_header() -> Header {
+    Header::from_bytes("Content-Type", "application/json").unwrap()
+}
@@ -0,0 +1,480 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::{builder::BoolishValueParser, Parser};
+use log::LevelFilter;
+use serde::Deserialize;
+
+use crate::acltemplate::AclTemplate;
+use crate::api::auth::credentials::{AuthConfig, OidcConfig};
+use crate::api::retry::DEFAULT_MAX_RETRIES;
+use crate::api::tls::TlsConfig;
+use crate::api::transport::DEFAULT_TIMEOUT;
+use crate::api::userdisplay::cache::{DEFAULT_NEGATIVE_TTL, DEFAULT_TTL};
+use crate::error::settingserror::SettingsError;
+
+const DEFAULT_API_URL: &str = "http://localhost:3000";
+const DEFAULT_WINDOW_SIZE: [f32; 2] = [1024.0, 768.0];
+const MIN_WINDOW_SIZE: f32 = 200.0;
+const DEFAULT_OIDC_SCOPE: &str = "openid offline_access";
+// Computing the disk usage walks the whole storage.
+const DEFAULT_DU_TIMEOUT: Duration = Duration::from_secs(300);
+// The lists are reloaded in the background every 5 minutes.
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+// Configuration file location under the user config directory.
+const CONFIG_DIR_NAME: &str = "storm-daenerys";
+const CONFIG_FILE_NAME: &str = "config.toml";
+// Cache location under the user cache directory.
+const CACHE_DIR_NAME: &str = "storm-daenerys";
+
+/// Command line flags.
+/// Each flag can also be set with the environment variable shown in `--help`.
+#[derive(Debug, Default, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// STORM backend API URL.
+    #[arg(long, env = "STORM_DAENERYS_API_URL")]
+    pub api_url: Option<String>,
+    /// Initial window width.
+    #[arg(long, env = "STORM_DAENERYS_WINDOW_WIDTH")]
+    pub window_width: Option<f32>,
+    /// Initial window height.
+    #[arg(long, env = "STORM_DAENERYS_WINDOW_HEIGHT")]
+    pub window_height: Option<f32>,
+    /// Log level: off, error, warn, info, debug or trace. Defaults to RUST_LOG.
+    #[arg(long, env = "STORM_DAENERYS_LOG_LEVEL")]
+    pub log_level: Option<LevelFilter>,
+    /// Hide every action modifying directories and groups.
+    #[arg(
+        long,
+        env = "STORM_DAENERYS_READ_ONLY",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new(),
+    )]
+    pub read_only: Option<bool>,
+    /// Static bearer token sent to the backend.
+    #[arg(long, env = "STORM_DAENERYS_AUTH_TOKEN", hide_env_values = true)]
+    pub auth_token: Option<String>,
+    /// OIDC issuer URL, enables the device login flow.
+    #[arg(long, env = "STORM_DAENERYS_OIDC_ISSUER")]
+    pub oidc_issuer: Option<String>,
+    /// OIDC client ID, required with --oidc-issuer.
+    #[arg(long, env = "STORM_DAENERYS_OIDC_CLIENT_ID")]
+    pub oidc_client_id: Option<String>,
+    /// OIDC scopes, space separated. Defaults to "openid offline_access".
+    #[arg(long, env = "STORM_DAENERYS_OIDC_SCOPE")]
+    pub oidc_scope: Option<String>,
+    /// PEM bundle of CAs to trust in addition to the default ones.
+    #[arg(long, env = "STORM_DAENERYS_TLS_CA_BUNDLE")]
+    pub tls_ca_bundle: Option<PathBuf>,
+    /// PEM client certificate for mutual TLS, requires --tls-client-key.
+    #[arg(long, env = "STORM_DAENERYS_TLS_CLIENT_CERT")]
+    pub tls_client_cert: Option<PathBuf>,
+    /// PEM private key of the client certificate.
+    #[arg(long, env = "STORM_DAENERYS_TLS_CLIENT_KEY")]
+    pub tls_client_key: Option<PathBuf>,
+    /// Do not verify the server certificates. For development only.
+    #[arg(
+        long,
+        env = "STORM_DAENERYS_TLS_INSECURE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new(),
+    )]
+    pub tls_insecure: Option<bool>,
+    /// Directory of the user display cache, defaults to <cache dir>/storm-daenerys.
+    #[arg(long, env = "STORM_DAENERYS_CACHE_DIR")]
+    pub cache_dir: Option<PathBuf>,
+    /// Seconds a resolved user display name is kept before being revalidated.
+    #[arg(long, env = "STORM_DAENERYS_USER_DISPLAY_TTL")]
+    pub user_display_ttl: Option<u64>,
+    /// Seconds an unknown account is kept before being revalidated.
+    #[arg(long, env = "STORM_DAENERYS_USER_DISPLAY_NEGATIVE_TTL")]
+    pub user_display_negative_ttl: Option<u64>,
+    /// Seconds before a backend request is abandoned.
+    #[arg(long, env = "STORM_DAENERYS_REQUEST_TIMEOUT")]
+    pub request_timeout: Option<u64>,
+    /// Seconds before the disk usage request is abandoned.
+    #[arg(long, env = "STORM_DAENERYS_DU_TIMEOUT")]
+    pub du_timeout: Option<u64>,
+    /// Retries of a failed read request, 0 to never retry.
+    #[arg(long, env = "STORM_DAENERYS_MAX_RETRIES")]
+    pub max_retries: Option<u32>,
+    /// Seconds between two background reloads of the lists, 0 to disable.
+    #[arg(long, env = "STORM_DAENERYS_REFRESH_INTERVAL")]
+    pub refresh_interval: Option<u64>,
+    /// Configuration file, defaults to <config dir>/storm-daenerys/config.toml.
+    #[arg(long, env = "STORM_DAENERYS_CONFIG")]
+    pub config: Option<PathBuf>,
+}
+
+/// Content of the TOML configuration file, every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileSettings {
+    api_url: Option<String>,
+    window_width: Option<f32>,
+    window_height: Option<f32>,
+    log_level: Option<String>,
+    read_only: Option<bool>,
+    auth_token: Option<String>,
+    oidc_issuer: Option<String>,
+    oidc_client_id: Option<String>,
+    oidc_scope: Option<String>,
+    tls_ca_bundle: Option<PathBuf>,
+    tls_client_cert: Option<PathBuf>,
+    tls_client_key: Option<PathBuf>,
+    tls_insecure: Option<bool>,
+    cache_dir: Option<PathBuf>,
+    user_display_ttl: Option<u64>,
+    user_display_negative_ttl: Option<u64>,
+    request_timeout: Option<u64>,
+    du_timeout: Option<u64>,
+    max_retries: Option<u32>,
+    refresh_interval: Option<u64>,
+    acl_templates: Option<Vec<AclTemplate>>,
+}
+
+impl FileSettings {
+    fn from_file(path: &Path) -> Result<Self, SettingsError> {
+        let content = fs::read_to_string(path).map_err(|error| SettingsError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        toml::from_str(&content).map_err(|error| SettingsError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+}
+
+/// Application settings.
+///
+/// Each value comes from, by order of precedence, the command line,
+/// the environment, the configuration file or the default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    // STORM backend API URL.
+    pub api_url: String,
+    // Initial window size.
+    pub window_size: [f32; 2],
+    // Log level, `None` to keep the RUST_LOG configuration.
+    pub log_level: Option<LevelFilter>,
+    // Hide every action modifying directories and groups.
+    pub read_only: bool,
+    // Backend authentication.
+    pub auth: AuthConfig,
+    // Trust settings of the HTTPS connections.
+    pub tls: TlsConfig,
+    // Directory of the persistent caches, `None` to keep them in memory.
+    pub cache_dir: Option<PathBuf>,
+    // Delay before a cached user display name is revalidated.
+    pub user_display_ttl: Duration,
+    // Same for an unknown account.
+    pub user_display_negative_ttl: Duration,
+    // Delay before a backend request is abandoned.
+    pub request_timeout: Duration,
+    // Same for the disk usage, much slower.
+    pub du_timeout: Duration,
+    // Retries of a read request failing on a network error or a 502, 503 or 504.
+    pub max_retries: u32,
+    // Delay between two background reloads of the lists, zero to disable.
+    pub refresh_interval: Duration,
+    // ACL templates offered in the directory view, file only.
+    pub acl_templates: Vec<AclTemplate>,
+    // Configuration file loaded, if any.
+    pub config_file: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            window_size: DEFAULT_WINDOW_SIZE,
+            log_level: None,
+            read_only: false,
+            auth: AuthConfig::None,
+            tls: TlsConfig::default(),
+            cache_dir: default_cache_dir(),
+            user_display_ttl: DEFAULT_TTL,
+            user_display_negative_ttl: DEFAULT_NEGATIVE_TTL,
+            request_timeout: DEFAULT_TIMEOUT,
+            du_timeout: DEFAULT_DU_TIMEOUT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            acl_templates: vec![AclTemplate::project()],
+            config_file: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Load the settings from the process command line and environment.
+    /// Exits with a usage message if the command line is invalid.
+    pub fn load() -> Result<Self, SettingsError> {
+        Self::from_cli(Cli::parse())
+    }
+
+    /// Load the settings from already parsed flags.
+    ///
+    /// A configuration file given with `--config` must exist,
+    /// the default one is optional.
+    pub fn from_cli(cli: Cli) -> Result<Self, SettingsError> {
+        let config_file = match &cli.config {
+            Some(path) => Some(path.clone()),
+            None => default_config_file().filter(|path| path.exists()),
+        };
+
+        let file = match &config_file {
+            Some(path) => FileSettings::from_file(path)?,
+            None => FileSettings::default(),
+        };
+
+        let file_log_level = match file.log_level {
+            Some(log_level) => {
+                Some(
+                    log_level
+                        .parse::<LevelFilter>()
+                        .map_err(|_| SettingsError::Invalid {
+                            name: "log_level",
+                            value: log_level,
+                            reason: "expected off, error, warn, info, debug or trace",
+                        })?,
+                )
+            }
+            None => None,
+        };
+
+        let auth = match (
+            cli.auth_token.or(file.auth_token),
+            cli.oidc_issuer.or(file.oidc_issuer),
+        ) {
+            (Some(_), Some(oidc_issuer)) => {
+                return Err(SettingsError::Invalid {
+                    name: "oidc_issuer",
+                    value: oidc_issuer,
+                    reason: "can not be used together with auth_token",
+                })
+            }
+            (Some(auth_token), None) => AuthConfig::Bearer(auth_token),
+            (None, Some(oidc_issuer)) => AuthConfig::Oidc(OidcConfig {
+                issuer: oidc_issuer.trim().to_string(),
+                client_id: cli.oidc_client_id.or(file.oidc_client_id).ok_or(
+                    SettingsError::Invalid {
+                        name: "oidc_client_id",
+                        value: String::new(),
+                        reason: "required with oidc_issuer",
+                    },
+                )?,
+                scope: cli
+                    .oidc_scope
+                    .or(file.oidc_scope)
+                    .unwrap_or(DEFAULT_OIDC_SCOPE.to_string()),
+            }),
+            (None, None) => AuthConfig::None,
+        };
+
+        // Paths of the configuration file are relative to its directory.
+        let config_dir = config_file
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new(""));
+        let file_path = |path: Option<PathBuf>| path.map(|path| config_dir.join(path));
+
+        let tls = TlsConfig {
+            ca_bundle: cli.tls_ca_bundle.or(file_path(file.tls_ca_bundle)),
+            client_cert: cli.tls_client_cert.or(file_path(file.tls_client_cert)),
+            client_key: cli.tls_client_key.or(file_path(file.tls_client_key)),
+            insecure: cli.tls_insecure.or(file.tls_insecure).unwrap_or_default(),
+        };
+
+        let default = Settings::default();
+        let settings = Settings {
+            api_url: cli
+                .api_url
+                .or(file.api_url)
+                .unwrap_or(default.api_url)
+                .trim()
+                .to_string(),
+            window_size: [
+                cli.window_width
+                    .or(file.window_width)
+                    .unwrap_or(default.window_size[0]),
+                cli.window_height
+                    .or(file.window_height)
+                    .unwrap_or(default.window_size[1]),
+            ],
+            log_level: cli.log_level.or(file_log_level),
+            read_only: cli
+                .read_only
+                .or(file.read_only)
+                .unwrap_or(default.read_only),
+            auth,
+            tls,
+            cache_dir: cli
+                .cache_dir
+                .or(file_path(file.cache_dir))
+                .or(default.cache_dir),
+            user_display_ttl: cli
+                .user_display_ttl
+                .or(file.user_display_ttl)
+                .map(Duration::from_secs)
+                .unwrap_or(default.user_display_ttl),
+            user_display_negative_ttl: cli
+                .user_display_negative_ttl
+                .or(file.user_display_negative_ttl)
+                .map(Duration::from_secs)
+                .unwrap_or(default.user_display_negative_ttl),
+            request_timeout: cli
+                .request_timeout
+                .or(file.request_timeout)
+                .map(Duration::from_secs)
+                .unwrap_or(default.request_timeout),
+            du_timeout: cli
+                .du_timeout
+                .or(file.du_timeout)
+                .map(Duration::from_secs)
+                .unwrap_or(default.du_timeout),
+            max_retries: cli
+                .max_retries
+                .or(file.max_retries)
+                .unwrap_or(default.max_retries),
+            refresh_interval: cli
+                .refresh_interval
+                .or(file.refresh_interval)
+                .map(Duration::from_secs)
+                .unwrap_or(default.refresh_interval),
+            acl_templates: file.acl_templates.unwrap_or(default.acl_templates),
+            config_file,
+        };
+
+        settings.validate()?;
+
+        Ok(settings)
+    }
+
+    fn validate(&self) -> Result<(), SettingsError> {
+        validate_url("api_url", &self.api_url)?;
+
+        match &self.auth {
+            AuthConfig::None => (),
+            AuthConfig::Bearer(auth_token) => {
+                if auth_token.trim().is_empty() {
+                    return Err(SettingsError::Invalid {
+                        name: "auth_token",
+                        value: String::new(),
+                        reason: "must not be empty",
+                    });
+                }
+            }
+            AuthConfig::Oidc(oidc) => validate_url("oidc_issuer", &oidc.issuer)?,
+        }
+
+        match (&self.tls.client_cert, &self.tls.client_key) {
+            (Some(_), None) => {
+                return Err(SettingsError::Invalid {
+                    name: "tls_client_key",
+                    value: String::new(),
+                    reason: "required with tls_client_cert",
+                })
+            }
+            (None, Some(_)) => {
+                return Err(SettingsError::Invalid {
+                    name: "tls_client_cert",
+                    value: String::new(),
+                    reason: "required with tls_client_key",
+                })
+            }
+            _ => (),
+        }
+
+        for (name, value) in [
+            ("window_width", self.window_size[0]),
+            ("window_height", self.window_size[1]),
+        ] {
+            if !(value.is_finite() && value >= MIN_WINDOW_SIZE) {
+                return Err(SettingsError::Invalid {
+                    name,
+                    value: value.to_string(),
+                    reason: "must be at least 200",
+                });
+            }
+        }
+
+        for (name, value) in [
+            ("request_timeout", self.request_timeout),
+            ("du_timeout", self.du_timeout),
+        ] {
+            if value.is_zero() {
+                return Err(SettingsError::Invalid {
+                    name,
+                    value: value.as_secs().to_string(),
+                    reason: "must be at least 1 second",
+                });
+            }
+        }
+
+        for (index, template) in self.acl_templates.iter().enumerate() {
+            template
+                .validate()
+                .map_err(|reason| SettingsError::Invalid {
+                    name: "acl_templates",
+                    value: template.name.clone(),
+                    reason,
+                })?;
+
+            if self.acl_templates[..index]
+                .iter()
+                .any(|other| other.name == template.name)
+            {
+                return Err(SettingsError::Invalid {
+                    name: "acl_templates",
+                    value: template.name.clone(),
+                    reason: "duplicate template name",
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_url(name: &'static str, url: &str) -> Result<(), SettingsError> {
+    let host = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"));
+
+    if !host.is_some_and(|host| !host.is_empty() && !host.starts_with('/')) {
+        return Err(SettingsError::Invalid {
+            name,
+            value: url.to_string(),
+            reason: "expected an http:// or https:// URL",
+        });
+    }
+
+    Ok(())
+}
+
+/// Default configuration file, `None` if the platform has no config directory.
+pub fn default_config_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+}
+
+/// Directory of the reports saved by the user, `None` if the platform has
+/// neither download nor home directory.
+pub fn default_report_dir() -> Option<PathBuf> {
+    dirs::download_dir().or_else(dirs::home_dir)
+}
+
+/// Default cache directory, `None` if the platform has no cache directory.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(CACHE_DIR_NAME))
+}
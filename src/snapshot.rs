@@ -0,0 +1,91 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use storm_daenerys_common::types::{config::Config, directory::Directory, group::Group};
+
+// Snapshot file name in the cache directory.
+pub const FILE_NAME: &str = "snapshot.json";
+
+/// Last data successfully fetched from a backend, shown read-only when it is
+/// unreachable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    // Backend the data was fetched from.
+    pub api_url: String,
+    // Unix time of the snapshot, in seconds.
+    pub saved_at: u64,
+    pub config: Config,
+    pub directories: Option<Vec<Directory>>,
+    pub groups: Option<Vec<Group>>,
+    // Display names of the users of the ACLs and groups, `None` for an
+    // unknown account.
+    pub user_displays: HashMap<String, Option<String>>,
+}
+
+impl Snapshot {
+    /// A snapshot of the `api_url` backend taken now.
+    pub fn new(
+        api_url: &str,
+        config: Config,
+        directories: Option<Vec<Directory>>,
+        groups: Option<Vec<Group>>,
+        user_displays: HashMap<String, Option<String>>,
+    ) -> Self {
+        Self {
+            api_url: api_url.to_string(),
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            config,
+            directories,
+            groups,
+            user_displays,
+        }
+    }
+
+    /// The snapshot of the `api_url` backend saved in `path`, `None` if
+    /// there is none.
+    pub fn load(path: &Path, api_url: &str) -> io::Result<Option<Self>> {
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let snapshot: Snapshot = serde_json::from_slice(&content).map_err(io::Error::other)?;
+
+        Ok(Some(snapshot).filter(|snapshot| snapshot.api_url == api_url))
+    }
+
+    /// Write the snapshot to `path`, replacing the previous one.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content = serde_json::to_vec(self).map_err(io::Error::other)?;
+
+        // Replace the file at once, another instance may read it.
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Local date and time of the snapshot.
+    pub fn saved_at_label(&self) -> String {
+        DateTime::from_timestamp(self.saved_at as i64, 0)
+            .map(|date| {
+                date.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "unknown date".to_string())
+    }
+}
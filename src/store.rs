@@ -0,0 +1,264 @@
+use std::collections::{BTreeSet, HashMap};
+
+use storm_daenerys_common::types::{
+    acl::{AclEntry, Qualifier},
+    directory::Directory,
+    group::Group,
+    user::User,
+};
+
+use crate::worker::displays::UserDisplays;
+
+/// Kind of the data kept by the [`Store`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Directories,
+    Groups,
+    // Result of the last user search.
+    Users,
+    UserDisplays,
+}
+
+/// User or group of an ACL entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AclQualifier {
+    User(String),
+    Group(String),
+}
+
+impl AclQualifier {
+    /// The user or group of `acl`, `None` for the owner, mask and other
+    /// entries.
+    pub fn of(acl: &AclEntry) -> Option<Self> {
+        let cn = acl.qualifier_cn.clone()?;
+
+        match acl.qualifier {
+            Qualifier::User(_) => Some(Self::User(cn)),
+            Qualifier::Group(_) => Some(Self::Group(cn)),
+            _ => None,
+        }
+    }
+
+    /// Uid of the user or cn of the group.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::User(name) | Self::Group(name) => name,
+        }
+    }
+}
+
+/// The data loaded from the backend, indexed for the lookups of the views.
+///
+/// Each kind is `None` until loaded and after its invalidation. The views
+/// borrow the lists, the indices are rebuilt when a list is set.
+#[derive(Debug, Default)]
+pub struct Store {
+    directories: Option<Vec<Directory>>,
+    // Position in `directories` by name.
+    directory_by_name: HashMap<String, usize>,
+    // Names of the directories whose ACL has the user or group.
+    directories_by_qualifier: HashMap<AclQualifier, BTreeSet<String>>,
+    // Subdirectories listed so far, by path of their parent.
+    subdirectories: HashMap<String, Vec<Directory>>,
+    // Gid of the groups by cn, kept across the reloads.
+    gid_by_cn: HashMap<String, u32>,
+    groups: Option<Vec<Group>>,
+    // Position in `groups` by cn.
+    group_by_cn: HashMap<String, usize>,
+    users: Option<Vec<User>>,
+    // Position in `users` by uid.
+    user_by_uid: HashMap<String, usize>,
+    user_displays: UserDisplays,
+}
+
+impl Store {
+    /// Directories, in the order they were set.
+    pub fn directories(&self) -> Option<&[Directory]> {
+        self.directories.as_deref()
+    }
+
+    /// Root directory of `name`, or subdirectory of this path if its parent
+    /// was listed.
+    pub fn directory(&self, name: &str) -> Option<&Directory> {
+        match self.directory_by_name.get(name) {
+            Some(index) => self.directories.as_ref()?.get(*index),
+            None => {
+                let (parent, _) = name.rsplit_once('/')?;
+                self.subdirectories
+                    .get(parent)?
+                    .iter()
+                    .find(|directory| directory.name == name)
+            }
+        }
+    }
+
+    /// Directories whose ACL has `qualifier`, by name.
+    pub fn directories_with<'a>(
+        &'a self,
+        qualifier: &AclQualifier,
+    ) -> impl Iterator<Item = &'a Directory> + 'a {
+        self.directories_by_qualifier
+            .get(qualifier)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.directory(name))
+    }
+
+    /// Users and groups of the directory ACLs.
+    pub fn acl_qualifiers(&self) -> impl Iterator<Item = &AclQualifier> {
+        self.directories_by_qualifier.keys()
+    }
+
+    pub fn set_directories(&mut self, directories: Option<Vec<Directory>>) {
+        self.directory_by_name.clear();
+        self.directories_by_qualifier.clear();
+
+        for (index, directory) in directories.iter().flatten().enumerate() {
+            self.directory_by_name.insert(directory.name.clone(), index);
+
+            for acl in &directory.acls {
+                let Some(qualifier) = AclQualifier::of(acl) else {
+                    continue;
+                };
+
+                if let (Qualifier::Group(gid @ 1..), AclQualifier::Group(cn)) =
+                    (&acl.qualifier, &qualifier)
+                {
+                    self.gid_by_cn.insert(cn.clone(), *gid);
+                }
+
+                self.directories_by_qualifier
+                    .entry(qualifier)
+                    .or_default()
+                    .insert(directory.name.clone());
+            }
+        }
+
+        self.directories = directories;
+    }
+
+    /// Take the directories out, such as to change them before setting them
+    /// again.
+    pub fn take_directories(&mut self) -> Option<Vec<Directory>> {
+        self.directory_by_name.clear();
+        self.directories_by_qualifier.clear();
+        self.directories.take()
+    }
+
+    /// Subdirectories of the directory `path`, `None` until listed.
+    pub fn subdirectories(&self, path: &str) -> Option<&[Directory]> {
+        self.subdirectories.get(path).map(Vec::as_slice)
+    }
+
+    /// Paths of the directories whose subdirectories are listed.
+    pub fn subdirectory_parents(&self) -> impl Iterator<Item = &String> {
+        self.subdirectories.keys()
+    }
+
+    /// Set the subdirectories of `path`, `None` to forget them and the ones
+    /// below.
+    pub fn set_subdirectories(&mut self, path: &str, subdirectories: Option<Vec<Directory>>) {
+        match subdirectories {
+            Some(subdirectories) => {
+                self.subdirectories.insert(path.to_string(), subdirectories);
+            }
+            None => {
+                let prefix = format!("{}/", path);
+                self.subdirectories
+                    .retain(|parent, _| parent != path && !parent.starts_with(&prefix));
+            }
+        }
+    }
+
+    /// Take the subdirectories of `path` out, such as to change them before
+    /// setting them again.
+    pub fn take_subdirectories(&mut self, path: &str) -> Option<Vec<Directory>> {
+        self.subdirectories.remove(path)
+    }
+
+    /// Groups, in the order they were set.
+    pub fn groups(&self) -> Option<&[Group]> {
+        self.groups.as_deref()
+    }
+
+    pub fn group(&self, cn: &str) -> Option<&Group> {
+        let index = *self.group_by_cn.get(cn)?;
+        self.groups.as_ref()?.get(index)
+    }
+
+    pub fn set_groups(&mut self, groups: Option<Vec<Group>>) {
+        self.group_by_cn = index_by(groups.as_deref(), |group| &group.cn);
+        self.groups = groups;
+    }
+
+    /// Gid of the group `cn`, if known from an ACL or resolved.
+    pub fn group_gid(&self, cn: &str) -> Option<u32> {
+        self.gid_by_cn.get(cn).copied()
+    }
+
+    pub fn set_group_gid(&mut self, cn: &str, gid: u32) {
+        self.gid_by_cn.insert(cn.to_string(), gid);
+    }
+
+    /// Users of the last search.
+    pub fn users(&self) -> Option<&[User]> {
+        self.users.as_deref()
+    }
+
+    #[allow(dead_code)]
+    pub fn user(&self, uid: &str) -> Option<&User> {
+        let index = *self.user_by_uid.get(uid)?;
+        self.users.as_ref()?.get(index)
+    }
+
+    pub fn set_users(&mut self, users: Option<Vec<User>>) {
+        self.user_by_uid = index_by(users.as_deref(), |user| &user.id);
+        self.users = users;
+    }
+
+    pub fn user_displays(&self) -> &UserDisplays {
+        &self.user_displays
+    }
+
+    pub fn user_displays_mut(&mut self) -> &mut UserDisplays {
+        &mut self.user_displays
+    }
+
+    /// True if the data of `kind` is loaded.
+    #[allow(dead_code)]
+    pub fn is_loaded(&self, kind: EntityKind) -> bool {
+        match kind {
+            EntityKind::Directories => self.directories.is_some(),
+            EntityKind::Groups => self.groups.is_some(),
+            EntityKind::Users => self.users.is_some(),
+            EntityKind::UserDisplays => !self.user_displays.is_empty(),
+        }
+    }
+
+    /// Drop the data of `kind` and its indices.
+    pub fn invalidate(&mut self, kind: EntityKind) {
+        match kind {
+            EntityKind::Directories => {
+                self.set_directories(None);
+                self.subdirectories.clear();
+            }
+            EntityKind::Groups => self.set_groups(None),
+            EntityKind::Users => self.set_users(None),
+            EntityKind::UserDisplays => self.user_displays.clear(),
+        }
+    }
+
+    /// Drop everything, such as for another backend.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Position of the items of `list` by `key`.
+fn index_by<T>(list: Option<&[T]>, key: impl Fn(&T) -> &String) -> HashMap<String, usize> {
+    list.into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, item)| (key(item).clone(), index))
+        .collect()
+}
@@ -0,0 +1,311 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+use log::debug;
+use poll_promise::Promise;
+
+use crate::api::cancel::CancelToken;
+use crate::error::apierror::ApiError;
+
+pub type TaskId = u64;
+
+// What a finished task does to the state.
+type Completion<S> = Box<dyn FnOnce(&mut S)>;
+// Callback of a task, given the state and the results.
+type OnDone<S, R> = Box<dyn FnOnce(&mut S, R)>;
+
+/// Requests of a task, polled until they are all done.
+trait Job<S> {
+    /// The completion once done.
+    fn poll(&mut self) -> Option<Completion<S>>;
+    /// Done fraction, `None` if unknown.
+    fn progress(&self) -> Option<f32>;
+}
+
+/// A single request.
+struct Single<S, T: Send + 'static> {
+    promise: Option<Promise<Result<T, ApiError>>>,
+    on_done: Option<OnDone<S, Result<T, ApiError>>>,
+}
+
+impl<S: 'static, T: Send + 'static> Job<S> for Single<S, T> {
+    fn poll(&mut self) -> Option<Completion<S>> {
+        let result = match self.promise.take()?.try_take() {
+            Ok(result) => result,
+            Err(promise) => {
+                self.promise = Some(promise);
+                return None;
+            }
+        };
+
+        let on_done = self.on_done.take()?;
+        Some(Box::new(move |state: &mut S| on_done(state, result)))
+    }
+
+    fn progress(&self) -> Option<f32> {
+        None
+    }
+}
+
+/// Progress of a task whose requests are sent elsewhere, such as by the
+/// worker, shared with the sender.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    done: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+}
+
+impl Progress {
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Count one more request done.
+    pub fn advance(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Done fraction, `None` until the total is known.
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total.load(Ordering::Relaxed);
+        (total > 0).then(|| self.done.load(Ordering::Relaxed).min(total) as f32 / total as f32)
+    }
+}
+
+/// A running operation, as listed in the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskInfo {
+    pub id: TaskId,
+    pub label: String,
+    pub started_at: Instant,
+    // Done fraction, `None` if unknown.
+    pub progress: Option<f32>,
+    // The user can cancel it.
+    pub cancellable: bool,
+    // Not listed, such as the periodic checks.
+    pub background: bool,
+}
+
+impl TaskInfo {
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// An operation on the state `S`: its requests and what to do with their
+/// results.
+pub struct Task<S> {
+    label: String,
+    // Tasks of the same key supersede each other.
+    key: Option<String>,
+    cancel: Option<CancelToken>,
+    background: bool,
+    // Reported by the sender of the requests, if any.
+    progress: Option<Progress>,
+    job: Box<dyn Job<S>>,
+}
+
+impl<S: 'static> Task<S> {
+    /// A task calling `on_done` with the result of `promise`.
+    pub fn request<T, F>(label: &str, promise: Promise<Result<T, ApiError>>, on_done: F) -> Self
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut S, Result<T, ApiError>) + 'static,
+    {
+        Self::new(
+            label,
+            Single {
+                promise: Some(promise),
+                on_done: Some(Box::new(on_done)),
+            },
+        )
+    }
+
+    fn new(label: &str, job: impl Job<S> + 'static) -> Self {
+        Self {
+            label: label.to_string(),
+            key: None,
+            cancel: None,
+            background: false,
+            progress: None,
+            job: Box::new(job),
+        }
+    }
+
+    /// Supersede the running task of the same `key`.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Let the user cancel the task, cancelling the requests sent with
+    /// `cancel`.
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Do not list the task.
+    pub fn in_background(mut self) -> Self {
+        self.background = true;
+        self
+    }
+
+    /// Show `progress` instead of the one of the requests.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn progress(&self) -> Option<f32> {
+        match &self.progress {
+            Some(progress) => progress.fraction(),
+            None => self.job.progress(),
+        }
+    }
+}
+
+struct Running<S> {
+    info: TaskInfo,
+    task: Task<S>,
+}
+
+impl<S> Running<S> {
+    fn cancel(self) {
+        if let Some(cancel) = &self.task.cancel {
+            cancel.cancel();
+        }
+        debug!("task {} \"{}\" cancelled", self.info.id, self.info.label);
+    }
+}
+
+/// Operations in flight on the state `S`, polled in one place.
+pub struct TaskManager<S> {
+    next_id: TaskId,
+    tasks: Vec<Running<S>>,
+}
+
+impl<S> Default for TaskManager<S> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            tasks: Vec::new(),
+        }
+    }
+}
+
+impl<S: 'static> TaskManager<S> {
+    /// Start `task`, superseding the running task of the same key.
+    pub fn start(&mut self, task: Task<S>) -> TaskId {
+        if let Some(key) = &task.key {
+            self.remove(key);
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let info = TaskInfo {
+            id,
+            label: task.label.clone(),
+            started_at: Instant::now(),
+            progress: task.progress(),
+            cancellable: task.cancel.is_some(),
+            background: task.background,
+        };
+        debug!("task {} \"{}\" started", id, info.label);
+
+        self.tasks.push(Running { info, task });
+
+        id
+    }
+
+    /// Completions of the tasks done since the last call, to be applied
+    /// to the state in this order.
+    pub fn poll(&mut self) -> Vec<Completion<S>> {
+        let mut completions = Vec::new();
+
+        self.tasks
+            .retain_mut(|running| match running.task.job.poll() {
+                Some(completion) => {
+                    debug!(
+                        "task {} \"{}\" done in {:?}",
+                        running.info.id,
+                        running.info.label,
+                        running.info.elapsed()
+                    );
+                    completions.push(completion);
+                    false
+                }
+                None => {
+                    running.info.progress = running.task.progress();
+                    true
+                }
+            });
+
+        completions
+    }
+
+    /// Running tasks, the oldest first.
+    pub fn running(&self) -> impl Iterator<Item = &TaskInfo> {
+        self.tasks.iter().map(|running| &running.info)
+    }
+
+    /// True if a task listed to the user is running.
+    pub fn is_busy(&self) -> bool {
+        self.running().any(|info| !info.background)
+    }
+
+    /// True if the task of `key` is running.
+    pub fn is_running(&self, key: &str) -> bool {
+        self.tasks
+            .iter()
+            .any(|running| running.task.key.as_deref() == Some(key))
+    }
+
+    /// Cancel the task `id`, returns false if it can not be cancelled.
+    pub fn cancel(&mut self, id: TaskId) -> bool {
+        let Some(index) = self
+            .tasks
+            .iter()
+            .position(|running| running.info.id == id && running.info.cancellable)
+        else {
+            return false;
+        };
+
+        self.tasks.remove(index).cancel();
+        true
+    }
+
+    /// Cancel every task the user can cancel.
+    pub fn cancel_all(&mut self) {
+        let (cancelled, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|running| running.info.cancellable);
+        self.tasks = kept;
+
+        cancelled.into_iter().for_each(Running::cancel);
+    }
+
+    /// Drop the task of `key` without its completion.
+    pub fn remove(&mut self, key: &str) {
+        self.remove_where(|task_key| task_key == key);
+    }
+
+    /// Drop the tasks whose key starts with `prefix`, such as the ones of
+    /// each path of a kind of request.
+    pub fn remove_prefixed(&mut self, prefix: &str) {
+        self.remove_where(|task_key| task_key.starts_with(prefix));
+    }
+
+    fn remove_where(&mut self, matches: impl Fn(&str) -> bool) {
+        let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .partition(|running| running.task.key.as_deref().is_some_and(&matches));
+        self.tasks = kept;
+
+        removed.into_iter().for_each(Running::cancel);
+    }
+}
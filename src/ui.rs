@@ -1,3 +1,4 @@
 pub mod daenerys;
 pub mod pages;
+pub mod rows;
 pub mod state;
@@ -1,32 +1,76 @@
 use super::state::{ApplicationState, Page};
-use crate::api;
+use crate::acl::{AclKind, EXECUTE, READ, WRITE};
+use crate::acltemplate::{AclTemplate, TemplateValues};
+use crate::api::acljob::{AclJob, AclJobState, AclJobStatus, RecursiveAcl};
+use crate::api::auth::credentials::{AuthConfig, Credentials};
+use crate::api::auth::device::{DeviceAuthorization, DeviceFlow, PollStatus};
+use crate::api::cancel::CancelToken;
+use crate::api::client::ApiClient;
+use crate::api::conditional::{Conditional, ConditionalList};
+use crate::api::retry::RetryPolicy;
+use crate::api::tls::TlsConfig;
+use crate::api::transport::Transport;
+use crate::api::userdisplay::cache::{self, UserDisplayCache};
+use crate::api::userdisplay::resolver::UserDisplayResolver;
+use crate::api::version::{BackendVersion, Feature, Negotiation};
+use crate::error::apierror::ApiError;
 use crate::error::apperror::AppError;
-use crate::ui::pages::main;
-// use crate::worker::builder::Worker;
-// use crate::worker::message::{ToApp, ToWorker};
+use crate::settings::{self, Settings};
+use crate::snapshot::{self, Snapshot};
+use crate::store::{AclQualifier, EntityKind, Store};
+use crate::tasks::{Progress, Task, TaskManager};
+use crate::ui::pages::{login, main, startup};
+use crate::ui::rows::{data_key, RowCache};
+use crate::worker::builder::Worker;
+use crate::worker::handle::WorkerHandle;
+use crate::worker::message::{Job, JobReply, ToAppMessage, ToWorkerMessage};
 use eframe::{egui, CreationContext};
 use egui::Vec2;
+use log::{debug, warn};
 use poll_promise::Promise;
 use regex::Regex;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
-// use std::sync::mpsc::{self, Receiver, Sender};
+use std::path::PathBuf;
 use std::sync::Once;
-// use std::thread;
+use std::time::{Duration, Instant};
 use storm_daenerys_common::defines::{
     DIRECTORY_NAME_RE_STRING, GROUP_CN_RE_STRING, QUOTA_FORMAT_RE_STRING,
 };
-use storm_daenerys_common::types::acl::Qualifier;
+use storm_daenerys_common::types::acl::{AclEntry, Qualifier, SetAcl};
 use storm_daenerys_common::types::config::Config;
-use storm_daenerys_common::types::directory::Directory;
 use storm_daenerys_common::types::directory::Quota;
+use storm_daenerys_common::types::directory::{CreateDirectory, Directory, RenameDirectory};
 use storm_daenerys_common::types::group::Group;
-use storm_daenerys_common::types::quota::QuotaUnit;
+use storm_daenerys_common::types::quota::{QuotaUnit, SetQuota};
 use storm_daenerys_common::types::user::User;
 
 static START: Once = Once::new();
 
+// Delays between two attempts to load the configuration at startup,
+// doubled after each failure.
+const STARTUP_RETRY_MIN: Duration = Duration::from_secs(2);
+const STARTUP_RETRY_MAX: Duration = Duration::from_secs(60);
+// Delay between two attempts to reach the backend while offline.
+const ONLINE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+// Delay between two status requests of the recursive ACL job.
+const ACL_JOB_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Keys of the tasks superseding each other.
+pub const TASK_CONFIG: &str = "config";
+pub const TASK_DEVICE_LOGIN: &str = "device_login";
+const TASK_DEVICE_POLL: &str = "device_poll";
+const TASK_VERSION: &str = "version";
+const TASK_DIRECTORIES: &str = "directories";
+const TASK_GROUPS: &str = "groups";
+const TASK_USERS: &str = "users";
+const TASK_DU: &str = "du";
+const TASK_DEFAULT_ACL: &str = "default_acl";
+const TASK_ACL_JOB: &str = "acl_job";
+// Followed by the path of the directory.
+const TASK_SUBDIRECTORIES: &str = "subdirectories:";
+
 #[derive(PartialEq)]
 pub enum Action {
     Home,
@@ -37,6 +81,7 @@ pub enum Action {
     DirectoryEditQuota,
     DirectoryEditAclAddUser,
     DirectoryEditAclAddGroup,
+    DirectoryEditAclTemplate,
     DirectoryEditRename,
     // DirectoryEditDelete,
     GroupEdit,
@@ -56,6 +101,7 @@ impl Display for Action {
             Action::DirectoryEditQuota => write!(f, "directory_edit_quota"),
             Action::DirectoryEditAclAddUser => write!(f, "directory_edit_acl_add_user"),
             Action::DirectoryEditAclAddGroup => write!(f, "directory_edit_acl_add_group"),
+            Action::DirectoryEditAclTemplate => write!(f, "directory_edit_acl_template"),
             Action::GroupEdit => write!(f, "group_edit"),
             Action::GroupCreate => write!(f, "group_create"),
             Action::GroupEditDeleteConfirm => write!(f, "group_edit_delete_confirm"),
@@ -75,8 +121,23 @@ pub struct DaenerysApp {
     pub active_action: Action,
     // Application version.
     pub app_version: String,
-    // API URL.
-    pub api_url: String,
+    // STORM backend API client.
+    pub api_client: ApiClient,
+    // Hide every action modifying directories and groups.
+    pub read_only: bool,
+    // Snapshot shown read-only while the backend is unreachable.
+    pub offline: Option<Snapshot>,
+    // Snapshot file, `None` to disable the offline mode.
+    snapshot_path: Option<PathBuf>,
+    // Data changed since the last snapshot.
+    snapshot_dirty: bool,
+    // Next attempt to reach the backend while offline.
+    next_online_check: Option<Instant>,
+    // Protocol of the backend compared with the one of the application,
+    // `None` until known.
+    pub negotiation: Option<Negotiation>,
+    // Trust settings of the HTTPS connections, shown in the top panel.
+    pub tls: TlsConfig,
     // Group name regex.
     pub group_cn_re: Regex,
     // Directory name regex.
@@ -85,6 +146,8 @@ pub struct DaenerysApp {
     pub quota_format_re: Regex,
     // Central panel available size.
     pub central_panel_available_size: Vec2,
+    // Last config, kept for the snapshot.
+    pub config: Option<Config>,
     // Disk usage.
     pub du: Option<String>,
     // Quota.
@@ -99,49 +162,42 @@ pub struct DaenerysApp {
     pub group_prefix: Option<String>,
     // Root groups.
     pub root_groups: Option<Vec<String>>,
-    // Directory list.
-    pub directories: Option<Vec<Directory>>,
-    // Group list.
-    pub groups: Option<Vec<Group>>,
-    // User list.
-    pub users: Option<Vec<User>>,
-
-    // Promise returned when calling the backend GET /du endpoint.
-    pub get_du_promise: Option<Promise<Result<Option<String>, String>>>,
-    // Promise returned when calling the backend GET /config endpoint.
-    pub get_config_prefix_promise: Option<Promise<Result<Config, String>>>,
-    // Promise returned when calling the backend GET /folders endpoint.
-    pub get_directories_promise: Option<Promise<Result<Option<Vec<Directory>>, String>>>,
-    // Promise returned when calling the backend GET /groups endpoint.
-    pub get_groups_promise: Option<Promise<Result<Option<Vec<Group>>, String>>>,
-    // Promise returned when calling the backend GET /users endpoint.
-    pub get_users_promise: Option<Promise<Result<Option<Vec<User>>, String>>>,
-    // Promise returned when calling the backend POST /directories endpoint.
-    pub create_directory_promise: Option<Promise<Result<(), String>>>,
-    // Promise returned when calling the backend PUT /directories endpoint.
-    pub rename_directory_promise: Option<Promise<Result<(), String>>>,
-    // Promise returned when calling the backend DELETE /directories endpoint.
-    pub delete_directory_promise: Option<Promise<Result<(), String>>>,
-    // Promise returned when calling the backend POST /acls endpoint.
-    pub save_directory_acl_promise: Option<Promise<Result<(), String>>>,
-    // Promise returned when calling the backend POST /quota endpoint.
-    pub save_directory_quota_promise: Option<Promise<Result<(), String>>>,
-    // Promises returned when calling the backend GET /userdisplay endpoint.
-    pub get_user_display_promises: HashMap<String, Option<Promise<Result<Option<String>, String>>>>,
-    // Promises returned when calling save_group.
-    pub save_group_promises: Option<Vec<Promise<Result<(), std::string::String>>>>,
-    // Promise returned when calling the backend POST /group endpoint.
-    pub create_group_promise: Option<Promise<Result<(), String>>>,
-    // Promise return when calling the backend DELETE /groups/:cn endpoint.
-    pub delete_group_promise: Option<Promise<Result<(), String>>>,
-
-    // User display name cache.
-    pub user_display_cache: HashMap<String, Option<String>>,
-
-    // Channels for communication beetween
-    // application (GUI) and worker.
-    // pub sender: Option<Sender<ToWorker>>,
-    // receiver: Option<Receiver<ToApp>>,
+    // Directories, groups, users and their display names.
+    pub store: Store,
+    // Display names and uids of the current group members, by display name.
+    member_rows: RowCache<(String, String)>,
+    // Positions of the current directory ACL entries, by display name.
+    acl_rows: RowCache<usize>,
+    default_acl_rows: RowCache<usize>,
+    // Changed with the members of the current group, see `members_changed`.
+    member_generation: u64,
+    // Changed with the ACL entries of the current directory, see
+    // `acls_changed`.
+    acl_generation: u64,
+
+    // Operations in flight.
+    pub tasks: TaskManager<DaenerysApp>,
+
+    // OIDC device login flow, if configured.
+    pub device_flow: Option<DeviceFlow>,
+    // Device code waiting for the user to log in.
+    pub device_authorization: Option<DeviceAuthorization>,
+    // Next token request of the device login.
+    pub next_device_poll: Option<Instant>,
+    // Access token input of the login form.
+    pub login_token: String,
+
+    // Backend URL input of the startup page.
+    pub startup_api_url: String,
+    // Failed attempts to load the configuration at startup.
+    pub startup_attempts: u32,
+    // Next attempt to load the configuration at startup.
+    pub next_startup_retry: Option<Instant>,
+
+    // Background worker, `None` once stopped.
+    worker: Option<WorkerHandle>,
+    // Delay between two background reloads of the lists, `None` to disable.
+    refresh_interval: Option<Duration>,
 
     // Current error if one.
     pub current_error: Option<AppError>,
@@ -149,9 +205,33 @@ pub struct DaenerysApp {
     pub current_info: Option<String>,
     // Toggle side panels.
     pub toggle_side_panels: bool,
+    // Show the API calls, recorded only meanwhile.
+    pub show_inspector: bool,
 
     // Directory been showned/edited.
     pub current_directory: Option<Box<Directory>>,
+    // Directories of the tree whose subdirectories are shown.
+    pub expanded_directories: HashSet<String>,
+    // Default ACL of the current directory, `None` until loaded or if the
+    // backend can not tell.
+    pub current_default_acl: Option<Vec<AclEntry>>,
+    // Directory and access ACL saved while its default ACL is not: saved
+    // again, only the default ACL is sent.
+    pub saved_access_acl: Option<(String, Vec<AclEntry>)>,
+    // ACL the users and groups are added to.
+    pub edited_acl: AclKind,
+    // Groups added to the ACL whose gid is being resolved.
+    pub pending_gids: HashSet<String>,
+    // ACL templates of the settings, and the one previewed.
+    pub acl_templates: Vec<AclTemplate>,
+    pub acl_template: Option<usize>,
+    // Save the ACLs on everything below the directory too.
+    pub apply_recursively: bool,
+    // Last status of the recursive ACL job, kept once finished for its
+    // report.
+    pub acl_job: Option<AclJobStatus>,
+    // Next status request of the running job.
+    next_acl_job_poll: Option<Instant>,
     // Group been showned/edited.
     pub current_group: Option<Box<Group>>,
     // Group been showned/edited - backup before edition.
@@ -168,33 +248,23 @@ pub struct DaenerysApp {
     // Groupe name and description input of the create group form.
     pub create_group_name: String,
     pub create_group_description: String,
-
-    // Spinner? shown on API calls.
-    pub is_working: bool,
 }
 
 impl Default for DaenerysApp {
     fn default() -> Self {
         Self {
             app_version: Default::default(),
-            is_working: Default::default(),
             group_cn_re: Regex::new(GROUP_CN_RE_STRING).unwrap(),
             directory_name_re: Regex::new(DIRECTORY_NAME_RE_STRING).unwrap(),
             quota_format_re: Regex::new(QUOTA_FORMAT_RE_STRING).unwrap(),
-            directories: Default::default(),
-            groups: Default::default(),
+            store: Default::default(),
+            member_rows: Default::default(),
+            acl_rows: Default::default(),
+            default_acl_rows: Default::default(),
+            member_generation: Default::default(),
+            acl_generation: Default::default(),
             root_groups: Default::default(),
-            users: Default::default(),
-            get_directories_promise: Default::default(),
-            get_groups_promise: Default::default(),
-            get_users_promise: Default::default(),
-            get_config_prefix_promise: Default::default(),
-            save_directory_acl_promise: Default::default(),
-            save_directory_quota_promise: Default::default(),
-            save_group_promises: Default::default(),
-            create_group_promise: Default::default(),
-            create_directory_promise: Default::default(),
-            delete_group_promise: Default::default(),
+            tasks: Default::default(),
             current_error: Default::default(),
             current_info: Default::default(),
             current_group_backup: Default::default(),
@@ -203,61 +273,125 @@ impl Default for DaenerysApp {
             create_group_description: Default::default(),
             create_directory_name: Default::default(),
             current_directory: Default::default(),
+            expanded_directories: Default::default(),
+            current_default_acl: Default::default(),
+            saved_access_acl: Default::default(),
+            edited_acl: AclKind::Access,
+            pending_gids: Default::default(),
+            acl_templates: Default::default(),
+            acl_template: Default::default(),
+            apply_recursively: Default::default(),
+            acl_job: Default::default(),
+            next_acl_job_poll: Default::default(),
             current_group: Default::default(),
             admin: Default::default(),
             current_admin_restriction: Default::default(),
-            api_url: "http://localhost:3000".to_string(),
-            get_du_promise: Default::default(),
+            api_client: ApiClient::new(&egui::Context::default(), Settings::default().api_url),
+            read_only: Default::default(),
+            offline: Default::default(),
+            negotiation: Default::default(),
+            snapshot_path: Default::default(),
+            snapshot_dirty: Default::default(),
+            next_online_check: Default::default(),
+            tls: Default::default(),
+            config: Default::default(),
             du: Default::default(),
             quota: Default::default(),
             central_panel_available_size: Default::default(),
             group_prefix: Default::default(),
-            get_user_display_promises: HashMap::new(),
-            user_display_cache: HashMap::new(),
+            device_flow: Default::default(),
+            device_authorization: Default::default(),
+            next_device_poll: Default::default(),
+            login_token: Default::default(),
+            startup_api_url: Default::default(),
+            startup_attempts: Default::default(),
+            next_startup_retry: Default::default(),
             connected_user: Default::default(),
             edited_directory_quota: Default::default(),
             edited_directory_quota_unit: QuotaUnit::Megabyte,
             state: Default::default(),
-            // sender: Default::default(),
-            // receiver: Default::default(),
+            worker: Default::default(),
+            refresh_interval: Default::default(),
             active_action: Action::Home,
-            rename_directory_promise: Default::default(),
-            delete_directory_promise: Default::default(),
             toggle_side_panels: true,
+            show_inspector: Default::default(),
         }
     }
 }
 
 impl DaenerysApp {
-    pub fn new(cc: &CreationContext, api_url: String, app_version: String) -> Self {
-        // Create channels.
-        // let (app_tx, app_rx) = mpsc::channel();
-        // let (worker_tx, worker_rx) = mpsc::channel();
-
-        // dbg!("Spawning new worker.");
-
-        // Spawn a thread with a new worker.
-        // let context = cc.egui_ctx.clone();
-        // thread::spawn(move || {
-        //     Worker::new(worker_tx, app_rx, context).init();
-        // });
-
-        // dbg!("New worker spawned.");
-
+    pub fn new(
+        cc: &CreationContext,
+        settings: Settings,
+        transport: Transport,
+        app_version: String,
+    ) -> Self {
         // Load custom fonts and styles.
         setup_custom_fonts(&cc.egui_ctx);
 
         // Create application state.
         let state = ApplicationState::new();
 
+        // Device login flow, if configured.
+        let device_flow = match &settings.auth {
+            AuthConfig::Oidc(oidc) => {
+                Some(DeviceFlow::new(&cc.egui_ctx, oidc.clone()).with_transport(transport.clone()))
+            }
+            AuthConfig::None | AuthConfig::Bearer(_) => None,
+        };
+
+        let api_client = ApiClient::new(&cc.egui_ctx, settings.api_url)
+            .with_credentials(Credentials::new(settings.auth))
+            .with_transport(transport)
+            .with_retry(RetryPolicy {
+                max_retries: settings.max_retries,
+                ..Default::default()
+            })
+            .with_du_timeout(settings.du_timeout);
+
+        // User display names of the previous runs.
+        let mut user_display_cache = UserDisplayCache::new(api_client.api_url()).with_ttl(
+            settings.user_display_ttl,
+            settings.user_display_negative_ttl,
+        );
+        if let Some(cache_dir) = &settings.cache_dir {
+            user_display_cache = user_display_cache.with_file(cache_dir.join(cache::FILE_NAME));
+        }
+
+        // Data shown when the backend is unreachable.
+        let snapshot_path = settings
+            .cache_dir
+            .as_ref()
+            .map(|cache_dir| cache_dir.join(snapshot::FILE_NAME));
+
+        // Spawn a thread with a new worker, sending the background requests.
+        let context = cc.egui_ctx.clone();
+        let worker_client = api_client.clone();
+        let user_displays =
+            UserDisplayResolver::new(api_client.clone()).with_cache(user_display_cache);
+        let (worker, current_error) = match WorkerHandle::spawn(move |sender, receiver| {
+            Worker::new(sender, receiver, context, worker_client).with_user_displays(user_displays)
+        }) {
+            Ok(worker) => (Some(worker), None),
+            Err(e) => (None, Some(e)),
+        };
+
         // Create application.
         DaenerysApp {
             group_cn_re: Regex::new(GROUP_CN_RE_STRING).unwrap(),
             app_version,
-            api_url,
+            startup_api_url: api_client.api_url().to_string(),
+            api_client,
+            device_flow,
+            read_only: settings.read_only,
+            acl_templates: settings.acl_templates,
+            snapshot_path,
+            tls: settings.tls,
             state,
-            // sender: Some(app_tx),
-            // receiver: Some(worker_rx),
+            worker,
+            refresh_interval: Some(settings.refresh_interval)
+                .filter(|interval| !interval.is_zero()),
+            current_error,
             ..Default::default()
         }
     }
@@ -266,468 +400,1646 @@ impl DaenerysApp {
 impl eframe::App for DaenerysApp {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         // Check for ToApp messages.
-        // if let Some(receiver) = &self.receiver {
-        //     let maybe_message = match receiver.try_recv() {
-        //         Ok(message) => Some(message),
-        //         Err(e) => match e {
-        //             mpsc::TryRecvError::Empty => None,
-        //             mpsc::TryRecvError::Disconnected => {
-        //                 self.current_error = Some(AppError::ChannelClosed);
-        //                 None
-        //             }
-        //         },
-        //     };
-
-        //     if let Some(message) = maybe_message {
-        //         println!("received = {:?}", message);
-        //         match message.message {
-        //             ToAppMessage::Pong => self.current_info = Some("pong".to_string()),
-        //             ToAppMessage::Error(e) => self.current_error = Some(e), //FIXME: handle fatal errors
-        //         }
-        //     }
-        // }
-
-        // Get user display promises.
-        let mut user_display_promises_done: Vec<String> = vec![];
-        for (username, maybe_p) in self.get_user_display_promises.iter() {
-            if let Some(p) = maybe_p {
-                if let Some(try_display) = p.ready() {
-                    match try_display {
-                        Ok(display) => {
-                            self.user_display_cache
-                                .insert(username.to_string(), display.clone());
-                            user_display_promises_done.push(username.to_string());
-                        }
-                        Err(e) => self.current_error = Some(AppError::InternalError(e.to_string())),
-                    }
-                }
+        while let Some(worker) = &self.worker {
+            match worker.try_recv() {
+                Ok(Some(message)) => self.worker_message(message),
+                Ok(None) => break,
+                Err(e) => self.worker_stopped(e),
             }
         }
 
-        for username in user_display_promises_done.iter() {
-            self.get_user_display_promises.remove(username);
+        // Apply the operations done.
+        for completion in self.tasks.poll() {
+            completion(self);
         }
 
-        // Get du promise.
-        if let Some(p) = &self.get_du_promise {
-            match p.ready() {
-                None => (),
-                Some(try_du) => {
-                    self.is_working = false;
+        // Ask for the device login token at the interval given by the identity provider.
+        if let (Some(device_flow), Some(authorization)) =
+            (&self.device_flow, &self.device_authorization)
+        {
+            let now = Instant::now();
+            let next_device_poll = self.next_device_poll.unwrap_or(now);
+
+            if authorization.is_expired() {
+                self.device_authorization = None;
+                self.tasks.remove(TASK_DEVICE_POLL);
+                self.current_error = Some(AppError::InternalError(
+                    "the login code has expired, please retry".to_string(),
+                ));
+            } else if self.tasks.is_running(TASK_DEVICE_POLL) {
+                // Wait for the current request.
+            } else if now >= next_device_poll {
+                self.tasks.start(
+                    Task::request(
+                        "waiting for the login",
+                        device_flow.poll(authorization),
+                        Self::device_login_polled,
+                    )
+                    .with_key(TASK_DEVICE_POLL)
+                    .in_background(),
+                );
+            } else {
+                ctx.request_repaint_after(next_device_poll - now);
+            }
+        }
 
-                    match try_du {
-                        Ok(du) => {
-                            self.du.clone_from(du);
+        // Follow the recursive ACL job until finished.
+        if let (Some(acl_job), Some(next_acl_job_poll)) = (&self.acl_job, self.next_acl_job_poll) {
+            let now = Instant::now();
+
+            if self.tasks.is_running(TASK_ACL_JOB) {
+                // Wait for the current request.
+            } else if now >= next_acl_job_poll {
+                self.tasks.start(
+                    Task::request(
+                        "applying the acl recursively",
+                        self.api_client.get_acl_job(&acl_job.id),
+                        Self::acl_job_polled,
+                    )
+                    .with_key(TASK_ACL_JOB)
+                    .in_background(),
+                );
+            } else {
+                ctx.request_repaint_after(next_acl_job_poll - now);
+            }
+        }
 
-                            self.active_action = Action::DiskUsage;
-                            self.get_du_promise = None;
-                        }
-                        Err(e) => self.current_error = Some(AppError::InternalError(e.to_string())),
-                    };
-                }
+        // Load the configuration again after a startup failure.
+        if let Some(next_startup_retry) = self.next_startup_retry {
+            let now = Instant::now();
+
+            if self.state.active_page != Page::Startup {
+                self.next_startup_retry = None;
+            } else if self.tasks.is_running(TASK_CONFIG) {
+                // Wait for the current request.
+            } else if now >= next_startup_retry {
+                self.load_config();
+            } else {
+                ctx.request_repaint_after(next_startup_retry - now);
             }
         }
 
-        // Get config promise.
-        if let Some(p) = &self.get_config_prefix_promise {
-            match p.ready() {
-                None => (),
-                Some(try_config) => {
-                    self.is_working = false;
+        // Try to reach the backend again while offline.
+        if let Some(next_online_check) = self.next_online_check {
+            let now = Instant::now();
+
+            if self.tasks.is_running(TASK_CONFIG) {
+                // Wait for the current request.
+            } else if now >= next_online_check {
+                self.tasks.start(
+                    Task::request(
+                        "checking the backend",
+                        self.api_client.get_config(),
+                        Self::config_loaded,
+                    )
+                    .with_key(TASK_CONFIG)
+                    .in_background(),
+                );
+            } else {
+                ctx.request_repaint_after(next_online_check - now);
+            }
+        }
 
-                    match try_config {
-                        Ok(config) => {
-                            self.admin = Some(config.admin.clone());
-                            self.connected_user = Some(config.connected_user.clone());
-                            self.current_admin_restriction
-                                .clone_from(&config.current_admin_restriction);
-                            self.group_prefix = Some(config.users_dsi_api_group_prefix.clone());
-                            self.root_groups.clone_from(&config.root_groups);
-                            self.quota = Some(config.quota.clone());
+        // Rebuild the views showing stale names, requesting them again.
+        if let Some(next_expiry) = self.store.user_displays_mut().expire() {
+            ctx.request_repaint_after(next_expiry.saturating_duration_since(Instant::now()));
+        }
 
-                            self.get_config_prefix_promise = None;
+        // Keep the data shown for the next offline start.
+        self.save_snapshot();
 
-                            self.get_directories_promise = Some(
-                                api::directory::get_root_directories(ctx, self.api_url.clone()),
-                            );
-                            self.get_groups_promise =
-                                Some(api::group::get_groups(ctx, self.api_url.clone()));
-                        }
-                        Err(e) => self.current_error = Some(AppError::InternalError(e.to_string())),
-                    };
+        match self.state.active_page {
+            Page::Login => login::ui::update(self, ctx),
+            Page::Startup => startup::ui::update(self, ctx),
+            // Render page only when admin and group prefix are retrieved.
+            Page::Main => {
+                if self.admin.is_some() && self.group_prefix.is_some() {
+                    main::ui::update(self, ctx, frame)
                 }
             }
         }
 
-        // Get directories promises.
-        if let Some(p) = &self.get_directories_promise {
-            match p.ready() {
-                None => (),
-                Some(try_directories) => {
-                    self.is_working = false;
-
-                    match try_directories {
-                        Ok(directories) => {
-                            self.directories.clone_from(directories);
-
-                            if self.directories.is_some() {
-                                // Filter directory ACLs.
-                                for directory in self.directories.as_mut().unwrap() {
-                                    directory.acls.retain(|acl| {
-                                        if acl.perm == 0 {
-                                            false
-                                        } else {
-                                            matches!(
-                                                acl.qualifier,
-                                                Qualifier::User(_) | Qualifier::Group(_)
-                                            )
-                                        }
-                                    });
-                                }
-
-                                // Get display name for each user of the ACLs.
-                                for directory in self.directories.as_mut().unwrap() {
-                                    for acl in directory.acls.iter_mut() {
-                                        let qualifier_cn = acl.qualifier_cn.clone().unwrap();
-
-                                        acl.qualifier_display = match self
-                                            .user_display_cache
-                                            .get(&qualifier_cn)
-                                        {
-                                            Some(maybe_display_name) => match maybe_display_name {
-                                                Some(display_name) => {
-                                                    Some(display_name.to_string())
-                                                }
-                                                None => Some(format!(
-                                                    "<invalid account> ({})",
-                                                    &qualifier_cn
-                                                )),
-                                            },
-                                            None => {
-                                                if !self
-                                                    .get_user_display_promises
-                                                    .contains_key(&qualifier_cn)
-                                                {
-                                                    self.get_user_display_promises.insert(
-                                                        qualifier_cn.clone(),
-                                                        Some(api::user::get_user_display(
-                                                            ctx,
-                                                            qualifier_cn.clone(),
-                                                            self.api_url.clone(),
-                                                        )),
-                                                    );
-                                                }
-
-                                                Some(qualifier_cn)
-                                            }
-                                        }
-                                    }
-                                }
-
-                                // Sort directories.
-                                self.directories.as_mut().unwrap().sort();
-                            }
+        // Get initial directory and group list and admin,
+        // unless the user must log in first.
+        START.call_once(|| {
+            if self.api_client.credentials().needs_login() {
+                self.state.active_page = Page::Login;
+            } else {
+                self.state.active_page = Page::Startup;
+                self.load_config();
+            }
+        });
 
-                            self.get_directories_promise = None;
-                        }
-                        Err(e) => self.current_error = Some(AppError::InternalError(e.to_string())),
-                    };
-                }
+        // Resolve the user display names requested by this frame.
+        let uids = self.store.user_displays_mut().take_requests();
+        if !uids.is_empty() {
+            self.send_to_worker(ToWorkerMessage::ResolveUserDisplays(uids));
+        }
+    }
+}
+
+impl DaenerysApp {
+    /// Send `message` to the worker, reporting a stopped worker.
+    fn send_to_worker(&mut self, message: ToWorkerMessage) {
+        if let Some(worker) = &self.worker {
+            if let Err(e) = worker.send(message) {
+                self.worker_stopped(e);
             }
         }
+    }
 
-        // Save acl promise.
-        if let Some(p) = &self.save_directory_acl_promise {
-            match p.ready() {
-                None => (),
-                Some(try_result) => {
-                    self.is_working = false;
+    /// Report the failure of the worker channels, the background requests
+    /// stop.
+    fn worker_stopped(&mut self, e: AppError) {
+        warn!("worker stopped: {}", e);
 
-                    match try_result {
-                        Ok(_) => {
-                            self.current_info = Some("acl set successfully".to_string());
-                            self.save_directory_acl_promise = None;
+        self.worker = None;
+        self.current_error = Some(e);
+    }
 
-                            self.get_directories_promise = Some(
-                                api::directory::get_root_directories(ctx, self.api_url.clone()),
-                            );
-                            self.active_action = Action::DirectoryEdit;
-                        }
-                        Err(e) => {
-                            self.current_error = Some(AppError::InternalError(e.to_string()));
-                            self.current_info = None;
-                        }
-                    };
+    fn worker_message(&mut self, message: ToAppMessage) {
+        match message {
+            ToAppMessage::Pong => debug!("worker pong"),
+            ToAppMessage::Directories(result) => {
+                if self.accepts_refresh(TASK_DIRECTORIES, &result) {
+                    self.directories_loaded(result);
                 }
             }
+            ToAppMessage::Groups(result) => {
+                if self.accepts_refresh(TASK_GROUPS, &result) {
+                    self.groups_loaded(result);
+                }
+            }
+            ToAppMessage::UserDisplays(update) => {
+                if self.store.user_displays_mut().apply(update) {
+                    self.refresh_acl_displays();
+                    self.snapshot_dirty = true;
+                }
+            }
+            ToAppMessage::Error(e) => self.current_error = Some(e),
         }
+    }
 
-        // Save quota promise.
-        if let Some(p) = &self.save_directory_quota_promise {
-            match p.ready() {
-                None => (),
-                Some(try_result) => {
-                    self.is_working = false;
+    /// True if the background reload of `key` is to be applied: not while
+    /// offline nor reloaded by the user, its failures only logged unless the
+    /// user must log in again.
+    /// The validators of a dropped list are forgotten, else the next GET
+    /// would be answered 304 without it.
+    fn accepts_refresh<T>(&self, key: &str, result: &Result<ConditionalList<T>, ApiError>) -> bool {
+        if self.offline.is_some() || self.tasks.is_running(key) {
+            if let Ok(Conditional::Modified(_)) = result {
+                self.api_client.clear_validators();
+            }
+            return false;
+        }
 
-                    match try_result {
-                        Ok(_) => {
-                            self.current_info = Some("quota set successfully".to_string());
-                            self.save_directory_quota_promise = None;
+        match result {
+            Err(ApiError::Http { status: 401, .. }) | Ok(_) => true,
+            Err(e) => {
+                warn!("background reload of the {} failed: {}", key, e);
+                false
+            }
+        }
+    }
 
-                            self.get_directories_promise = Some(
-                                api::directory::get_root_directories(ctx, self.api_url.clone()),
-                            );
-                            self.active_action = Action::DirectoryEdit;
-                        }
-                        Err(e) => {
-                            self.current_error = Some(AppError::InternalError(e.to_string()));
-                            self.current_info = None;
+    /// Show `e` to the user, or send them to the login page if the backend
+    /// rejected the credentials.
+    pub fn set_api_error(&mut self, e: ApiError) {
+        if let ApiError::Http { status: 401, .. } = e {
+            self.api_client.credentials().clear();
+            self.send_to_worker(ToWorkerMessage::SetRefreshInterval(None));
+
+            self.state.active_page = Page::Login;
+            self.current_error = None;
+            self.current_info = Some("authentication required, please log in".to_string());
+        } else {
+            self.current_error = Some(AppError::Api(e));
+        }
+    }
+
+    fn du_loaded(&mut self, result: Result<Option<String>, ApiError>) {
+        match result {
+            Ok(du) => {
+                self.du = du;
+
+                self.active_action = Action::DiskUsage;
+            }
+            Err(e) => self.set_api_error(e),
+        };
+    }
+
+    fn version_loaded(&mut self, result: Result<Option<BackendVersion>, ApiError>) {
+        match result {
+            Ok(backend) => {
+                let negotiation = Negotiation::new(backend);
+
+                if let Some(message) = negotiation.message() {
+                    warn!("{}", message);
+                }
+
+                self.api_client
+                    .set_conditional(negotiation.supports(Feature::ConditionalGet));
+                self.send_to_worker(ToWorkerMessage::SetBatchSupported(
+                    negotiation.supports(Feature::BatchUserDisplays),
+                ));
+
+                self.negotiation = Some(negotiation);
+            }
+            // The configuration request tells the user what went wrong.
+            Err(e) => debug!("can not get the backend version: {}", e),
+        };
+    }
+
+    fn config_loaded(&mut self, result: Result<Config, ApiError>) {
+        match result {
+            Ok(config) => {
+                self.startup_attempts = 0;
+                self.next_startup_retry = None;
+                if self.state.active_page == Page::Startup {
+                    self.state.active_page = Page::Main;
+                    self.current_error = None;
+                }
+
+                if self.offline.take().is_some() {
+                    self.next_online_check = None;
+                    self.current_info = Some("backend reachable again".to_string());
+                }
+
+                self.set_config(config);
+                self.snapshot_dirty = true;
+
+                // Such as when the backend was unreachable.
+                if self.negotiation.is_none() && !self.tasks.is_running(TASK_VERSION) {
+                    self.load_version();
+                }
+
+                self.reload_directories();
+                self.reload_groups();
+                self.send_to_worker(ToWorkerMessage::SetRefreshInterval(self.refresh_interval));
+            }
+            Err(e) if e.is_unreachable() && self.offline.is_some() => {
+                debug!("backend still unreachable: {}", e);
+                self.next_online_check = Some(Instant::now() + ONLINE_CHECK_INTERVAL);
+            }
+            Err(e) if e.is_unreachable() && self.go_offline() => (),
+            Err(e) => {
+                self.set_api_error(e);
+
+                // Retry later, unless sent to the login page.
+                if self.state.active_page == Page::Startup {
+                    self.startup_attempts += 1;
+                    self.next_startup_retry =
+                        Some(Instant::now() + startup_retry_delay(self.startup_attempts));
+                }
+            }
+        };
+    }
+
+    fn directories_loaded(&mut self, result: Result<ConditionalList<Directory>, ApiError>) {
+        match result {
+            // The directories are already filtered and sorted.
+            Ok(Conditional::NotModified) => (),
+            Ok(Conditional::Modified(directories)) => {
+                self.set_directories(directories);
+                self.snapshot_dirty = true;
+
+                // Show the reloaded directory, unless being edited or below
+                // the root ones.
+                if self.active_action == Action::DirectoryEdit {
+                    if let Some(current_directory) = self.current_directory.as_mut() {
+                        match self.store.directory(&current_directory.name) {
+                            Some(directory) => **current_directory = directory.clone(),
+                            None if current_directory.name.contains('/') => (),
+                            None => {
+                                self.current_directory = None;
+                                self.active_action = Action::Home;
+                            }
                         }
-                    };
+                    }
                 }
+
+                // Failed display names are resolved again on reload.
+                self.store.user_displays_mut().clear_errors();
+                self.send_to_worker(ToWorkerMessage::ClearUserDisplayErrors);
+
+                // Get display name for each user of the ACLs.
+                self.refresh_acl_displays();
             }
+            Err(e) => self.set_api_error(e),
+        };
+    }
+
+    fn default_acl_loaded(&mut self, name: String, result: Result<Vec<AclEntry>, ApiError>) {
+        // Another directory was opened meanwhile.
+        if self
+            .current_directory
+            .as_ref()
+            .map(|directory| &directory.name)
+            != Some(&name)
+        {
+            return;
         }
 
-        // Save group promise.
-        if let Some(p) = &self.save_group_promises {
-            let mut count = 0;
-            let total_promises = p.len();
-            let mut is_error: bool = false;
+        match result {
+            Ok(mut default_acl) => {
+                filter_acl(&mut default_acl);
+                self.set_acl_displays(default_acl.iter_mut());
+                self.current_default_acl = Some(default_acl);
+                self.acls_changed();
+            }
+            Err(e) => self.set_api_error(e),
+        };
+    }
+
+    fn acl_job_started(&mut self, name: String, result: Result<AclJob, ApiError>) {
+        match result {
+            Ok(job) => {
+                self.current_info = Some(format!("applying the acl below {}", name));
+                self.acl_job = Some(AclJobStatus::started(job, &name));
+                self.next_acl_job_poll = Some(Instant::now());
+                self.apply_recursively = false;
+                self.active_action = Action::DirectoryEdit;
+            }
+            Err(e) => {
+                self.set_api_error(e);
+                self.current_info = None;
+            }
+        };
+    }
+
+    fn acl_job_polled(&mut self, result: Result<AclJobStatus, ApiError>) {
+        match result {
+            Ok(acl_job) if acl_job.is_running() => {
+                self.acl_job = Some(acl_job);
+                self.next_acl_job_poll = Some(Instant::now() + ACL_JOB_POLL_INTERVAL);
+            }
+            Ok(acl_job) => {
+                self.current_info = Some(format!(
+                    "acl {} below {}: {} processed, {} failed",
+                    if acl_job.state == AclJobState::Cancelled {
+                        "application cancelled"
+                    } else {
+                        "applied"
+                    },
+                    acl_job.name,
+                    acl_job.processed,
+                    acl_job.failures.len()
+                ));
+                self.acl_job = Some(acl_job);
+                self.next_acl_job_poll = None;
+
+                // Show the ACLs set.
+                self.reload_directories();
+                if self.current_default_acl.is_some() {
+                    self.load_default_acl();
+                }
+            }
+            Err(e) => {
+                self.next_acl_job_poll = None;
+                self.set_api_error(e);
+            }
+        };
+    }
 
-            for promise in p.iter() {
-                match promise.ready() {
-                    None => (),
-                    Some(try_result) => {
-                        match try_result {
-                            Ok(_) => {
-                                count += 1;
+    fn subdirectories_loaded(&mut self, path: String, result: Result<Vec<Directory>, ApiError>) {
+        match result {
+            Ok(mut subdirectories) => {
+                filter_acls(&mut subdirectories);
+                subdirectories.sort();
+                self.set_acl_displays(
+                    subdirectories
+                        .iter_mut()
+                        .flat_map(|directory| directory.acls.iter_mut()),
+                );
+                self.store.set_subdirectories(&path, Some(subdirectories));
+
+                // Show the reloaded directory, unless being edited.
+                if self.active_action == Action::DirectoryEdit {
+                    if let Some(current_directory) = self.current_directory.as_mut() {
+                        match self.store.directory(&current_directory.name) {
+                            Some(directory) => **current_directory = directory.clone(),
+                            None if parent_path(&current_directory.name) == Some(&path) => {
+                                self.current_directory = None;
+                                self.active_action = Action::Home;
                             }
-                            Err(e) => {
-                                self.current_error = Some(AppError::InternalError(e.to_string()));
-                                self.current_info = None;
+                            None => (),
+                        }
+                        self.acls_changed();
+                    }
+                }
+            }
+            Err(e) => {
+                self.expanded_directories.remove(&path);
+                self.set_api_error(e);
+            }
+        };
+    }
 
-                                is_error = true;
-                                break;
+    fn groups_loaded(&mut self, result: Result<ConditionalList<Group>, ApiError>) {
+        match result {
+            // The groups are already sorted.
+            Ok(Conditional::NotModified) => (),
+            Ok(Conditional::Modified(groups)) => {
+                self.set_groups(groups);
+                self.snapshot_dirty = true;
+
+                // Show the reloaded group, unless being edited.
+                if self.active_action == Action::GroupEdit {
+                    if let Some(current_group) = self.current_group.as_mut() {
+                        match self.store.group(&current_group.cn) {
+                            Some(group) => **current_group = group.clone(),
+                            None => {
+                                self.current_group = None;
+                                self.active_action = Action::Home;
                             }
-                        };
+                        }
+                        self.members_changed();
                     }
                 }
             }
+            Err(e) => self.set_api_error(e),
+        };
+    }
+
+    fn users_loaded(&mut self, result: Result<Option<Vec<User>>, ApiError>) {
+        match result {
+            Ok(users) => self.store.set_users(users),
+            Err(e) => self.set_api_error(e),
+        };
+    }
 
-            if is_error || count == total_promises {
-                self.save_group_promises = None;
+    fn device_login_started(&mut self, result: Result<DeviceAuthorization, ApiError>) {
+        match result {
+            Ok(authorization) => {
+                self.next_device_poll = Some(Instant::now() + authorization.interval);
+                self.device_authorization = Some(authorization);
             }
+            Err(e) => self.set_api_error(e),
+        };
+    }
 
-            if count == total_promises {
-                self.is_working = false;
-                self.current_info = Some("group updated successfully".to_string());
-                self.active_action = Action::GroupEdit;
-                // self.get_groups_promise = Some(api::group::get_groups(ctx, self.api_url.clone()));
+    fn device_login_polled(&mut self, result: Result<PollStatus, ApiError>) {
+        match result {
+            Ok(PollStatus::Granted(token)) => {
+                let token_endpoint = self
+                    .device_authorization
+                    .take()
+                    .map(|authorization| authorization.token_endpoint);
+
+                self.api_client
+                    .credentials()
+                    .set_token(token, token_endpoint);
+                self.logged_in();
+            }
+            Ok(PollStatus::SlowDown) => {
+                if let Some(authorization) = self.device_authorization.as_mut() {
+                    authorization.slow_down();
+                }
             }
+            Ok(PollStatus::Pending) => (),
+            Err(e) => {
+                self.device_authorization = None;
+                self.set_api_error(e);
+            }
+        };
+
+        if let Some(authorization) = &self.device_authorization {
+            self.next_device_poll = Some(Instant::now() + authorization.interval);
         }
+    }
 
-        // Create directory promise.
-        if let Some(p) = &self.create_directory_promise {
-            match p.ready() {
-                None => (),
-                Some(try_result) => {
-                    self.is_working = false;
+    /// True if the directories and groups can not be modified.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+            || self.offline.is_some()
+            || self
+                .negotiation
+                .as_ref()
+                .is_some_and(|negotiation| negotiation.is_incompatible())
+    }
 
-                    match try_result {
-                        Ok(_) => {
-                            self.current_info = Some("directory created successfully".to_string());
-                            self.create_directory_promise = None;
+    /// Display name of the user `uid`, requested in the background if unknown
+    /// or stale.
+    /// Unknown accounts and failed resolutions start with `<`.
+    /// Offline, only the names known by the cache or the snapshot are shown.
+    pub fn user_display(&mut self, uid: &str) -> String {
+        let snapshot_display = self
+            .offline
+            .as_ref()
+            .map(|snapshot| snapshot.user_displays.get(uid));
+
+        let user_displays = self.store.user_displays_mut();
+        if self.offline.is_none() {
+            user_displays.request(uid);
+        }
 
-                            self.get_directories_promise = Some(
-                                api::directory::get_root_directories(ctx, self.api_url.clone()),
-                            );
-                        }
-                        Err(e) => {
-                            self.current_error = Some(AppError::InternalError(e.to_string()));
-                            self.current_info = None;
-                        }
-                    };
-                }
+        match user_displays.get(uid).or(snapshot_display.flatten()) {
+            Some(Some(display_name)) => display_name.to_string(),
+            Some(None) => format!("<invalid account> ({})", uid),
+            None if user_displays.error(uid).is_some() => {
+                format!("<unresolved account> ({})", uid)
             }
+            None => uid.to_string(),
         }
+    }
+
+    /// Display names and uids of the members of the current group, sorted
+    /// by display name and sorted again only when the members or the names
+    /// change.
+    pub fn member_rows(&mut self) -> &[(String, String)] {
+        let key = data_key((
+            self.member_generation,
+            self.store.user_displays().generation(),
+            self.offline.is_some(),
+        ));
+
+        let mut member_rows = std::mem::take(&mut self.member_rows);
+        member_rows.get(key, || {
+            let members = self
+                .current_group
+                .as_ref()
+                .and_then(|group| group.member.clone())
+                .unwrap_or_default();
+
+            let mut rows: Vec<(String, String)> = members
+                .into_iter()
+                .map(|member| (self.user_display(&member), member))
+                .collect();
+            rows.sort_by_cached_key(|(display, _)| display.to_lowercase());
+            rows
+        });
+        self.member_rows = member_rows;
 
-        // Rename directory promise.
-        if let Some(p) = &self.rename_directory_promise {
-            match p.ready() {
-                None => (),
-                Some(try_result) => {
-                    self.is_working = false;
+        self.member_rows.rows()
+    }
 
-                    match try_result {
-                        Ok(_) => {
-                            self.current_info = Some("directory renamed successfully".to_string());
-                            self.rename_directory_promise = None;
+    /// Entries of the ACL of `kind` of the current directory, `None` if not
+    /// loaded.
+    pub fn acl(&self, kind: AclKind) -> Option<&Vec<AclEntry>> {
+        match kind {
+            AclKind::Access => self
+                .current_directory
+                .as_ref()
+                .map(|directory| &directory.acls),
+            AclKind::Default => self.current_default_acl.as_ref(),
+        }
+    }
 
-                            self.active_action = Action::Home;
-                            self.get_directories_promise = Some(
-                                api::directory::get_root_directories(ctx, self.api_url.clone()),
-                            );
-                        }
-                        Err(e) => {
-                            self.current_error = Some(AppError::InternalError(e.to_string()));
-                            self.current_info = None;
-                        }
-                    };
+    /// Entries of the ACL of `kind` of the current directory, to be changed.
+    /// Call `acls_changed` once entries are added, removed or renamed.
+    pub fn acl_mut(&mut self, kind: AclKind) -> Option<&mut Vec<AclEntry>> {
+        match kind {
+            AclKind::Access => self
+                .current_directory
+                .as_mut()
+                .map(|directory| &mut directory.acls),
+            AclKind::Default => self.current_default_acl.as_mut(),
+        }
+    }
+
+    /// Positions of the user and group entries of the ACL of `kind` of the
+    /// current directory, sorted by display name and sorted again only when
+    /// the entries change.
+    pub fn acl_rows(&mut self, kind: AclKind) -> &[usize] {
+        let acls = match kind {
+            AclKind::Access => self
+                .current_directory
+                .as_ref()
+                .map_or(&[][..], |directory| &directory.acls),
+            AclKind::Default => self.current_default_acl.as_deref().unwrap_or_default(),
+        };
+        let key = self.acl_generation;
+        let acl_rows = match kind {
+            AclKind::Access => &mut self.acl_rows,
+            AclKind::Default => &mut self.default_acl_rows,
+        };
+
+        acl_rows.get(key, || {
+            let mut rows: Vec<usize> = (0..acls.len())
+                .filter(|index| {
+                    matches!(
+                        acls[*index].qualifier,
+                        Qualifier::User(_) | Qualifier::Group(_)
+                    )
+                })
+                .collect();
+            rows.sort_by(|a, b| acls[*a].qualifier_display.cmp(&acls[*b].qualifier_display));
+            rows
+        })
+    }
+
+    /// The members of the current group changed or another group is shown,
+    /// `member_rows` are built again.
+    pub fn members_changed(&mut self) {
+        self.member_generation += 1;
+    }
+
+    /// The ACL entries of the current directory changed or another
+    /// directory is shown, `acl_rows` are built again.
+    pub fn acls_changed(&mut self) {
+        self.acl_generation += 1;
+    }
+
+    /// Set the display name of the user ACLs of the loaded directories.
+    pub fn refresh_acl_displays(&mut self) {
+        let mut directories = self.store.take_directories();
+        let mut current_directory = self.current_directory.take();
+
+        let mut current_default_acl = self.current_default_acl.take();
+
+        self.set_acl_displays(
+            directories
+                .iter_mut()
+                .flatten()
+                .chain(current_directory.as_deref_mut())
+                .flat_map(|directory| directory.acls.iter_mut())
+                .chain(current_default_acl.iter_mut().flatten()),
+        );
+
+        self.store.set_directories(directories);
+        self.current_directory = current_directory;
+        self.current_default_acl = current_default_acl;
+        self.acls_changed();
+
+        let parents: Vec<String> = self.store.subdirectory_parents().cloned().collect();
+        for parent in parents {
+            let mut subdirectories = self.store.take_subdirectories(&parent);
+            self.set_acl_displays(
+                subdirectories
+                    .iter_mut()
+                    .flatten()
+                    .flat_map(|directory| directory.acls.iter_mut()),
+            );
+            self.store.set_subdirectories(&parent, subdirectories);
+        }
+    }
+
+    /// Set the display name of the user entries of `acls`.
+    fn set_acl_displays<'a>(&mut self, acls: impl Iterator<Item = &'a mut AclEntry>) {
+        for acl in acls {
+            let Some(qualifier_cn) = &acl.qualifier_cn else {
+                continue;
+            };
+
+            acl.qualifier_display = match acl.qualifier {
+                Qualifier::User(_) => Some(self.user_display(qualifier_cn)),
+                _ => Some(qualifier_cn.clone()),
+            };
+        }
+    }
+
+    /// Set the config of the backend.
+    fn set_config(&mut self, config: Config) {
+        self.admin = Some(config.admin.clone());
+        self.connected_user = Some(config.connected_user.clone());
+        self.current_admin_restriction
+            .clone_from(&config.current_admin_restriction);
+        self.group_prefix = Some(config.users_dsi_api_group_prefix.clone());
+        self.root_groups.clone_from(&config.root_groups);
+        self.quota = Some(config.quota.clone());
+        self.config = Some(config);
+    }
+
+    /// Set the directory list, keeping the user and group ACLs, sorted.
+    fn set_directories(&mut self, mut directories: Option<Vec<Directory>>) {
+        if let Some(directories) = directories.as_mut() {
+            filter_acls(directories);
+
+            // Sort directories.
+            directories.sort();
+        }
+
+        self.store.set_directories(directories);
+    }
+
+    /// Set the group list, the group of the users and its invite group first.
+    fn set_groups(&mut self, mut groups: Option<Vec<Group>>) {
+        if let Some(groups) = groups.as_mut() {
+            let auto_group = self.group_prefix.as_ref().unwrap();
+            let invite_group = format!("{}-invite", auto_group);
+
+            groups.sort_by(|groupa, groupb| {
+                if groupa.cn.eq(auto_group) && groupb.cn.eq(&invite_group) {
+                    Ordering::Less
+                } else if groupb.cn.eq(auto_group) && groupa.cn.eq(&invite_group) {
+                    Ordering::Greater
+                } else if groupa.cn.eq(auto_group) {
+                    Ordering::Less
+                } else if groupb.cn.eq(auto_group) {
+                    Ordering::Greater
+                } else if groupa.cn.eq(&invite_group) {
+                    Ordering::Less
+                } else if groupb.cn.eq(&invite_group) {
+                    Ordering::Greater
+                } else {
+                    groupa.cmp(groupb)
                 }
+            })
+        }
+
+        self.store.set_groups(groups);
+    }
+
+    /// Show the last snapshot read-only, returns false if there is none.
+    fn go_offline(&mut self) -> bool {
+        let Some(path) = &self.snapshot_path else {
+            return false;
+        };
+
+        let snapshot = match Snapshot::load(path, self.api_client.api_url()) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return false,
+            Err(e) => {
+                warn!("can not load snapshot {}: {}", path.display(), e);
+                return false;
             }
+        };
+
+        self.set_config(snapshot.config.clone());
+        self.set_directories(snapshot.directories.clone());
+        self.set_groups(snapshot.groups.clone());
+
+        self.current_error = None;
+        self.current_info = Some(format!(
+            "backend unreachable, showing the data of {}",
+            snapshot.saved_at_label()
+        ));
+        self.active_action = Action::Home;
+        self.state.active_page = Page::Main;
+        self.offline = Some(snapshot);
+        self.next_online_check = Some(Instant::now() + ONLINE_CHECK_INTERVAL);
+        self.send_to_worker(ToWorkerMessage::SetRefreshInterval(None));
+
+        self.refresh_acl_displays();
+
+        true
+    }
+
+    /// Save the data shown once loaded and every display name is resolved,
+    /// written by the worker.
+    fn save_snapshot(&mut self) {
+        if !self.snapshot_dirty
+            || self.offline.is_some()
+            || self.tasks.is_running(TASK_DIRECTORIES)
+            || self.tasks.is_running(TASK_GROUPS)
+            || !self.store.user_displays().is_idle()
+        {
+            return;
         }
+        let (Some(path), Some(config)) = (self.snapshot_path.clone(), &self.config) else {
+            return;
+        };
+
+        let acl_users = self
+            .store
+            .acl_qualifiers()
+            .filter_map(|qualifier| match qualifier {
+                AclQualifier::User(uid) => Some(uid),
+                AclQualifier::Group(_) => None,
+            });
+        let members = self
+            .store
+            .groups()
+            .into_iter()
+            .flatten()
+            .flat_map(|group| group.member.iter().flatten());
+
+        let user_displays: HashMap<String, Option<String>> = acl_users
+            .chain(members)
+            .filter_map(|uid| {
+                let display = self.store.user_displays().get(uid)?;
+                Some((uid.clone(), display.clone()))
+            })
+            .collect();
+
+        let snapshot = Snapshot::new(
+            self.api_client.api_url(),
+            config.clone(),
+            self.store.directories().map(<[_]>::to_vec),
+            self.store.groups().map(<[_]>::to_vec),
+            user_displays,
+        );
+
+        self.send_to_worker(ToWorkerMessage::SaveSnapshot(Box::new(snapshot), path));
+        self.snapshot_dirty = false;
+    }
 
-        // Delete directory promise.
-        if let Some(p) = &self.delete_directory_promise {
-            match p.ready() {
-                None => (),
-                Some(try_result) => {
-                    self.is_working = false;
+    /// Go back to the main page and reload the data once logged in.
+    pub fn logged_in(&mut self) {
+        // The lists may differ for another user.
+        self.api_client.clear_validators();
+        self.store.invalidate(EntityKind::Directories);
+        self.tasks.remove_prefixed(TASK_SUBDIRECTORIES);
+        self.store.invalidate(EntityKind::Groups);
 
-                    match try_result {
-                        Ok(_) => {
-                            self.current_info = Some("directory deleted successfully".to_string());
-                            self.delete_directory_promise = None;
+        self.state.active_page = Page::Startup;
+        self.current_error = None;
+        self.current_info = None;
+        self.startup_attempts = 0;
 
-                            self.active_action = Action::Home;
-                            self.get_directories_promise = Some(
-                                api::directory::get_root_directories(ctx, self.api_url.clone()),
-                            );
-                        }
-                        Err(e) => {
-                            self.current_error = Some(AppError::InternalError(e.to_string()));
-                            self.current_info = None;
-                        }
-                    };
-                }
+        self.load_config();
+    }
+
+    /// Load the backend configuration, the directories and groups follow.
+    pub fn load_config(&mut self) {
+        self.next_startup_retry = None;
+        self.tasks.start(
+            Task::request(
+                "loading the configuration",
+                self.api_client.get_config(),
+                Self::config_loaded,
+            )
+            .with_key(TASK_CONFIG),
+        );
+
+        // Checked again, the backend may have been upgraded.
+        self.load_version();
+    }
+
+    fn load_version(&mut self) {
+        self.tasks.start(
+            Task::request(
+                "checking the backend version",
+                self.api_client.get_version(),
+                Self::version_loaded,
+            )
+            .with_key(TASK_VERSION)
+            .in_background(),
+        );
+    }
+
+    /// Start a load the user can cancel, superseding the one of the same `key`.
+    fn start_load<T, F, D>(&mut self, key: &str, label: &str, send: F, on_done: D)
+    where
+        T: Send + 'static,
+        F: FnOnce(&ApiClient) -> Promise<Result<T, ApiError>>,
+        D: FnOnce(&mut Self, Result<T, ApiError>) + 'static,
+    {
+        // Before sending: the validators of the superseded request are then
+        // not sent.
+        self.tasks.remove(key);
+
+        let cancel = CancelToken::default();
+        let promise = send(&self.api_client.with_cancel(&cancel));
+
+        self.tasks.start(
+            Task::request(label, promise, on_done)
+                .with_key(key)
+                .with_cancel(cancel),
+        );
+    }
+
+    /// Reload the directory list, superseding a reload in flight, and the
+    /// subdirectories shown in the tree.
+    pub fn reload_directories(&mut self) {
+        self.start_load(
+            TASK_DIRECTORIES,
+            "loading the directories",
+            ApiClient::get_root_directories,
+            Self::directories_loaded,
+        );
+
+        // And the one of the directory shown, such as after a change.
+        let current_parent = self
+            .current_directory
+            .as_ref()
+            .and_then(|directory| parent_path(&directory.name))
+            .map(str::to_string);
+
+        let parents: Vec<String> = self.store.subdirectory_parents().cloned().collect();
+        for parent in parents {
+            if self.expanded_directories.contains(&parent)
+                || current_parent.as_deref() == Some(parent.as_str())
+            {
+                self.load_subdirectories(parent);
+            } else {
+                // Listed again once shown.
+                self.store.set_subdirectories(&parent, None);
             }
         }
+    }
 
-        // Create group promise.
-        if let Some(p) = &self.create_group_promise {
-            match p.ready() {
-                None => (),
-                Some(try_result) => {
-                    self.is_working = false;
+    /// True if the directories below the root ones can be listed.
+    pub fn can_browse_subdirectories(&self) -> bool {
+        self.offline.is_none()
+            && self
+                .negotiation
+                .as_ref()
+                .is_some_and(|negotiation| negotiation.supports(Feature::Subdirectories))
+    }
 
-                    match try_result {
-                        Ok(_) => {
-                            self.current_info = Some("group created successfully".to_string());
-                            self.create_group_promise = None;
+    /// True if the default ACL of the directories can be edited.
+    pub fn can_edit_default_acl(&self) -> bool {
+        self.offline.is_none()
+            && self
+                .negotiation
+                .as_ref()
+                .is_some_and(|negotiation| negotiation.supports(Feature::DefaultAcls))
+    }
 
-                            self.get_groups_promise =
-                                Some(api::group::get_groups(ctx, self.api_url.clone()));
-                        }
-                        Err(e) => {
-                            self.current_error = Some(AppError::InternalError(e.to_string()));
-                            self.current_info = None;
-                        }
-                    };
-                }
+    /// True if the ACLs can be applied to everything below a directory.
+    pub fn can_apply_recursively(&self) -> bool {
+        self.offline.is_none()
+            && self
+                .negotiation
+                .as_ref()
+                .is_some_and(|negotiation| negotiation.supports(Feature::RecursiveAcls))
+    }
+
+    /// True if the status of the recursive ACL job is being requested.
+    pub fn is_following_acl_job(&self) -> bool {
+        self.next_acl_job_poll.is_some()
+    }
+
+    /// Request the status of the recursive ACL job again, such as after a
+    /// failed request.
+    pub fn follow_acl_job(&mut self) {
+        if self.acl_job.as_ref().is_some_and(AclJobStatus::is_running) {
+            self.next_acl_job_poll = Some(Instant::now());
+        }
+    }
+
+    /// Ask the backend to stop the recursive ACL job, its status tells when
+    /// it did.
+    pub fn cancel_acl_job(&mut self) {
+        let Some(id) = self.acl_job.as_ref().map(|acl_job| acl_job.id.clone()) else {
+            return;
+        };
+
+        self.start_change(
+            "cancelling the recursive acl application",
+            |client| client.cancel_acl_job(&id),
+            "cancellation requested",
+            Self::follow_acl_job,
+        );
+    }
+
+    /// Forget the finished recursive ACL job.
+    pub fn dismiss_acl_job(&mut self) {
+        if !self.acl_job.as_ref().is_some_and(AclJobStatus::is_running) {
+            self.acl_job = None;
+        }
+    }
+
+    /// Write the paths the recursive ACL job failed on in the download
+    /// directory.
+    pub fn save_acl_report(&mut self) {
+        let Some(acl_job) = &self.acl_job else {
+            return;
+        };
+        let Some(dir) = settings::default_report_dir() else {
+            self.current_error = Some(AppError::InternalError(
+                "no directory to save the report in".to_string(),
+            ));
+            return;
+        };
+
+        let path = dir.join(acl_job.report_file_name());
+        match acl_job.save_report(&path) {
+            Ok(()) => self.current_info = Some(format!("report saved to {}", path.display())),
+            Err(e) => {
+                self.current_error = Some(AppError::InternalError(format!(
+                    "can not save the report {}: {}",
+                    path.display(),
+                    e
+                )))
             }
         }
+    }
 
-        // Delete group promise.
-        if let Some(p) = &self.delete_group_promise {
-            match p.ready() {
-                None => (),
-                Some(try_result) => {
-                    self.is_working = false;
+    /// Add the group `cn` to the edited ACL of the current directory, with
+    /// all the rights, resolving its gid if unknown and the backend can.
+    /// Otherwise the backend finds the group by its cn.
+    pub fn add_group_to_acl(&mut self, cn: String) {
+        let gid = self.store.group_gid(&cn);
+
+        self.acl_mut(self.edited_acl).unwrap().push(AclEntry {
+            qualifier: Qualifier::Group(gid.unwrap_or_default()),
+            qualifier_cn: Some(cn.clone()),
+            qualifier_display: Some(cn.clone()),
+            perm: READ | WRITE | EXECUTE,
+        });
+        self.acls_changed();
 
-                    match try_result {
-                        Ok(_) => {
-                            self.current_info = Some("group deleted successfully".to_string());
-                            self.delete_group_promise = None;
+        if gid.is_none() {
+            let name = self.current_directory.as_ref().unwrap().name.clone();
+            self.resolve_group_gid(cn, Some((name, self.edited_acl)));
+        }
+    }
 
-                            self.get_groups_promise =
-                                Some(api::group::get_groups(ctx, self.api_url.clone()));
-                        }
-                        Err(e) => {
-                            self.current_error = Some(AppError::InternalError(e.to_string()));
-                            self.current_info = None;
-                        }
-                    };
+    /// True if the backend resolves the gid of a group.
+    fn supports_group_gids(&self) -> bool {
+        self.negotiation
+            .as_ref()
+            .is_some_and(|negotiation| negotiation.supports(Feature::GroupGids))
+    }
+
+    /// Request the gid of the group `cn`, unless already requested or the
+    /// backend can not resolve it. `added_to` is the directory and ACL the
+    /// group was added to, `None` for a template.
+    fn resolve_group_gid(&mut self, cn: String, added_to: Option<(String, AclKind)>) {
+        if self.supports_group_gids() && self.pending_gids.insert(cn.clone()) {
+            self.tasks.start(Task::request(
+                &format!("resolving the gid of {}", cn),
+                self.api_client.get_group_gid(&cn),
+                move |app: &mut Self, result| app.group_gid_resolved(cn, added_to, result),
+            ));
+        }
+    }
+
+    /// Preview the template `index` on the current directory, resolving the
+    /// gids of its groups.
+    pub fn select_acl_template(&mut self, index: usize) {
+        self.acl_template = Some(index);
+
+        let cns: Vec<String> = self
+            .template_acl(index)
+            .into_iter()
+            .filter(|acl| acl.qualifier == Qualifier::Group(0))
+            .filter_map(|acl| acl.qualifier_cn)
+            .collect();
+        for cn in cns {
+            self.resolve_group_gid(cn, None);
+        }
+    }
+
+    /// Access ACL of the current directory once the template `index`
+    /// applied: its entries, the admin ones kept and the mask.
+    pub fn template_acl(&self, index: usize) -> Vec<AclEntry> {
+        let (Some(template), Some(directory)) =
+            (self.acl_templates.get(index), &self.current_directory)
+        else {
+            return Vec::new();
+        };
+        let admin = self.admin.clone().unwrap_or_default();
+
+        let values = TemplateValues {
+            prefix: self.group_prefix.clone().unwrap_or_default(),
+            name: directory
+                .name
+                .rsplit_once('/')
+                .map_or(directory.name.as_str(), |(_, name)| name)
+                .to_string(),
+            admin: admin.clone(),
+        };
+
+        let mut acls = template.resolve(&values);
+        for acl in acls.iter_mut() {
+            if let (Qualifier::Group(0), Some(cn)) = (&acl.qualifier, &acl.qualifier_cn) {
+                if let Some(gid) = self.store.group_gid(cn) {
+                    acl.qualifier = Qualifier::Group(gid);
                 }
             }
         }
 
-        // Get groups promises.
-        if let Some(p) = &self.get_groups_promise {
-            match p.ready() {
-                None => (),
-                Some(try_groups) => {
-                    self.is_working = false;
-
-                    match try_groups {
-                        Ok(groups) => {
-                            self.groups.clone_from(groups);
-                            if self.groups.is_some() {
-                                self.groups.as_mut().unwrap().sort_by(|groupa, groupb| {
-                                    let auto_group = self.group_prefix.as_ref().unwrap();
-                                    let invite_group =
-                                        format!("{}-invite", self.group_prefix.as_ref().unwrap());
-
-                                    if groupa.cn.eq(auto_group) && groupb.cn.eq(&invite_group) {
-                                        Ordering::Less
-                                    } else if groupb.cn.eq(auto_group)
-                                        && groupa.cn.eq(&invite_group)
-                                    {
-                                        Ordering::Greater
-                                    } else if groupa.cn.eq(auto_group) {
-                                        Ordering::Less
-                                    } else if groupb.cn.eq(auto_group) {
-                                        Ordering::Greater
-                                    } else if groupa.cn.eq(&invite_group) {
-                                        Ordering::Less
-                                    } else if groupb.cn.eq(&invite_group) {
-                                        Ordering::Greater
-                                    } else {
-                                        groupa.cmp(groupb)
-                                    }
-                                })
-                            }
+        // The admin entries can not be removed.
+        let kept: Vec<AclEntry> = directory
+            .acls
+            .iter()
+            .filter(|acl| acl.qualifier == Qualifier::Mask || acl.is_admin(&admin))
+            .filter(|acl| {
+                AclQualifier::of(acl).is_none_or(|qualifier| {
+                    !acls
+                        .iter()
+                        .any(|entry| AclQualifier::of(entry).as_ref() == Some(&qualifier))
+                })
+            })
+            .cloned()
+            .collect();
+        acls.extend(kept);
+
+        self.set_template_displays(acls)
+    }
 
-                            self.get_groups_promise = None;
-                        }
-                        Err(e) => self.current_error = Some(AppError::InternalError(e.to_string())),
-                    };
+    /// Set the display names of the user entries of a template ACL.
+    fn set_template_displays(&self, mut acls: Vec<AclEntry>) -> Vec<AclEntry> {
+        for acl in acls.iter_mut() {
+            if let (Qualifier::User(_), Some(uid)) = (&acl.qualifier, &acl.qualifier_cn) {
+                if let Some(Some(display)) = self.store.user_displays().get(uid) {
+                    acl.qualifier_display = Some(display.clone());
                 }
             }
         }
+        acls
+    }
 
-        // Get users promise.
-        if let Some(p) = &self.get_users_promise {
-            match p.ready() {
-                None => (),
-                Some(try_users) => {
-                    self.is_working = false;
+    /// True if a template is previewed and no gid of its groups is being
+    /// resolved. Without GET /gid, the backend finds the groups by their cn.
+    pub fn can_apply_acl_template(&self) -> bool {
+        self.acl_template.is_some() && self.pending_gids.is_empty()
+    }
 
-                    match try_users {
-                        Ok(users) => {
-                            self.users.clone_from(users);
+    /// Replace the access ACL of the current directory with the previewed
+    /// template and save it.
+    pub fn apply_acl_template(&mut self) {
+        if !self.can_apply_acl_template() {
+            return;
+        }
+        let Some(index) = self.acl_template.take() else {
+            return;
+        };
 
-                            self.get_users_promise = None;
+        let acls = self.template_acl(index);
+        self.current_directory.as_mut().unwrap().acls = acls;
+        self.acls_changed();
+
+        // On the directory only.
+        self.apply_recursively = false;
+        self.save_acls();
+    }
+
+    fn group_gid_resolved(
+        &mut self,
+        cn: String,
+        added_to: Option<(String, AclKind)>,
+        result: Result<u32, ApiError>,
+    ) {
+        self.pending_gids.remove(&cn);
+
+        match result {
+            Ok(gid) => {
+                self.store.set_group_gid(&cn, gid);
+
+                let acls = self
+                    .current_directory
+                    .iter_mut()
+                    .flat_map(|directory| directory.acls.iter_mut())
+                    .chain(self.current_default_acl.iter_mut().flatten());
+                for acl in acls {
+                    if matches!(acl.qualifier, Qualifier::Group(_))
+                        && acl.qualifier_cn.as_ref() == Some(&cn)
+                    {
+                        acl.qualifier = Qualifier::Group(gid);
+                    }
+                }
+                self.acls_changed();
+            }
+            Err(e) => {
+                match added_to {
+                    // Drop the entry added for the group, if still edited.
+                    Some((name, kind)) => {
+                        let edited = self
+                            .current_directory
+                            .as_ref()
+                            .is_some_and(|directory| directory.name == name);
+                        if let Some(acls) = self.acl_mut(kind).filter(|_| edited) {
+                            if let Some(index) = acls.iter().rposition(|acl| {
+                                matches!(acl.qualifier, Qualifier::Group(_))
+                                    && acl.qualifier_cn.as_ref() == Some(&cn)
+                            }) {
+                                acls.remove(index);
+                                self.acls_changed();
+                            }
                         }
-                        Err(e) => self.current_error = Some(AppError::InternalError(e.to_string())),
-                    };
+                    }
+                    // Leave the preview of the template.
+                    None => self.acl_template = None,
                 }
+
+                self.set_api_error(e);
             }
+        };
+    }
+
+    /// Show `directory` in the central panel.
+    pub fn open_directory(&mut self, directory: Directory) {
+        self.active_action = Action::DirectoryEdit;
+
+        // And its quota in bytes to populate the quota edition input text.
+        if let Some(quota) = directory.quota {
+            let quota_in_mb = quota / 1024 / 1024;
+            self.edited_directory_quota = quota_in_mb.to_string()
+        } else {
+            self.edited_directory_quota = 0.to_string();
+        }
+        self.current_directory = Some(Box::new(directory));
+
+        self.current_default_acl = None;
+        self.saved_access_acl = None;
+        self.edited_acl = AclKind::Access;
+        if self.can_edit_default_acl() {
+            self.load_default_acl();
+        } else {
+            self.tasks.remove(TASK_DEFAULT_ACL);
         }
 
-        // Render page only when admin and group prefix are retrieved.
-        if self.admin.is_some() && self.group_prefix.is_some() {
-            match self.state.active_page {
-                Page::Main => main::ui::update(self, ctx, frame),
-            }
+        self.acls_changed();
+
+        self.edited_directory_quota_unit = QuotaUnit::Megabyte;
+        self.current_group = None;
+        self.members_changed();
+        self.current_error = None;
+        self.current_info = None;
+        self.du = None;
+    }
+
+    /// Show or hide the subdirectories of `path`, listed the first time.
+    pub fn toggle_directory(&mut self, path: &str) {
+        if self.expanded_directories.remove(path) {
+            return;
         }
 
-        // Get initial directory and group list and admin.
-        START.call_once(|| {
-            self.is_working = true;
+        self.expanded_directories.insert(path.to_string());
+        if self.store.subdirectories(path).is_none()
+            && !self.tasks.is_running(&subdirectories_task(path))
+        {
+            self.load_subdirectories(path.to_string());
+        }
+    }
 
-            self.get_config_prefix_promise = Some(api::root::get_config(ctx, self.api_url.clone()));
-        });
+    /// List the subdirectories of `path`, superseding a listing in flight.
+    fn load_subdirectories(&mut self, path: String) {
+        let label = format!("listing {}", path);
+        let promise_path = path.clone();
+
+        self.start_load(
+            &subdirectories_task(&path),
+            &label,
+            move |client| client.get_subdirectories(&promise_path),
+            move |app: &mut Self, result| app.subdirectories_loaded(path, result),
+        );
+    }
+
+    /// Reload the group list, superseding a reload in flight.
+    pub fn reload_groups(&mut self) {
+        self.start_load(
+            TASK_GROUPS,
+            "loading the groups",
+            ApiClient::get_groups,
+            Self::groups_loaded,
+        );
+    }
+
+    /// Search the users matching `search`, superseding the previous search.
+    pub fn search_users(&mut self, search: String) {
+        self.start_load(
+            TASK_USERS,
+            &format!("searching users {}", search),
+            |client| client.get_users(search),
+            Self::users_loaded,
+        );
+    }
+
+    /// Load the default ACL of the current directory.
+    fn load_default_acl(&mut self) {
+        let name = self.current_directory.as_ref().unwrap().name.clone();
+        let label = format!("loading the default acl of {}", name);
+        let promise_name = name.clone();
+
+        self.start_load(
+            TASK_DEFAULT_ACL,
+            &label,
+            move |client| client.get_default_acl(&promise_name),
+            move |app: &mut Self, result| app.default_acl_loaded(name, result),
+        );
+    }
+
+    /// Compute the disk usage.
+    pub fn load_du(&mut self) {
+        self.start_load(
+            TASK_DU,
+            "computing the disk usage",
+            ApiClient::get_du,
+            Self::du_loaded,
+        );
+    }
+
+    /// Cancel the lists and disk usage being loaded.
+    /// The changes already sent can not be cancelled.
+    pub fn cancel_loading(&mut self) {
+        self.tasks.cancel_all();
+    }
+
+    /// Start the device login.
+    pub fn start_device_login(&mut self) {
+        let Some(device_flow) = &self.device_flow else {
+            return;
+        };
+
+        self.tasks.start(
+            Task::request(
+                "starting the login",
+                device_flow.start(),
+                |app: &mut Self, result| app.device_login_started(result),
+            )
+            .with_key(TASK_DEVICE_LOGIN)
+            .in_background(),
+        );
+    }
+
+    /// Stop waiting for the device login.
+    pub fn cancel_device_login(&mut self) {
+        self.device_authorization = None;
+        self.tasks.remove(TASK_DEVICE_POLL);
+    }
+
+    /// Send a change with `send`, showing `success` once done and calling
+    /// `then` on success.
+    fn start_change<F, D>(&mut self, label: &str, send: F, success: &str, then: D)
+    where
+        F: FnOnce(&ApiClient) -> Promise<Result<(), ApiError>>,
+        D: FnOnce(&mut Self) + 'static,
+    {
+        let success = success.to_string();
+
+        self.tasks.start(Task::request(
+            label,
+            send(&self.api_client),
+            move |app: &mut Self, result| match result {
+                Ok(()) => {
+                    app.current_info = Some(success);
+                    then(app);
+                }
+                Err(e) => {
+                    app.set_api_error(e);
+                    app.current_info = None;
+                }
+            },
+        ));
+    }
+
+    pub fn create_directory(&mut self, create_directory: CreateDirectory) {
+        self.start_change(
+            &format!("creating directory {}", create_directory.name),
+            |client| client.create_directory(create_directory),
+            "directory created successfully",
+            Self::reload_directories,
+        );
+    }
+
+    pub fn rename_directory(&mut self, rename_directory: RenameDirectory) {
+        self.start_change(
+            &format!("renaming directory {}", rename_directory.name),
+            |client| client.rename_directory(rename_directory),
+            "directory renamed successfully",
+            |app| {
+                app.active_action = Action::Home;
+                app.reload_directories();
+            },
+        );
+    }
+
+    pub fn delete_directory(&mut self, delete_directory: CreateDirectory) {
+        self.start_change(
+            &format!("deleting directory {}", delete_directory.name),
+            |client| client.delete_directory(delete_directory),
+            "directory deleted successfully",
+            |app| {
+                app.active_action = Action::Home;
+                app.reload_directories();
+            },
+        );
+    }
+
+    /// Save the access ACL of the current directory, then its default ACL if
+    /// loaded, on everything below it too if requested.
+    /// The access ACL is not sent again if already saved, unchanged since.
+    /// The edition is left once both are saved.
+    pub fn save_acls(&mut self) {
+        // Not before the gids of the added groups are known.
+        if !self.pending_gids.is_empty() {
+            return;
+        }
+
+        let directory = self.current_directory.as_ref().unwrap();
+        let name = directory.name.clone();
+
+        self.current_info = Some(format!("saving acl for {}", name));
+
+        if self.apply_recursively && self.can_apply_recursively() {
+            self.saved_access_acl = None;
+
+            let recursive_acl = RecursiveAcl {
+                name: name.clone(),
+                acls: directory.acls.clone(),
+                default_acls: self.current_default_acl.clone(),
+            };
+
+            self.tasks.start(Task::request(
+                &format!("starting the acl application below {}", name),
+                self.api_client.start_recursive_acl(recursive_acl),
+                move |app: &mut Self, result| app.acl_job_started(name, result),
+            ));
+            return;
+        }
+
+        let acls = directory.acls.clone();
+        let default_acl = self.current_default_acl.clone();
+
+        let saved = self
+            .saved_access_acl
+            .as_ref()
+            .is_some_and(|(saved_name, saved_acls)| {
+                *saved_name == name && same_entries(saved_acls, &acls)
+            });
+        if saved {
+            self.save_default_acl(name, default_acl);
+            return;
+        }
+
+        self.tasks.start(Task::request(
+            &format!("saving the acl of {}", name),
+            self.api_client.save_acl(SetAcl {
+                name: name.clone(),
+                acls: acls.clone(),
+            }),
+            move |app: &mut Self, result| app.acl_saved(name, acls, default_acl, result),
+        ));
+    }
+
+    /// Save the default ACL once the access one is saved, showing the saved
+    /// access ACL in the directory list meanwhile.
+    fn acl_saved(
+        &mut self,
+        name: String,
+        acls: Vec<AclEntry>,
+        default_acl: Option<Vec<AclEntry>>,
+        result: Result<(), ApiError>,
+    ) {
+        // Still edited, to be saved again.
+        if let Err(e) = result {
+            self.current_info = Some(format!("the acl of {} is not saved", name));
+            self.set_api_error(e);
+            return;
+        }
+
+        self.saved_access_acl = Some((name.clone(), acls));
+        self.reload_directories();
+
+        self.save_default_acl(name, default_acl);
     }
+
+    fn save_default_acl(&mut self, name: String, default_acl: Option<Vec<AclEntry>>) {
+        let Some(default_acl) = default_acl else {
+            self.acls_saved();
+            return;
+        };
+
+        self.tasks.start(Task::request(
+            &format!("saving the default acl of {}", name),
+            self.api_client.save_default_acl(SetAcl {
+                name: name.clone(),
+                acls: default_acl,
+            }),
+            move |app: &mut Self, result| app.default_acl_saved(name, result),
+        ));
+    }
+
+    fn default_acl_saved(&mut self, name: String, result: Result<(), ApiError>) {
+        // Still edited, to be saved again.
+        if let Err(e) = result {
+            self.current_info = Some(format!(
+                "the acl of {} is saved, not its default acl: saving again only sends the default acl",
+                name
+            ));
+            self.set_api_error(e);
+            return;
+        }
+
+        self.acls_saved();
+    }
+
+    /// Leave the edition once the access and default ACLs are saved, the
+    /// directories being reloaded.
+    fn acls_saved(&mut self) {
+        self.current_info = Some("acl set successfully".to_string());
+        self.saved_access_acl = None;
+        if self.current_default_acl.is_some() {
+            self.load_default_acl();
+        }
+        self.active_action = Action::DirectoryEdit;
+    }
+
+    pub fn save_quota(&mut self, set_quota: SetQuota) {
+        self.start_change(
+            &format!("saving the quota of {}", set_quota.name),
+            |client| client.save_quota(set_quota),
+            "quota set successfully",
+            |app| {
+                app.reload_directories();
+                app.active_action = Action::DirectoryEdit;
+            },
+        );
+    }
+
+    pub fn create_group(&mut self, create_group: Group) {
+        self.start_change(
+            &format!("creating group {}", create_group.cn),
+            |client| client.create_group(create_group),
+            "group created successfully",
+            Self::reload_groups,
+        );
+    }
+
+    pub fn delete_group(&mut self, cn: String) {
+        self.start_change(
+            &format!("deleting group {}", cn),
+            |client| client.delete_group(cn),
+            "group deleted successfully",
+            Self::reload_groups,
+        );
+    }
+
+    /// Save the members of `group`, changed from `group_backup`, with a job
+    /// of the worker.
+    pub fn save_group(&mut self, group_backup: Group, group: Group) {
+        let label = format!("saving group {}", group.cn);
+        let (sender, promise) = Promise::new();
+        let progress = Progress::default();
+
+        self.send_to_worker(ToWorkerMessage::Job(Job::SaveGroup {
+            group_backup,
+            group,
+            progress: progress.clone(),
+            reply: JobReply::new(sender),
+        }));
+
+        self.tasks.start(
+            Task::request(&label, promise, |app: &mut Self, result| match result {
+                Ok(()) => {
+                    app.current_info = Some("group updated successfully".to_string());
+                    app.active_action = Action::GroupEdit;
+                }
+                Err(e) => {
+                    app.set_api_error(e);
+                    app.current_info = None;
+                }
+            })
+            .with_progress(progress),
+        );
+    }
+
+    /// Forget every user display name, the shown ones are resolved again.
+    pub fn flush_user_displays(&mut self) {
+        self.store.invalidate(EntityKind::UserDisplays);
+        self.send_to_worker(ToWorkerMessage::FlushUserDisplays);
+
+        self.current_info = Some("user display cache flushed".to_string());
+        self.refresh_acl_displays();
+    }
+
+    /// Connect to the backend at `api_url` from the startup page.
+    pub fn connect(&mut self, api_url: &str) {
+        // Not to apply the lists of the previous backend.
+        self.cancel_loading();
+
+        self.api_client = self.api_client.with_api_url(api_url);
+        self.store.clear();
+        self.tasks.remove_prefixed(TASK_SUBDIRECTORIES);
+        self.expanded_directories.clear();
+        self.send_to_worker(ToWorkerMessage::SetRefreshInterval(None));
+        self.send_to_worker(ToWorkerMessage::SetClient(self.api_client.clone()));
+        self.negotiation = None;
+        self.startup_api_url = self.api_client.api_url().to_string();
+
+        self.current_error = None;
+        self.current_info = None;
+        self.startup_attempts = 0;
+
+        self.load_config();
+    }
+}
+
+/// True if `a` and `b` have the same entries, whatever their display names.
+fn same_entries(a: &[AclEntry], b: &[AclEntry]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.qualifier == b.qualifier && a.qualifier_cn == b.qualifier_cn && a.perm == b.perm
+        })
+}
+
+/// Key of the task listing the subdirectories of `path`.
+fn subdirectories_task(path: &str) -> String {
+    format!("{}{}", TASK_SUBDIRECTORIES, path)
+}
+
+/// Path of the parent of the directory `path`, `None` for a root one.
+pub fn parent_path(path: &str) -> Option<&str> {
+    path.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+/// Keep the user and group entries of the ACLs of `directories`, and their
+/// mask.
+fn filter_acls(directories: &mut [Directory]) {
+    for directory in directories.iter_mut() {
+        filter_acl(&mut directory.acls);
+    }
+}
+
+/// Keep the user and group entries of `acls`, and their mask.
+fn filter_acl(acls: &mut Vec<AclEntry>) {
+    acls.retain(|acl| match acl.qualifier {
+        Qualifier::User(_) | Qualifier::Group(_) => acl.perm != 0,
+        Qualifier::Mask => true,
+        _ => false,
+    });
+}
+
+/// Delay before the next startup attempt after `attempts` failures.
+fn startup_retry_delay(attempts: u32) -> Duration {
+    STARTUP_RETRY_MIN
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(STARTUP_RETRY_MAX)
 }
 
 fn setup_custom_fonts(ctx: &egui::Context) {
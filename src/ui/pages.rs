@@ -1 +1,3 @@
-pub mod main;
\ No newline at end of file
+pub mod login;
+pub mod main;
+pub mod startup;
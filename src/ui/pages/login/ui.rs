@@ -0,0 +1,126 @@
+use eframe::egui::{self, Context, RichText};
+use egui::{Color32, Frame, Ui};
+
+use crate::{
+    api::auth::{credentials::Token, device::DeviceFlow},
+    defines::{AF_CANCEL_CODE, AF_CONFIRM_CODE, AF_ERROR_CODE, AF_INFO_CODE, AF_LOCK_CODE},
+    ui::daenerys::{DaenerysApp, TASK_DEVICE_LOGIN},
+};
+
+pub fn update(app: &mut DaenerysApp, ctx: &Context) {
+    egui::CentralPanel::default()
+        .frame(Frame {
+            inner_margin: 15.0.into(),
+            ..Default::default()
+        })
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                // STORM logo.
+                ui.add_sized(
+                    [200., 70.],
+                    egui::Image::new(egui::include_image!("../../media/storm-logo.svg")),
+                );
+
+                ui.add_space(40.0);
+
+                ui.heading(format!("{} log in", AF_LOCK_CODE));
+
+                ui.add_space(20.0);
+
+                match app.device_flow.clone() {
+                    Some(device_flow) => render_device_login(app, &device_flow, ui),
+                    None => render_token_login(app, ui),
+                }
+
+                ui.add_space(20.0);
+
+                // Current error label.
+                if let Some(current_error) = &app.current_error {
+                    ui.label(
+                        RichText::new(format!("{} {}", AF_ERROR_CODE, current_error))
+                            .color(Color32::RED),
+                    )
+                    .on_hover_text(format!("{:?}", current_error));
+                }
+
+                // Current info label.
+                if let Some(current_info) = &app.current_info {
+                    ui.label(
+                        RichText::new(format!("{} {}", AF_INFO_CODE, current_info))
+                            .color(Color32::GREEN),
+                    );
+                }
+            });
+        });
+}
+
+/// OIDC device flow: show the code to enter on the identity provider page.
+fn render_device_login(app: &mut DaenerysApp, device_flow: &DeviceFlow, ui: &mut Ui) {
+    if let Some(authorization) = app.device_authorization.clone() {
+        ui.label("open the page below and enter the code");
+
+        let uri = authorization
+            .verification_uri_complete
+            .unwrap_or(authorization.verification_uri.clone());
+        ui.hyperlink_to(authorization.verification_uri, uri);
+
+        ui.add_space(10.0);
+
+        ui.label(RichText::new(&authorization.user_code).heading().strong());
+        if ui.button("copy code").clicked() {
+            ui.ctx().copy_text(authorization.user_code);
+        }
+
+        ui.add_space(10.0);
+
+        ui.add(egui::widgets::Spinner::new());
+        ui.label("waiting for authorization");
+
+        ui.add_space(10.0);
+
+        let button = egui::Button::new(format!("{} {}", AF_CANCEL_CODE, "cancel"));
+        if ui.add_sized([150., 30.], button).clicked() {
+            app.cancel_device_login();
+        }
+    } else if app.tasks.is_running(TASK_DEVICE_LOGIN) {
+        ui.add(egui::widgets::Spinner::new());
+    } else {
+        ui.label(format!("log in with {}", device_flow.config().issuer));
+
+        ui.add_space(10.0);
+
+        let button = egui::Button::new(format!("{} {}", AF_CONFIRM_CODE, "log in"));
+        if ui.add_sized([150., 30.], button).clicked() {
+            app.current_error = None;
+            app.current_info = None;
+            app.start_device_login();
+        }
+    }
+}
+
+/// Bearer token typed by the user.
+fn render_token_login(app: &mut DaenerysApp, ui: &mut Ui) {
+    ui.label("the backend requires an access token");
+
+    ui.add_space(10.0);
+
+    ui.add(
+        egui::TextEdit::singleline(&mut app.login_token)
+            .password(true)
+            .hint_text("access token"),
+    );
+
+    ui.add_space(10.0);
+
+    ui.add_enabled_ui(!app.login_token.trim().is_empty(), |ui| {
+        let button = egui::Button::new(format!("{} {}", AF_CONFIRM_CODE, "log in"));
+        if ui.add_sized([150., 30.], button).clicked() {
+            app.api_client
+                .credentials()
+                .set_token(Token::new(app.login_token.trim().to_string()), None);
+            app.login_token.clear();
+
+            app.logged_in();
+        }
+    });
+}
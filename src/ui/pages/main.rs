@@ -1,5 +1,6 @@
 pub mod panel_bottom;
 pub mod panel_central;
+pub mod panel_inspector;
 pub mod panel_left;
 pub mod panel_right;
 pub mod panel_top;
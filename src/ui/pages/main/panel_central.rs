@@ -2,4 +2,6 @@ pub mod directory;
 pub mod du;
 pub mod group;
 pub mod home;
+pub mod protocol;
+pub mod tasks;
 pub mod ui;
@@ -1,3 +1,5 @@
 pub mod add_group;
 pub mod add_user;
+pub mod job;
+pub mod template;
 pub mod ui;
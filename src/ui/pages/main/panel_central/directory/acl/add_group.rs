@@ -1,34 +1,32 @@
-use crate::{api::acl::save_acl, defines::AF_SAVE_CODE, ui::daenerys::DaenerysApp};
+use std::collections::HashSet;
+
+use super::ui::render_save_button;
+use crate::{store::AclQualifier, ui::daenerys::DaenerysApp};
 use egui::Ui;
-use storm_daenerys_common::types::acl::{AclEntry, Qualifier, SetAcl};
 
-pub fn render_add_group(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
+pub fn render_add_group(app: &mut DaenerysApp, ui: &mut Ui) {
+    // Users and groups already in the edited ACL.
+    let in_acl: HashSet<AclQualifier> = app
+        .acl(app.edited_acl)
+        .unwrap()
+        .iter()
+        .filter_map(AclQualifier::of)
+        .collect();
+
     // Group list.
-    if app.groups.is_some() {
-        for group in app.groups.as_ref().unwrap() {
-            if ui.link(group.clone().cn).clicked() {
-                // Find already exist.
-                let mut found: bool = false;
-                for acl in &app.current_directory.as_ref().unwrap().acls {
-                    if let Qualifier::Group(_) = acl.qualifier {
-                        if acl.qualifier_cn.as_ref().unwrap().eq(&group.cn.clone()) {
-                            found = true;
-                        }
-                    }
-                }
-
-                if !found {
-                    app.current_directory.as_mut().unwrap().acls.push(AclEntry {
-                        qualifier: storm_daenerys_common::types::acl::Qualifier::Group(0), // FIXME
-                        qualifier_cn: Some(group.cn.clone().to_string()),
-                        qualifier_display: Some(group.cn.clone().to_string()),
-                        perm: 7,
-                    });
-                }
-            }
+    let mut clicked_group = None;
+
+    for group in app.store.groups().into_iter().flatten() {
+        if ui.link(&group.cn).clicked() && !in_acl.contains(&AclQualifier::Group(group.cn.clone()))
+        {
+            clicked_group = Some(group.cn.clone());
         }
     }
 
+    if let Some(cn) = clicked_group {
+        app.add_group_to_acl(cn);
+    }
+
     // Done button.
     // let button_label = format!("{} {}", AF_CANCEL_CODE, "done");
     // let button = egui::Button::new(button_label);
@@ -38,20 +36,5 @@ pub fn render_add_group(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui)
     // }
 
     // Save button.
-    let button_label = format!("{} {}", AF_SAVE_CODE, "save");
-    let button = egui::Button::new(button_label);
-
-    if ui.add_sized([150., 30.], button).clicked() {
-        let directory_name = app.current_directory.as_ref().unwrap().name.clone();
-
-        app.current_info = Some(format!("saving acl for {}", directory_name));
-
-        let set_acl = SetAcl {
-            name: directory_name,
-            acls: app.current_directory.as_ref().unwrap().acls.clone(),
-        };
-
-        app.is_working = true;
-        app.save_directory_acl_promise = Some(save_acl(ctx, set_acl, app.api_url.clone()));
-    }
+    render_save_button(app, ui);
 }
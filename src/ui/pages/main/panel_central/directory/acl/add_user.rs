@@ -1,10 +1,11 @@
+use super::ui::render_save_button;
 use crate::{
-    api::{self, acl::save_acl},
-    defines::{AF_SAVE_CODE, AF_SEARCH_CODE},
+    defines::AF_SEARCH_CODE,
+    store::{AclQualifier, EntityKind},
     ui::daenerys::DaenerysApp,
 };
-use egui::{Key, Ui};
-use storm_daenerys_common::types::acl::{AclEntry, Qualifier, SetAcl};
+use egui::{Key, TextStyle, Ui};
+use storm_daenerys_common::types::acl::AclEntry;
 
 pub fn render_add_user(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
     ui.add_space(20.0);
@@ -21,52 +22,47 @@ pub fn render_add_user(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui)
         let button = egui::Button::new(button_label);
 
         if ui.add_sized([150., 30.], button).clicked() || ctx.input(|i| i.key_pressed(Key::Enter)) {
-            app.is_working = true;
-            app.get_users_promise = Some(api::user::get_users(
-                ctx,
-                app.user_search.clone(),
-                app.api_url.clone(),
-            ));
+            app.search_users(app.user_search.clone());
         }
     });
 
     // User list.
     let scroll_height = ui.available_height() - 50.;
+    let row_height = ui.text_style_height(&TextStyle::Body);
+
+    if let Some(users) = app.store.users() {
+        let mut clicked_user = None;
 
-    if app.users.is_some() {
         egui::ScrollArea::vertical()
             .id_salt("directory_search_user_scroll")
             .max_height(scroll_height)
-            .show(ui, |ui| {
-                for user in app.users.as_ref().unwrap().clone() {
-                    if ui
-                        .link(format!("{} [{}]", user.clone().display, user.clone().id))
-                        .clicked()
-                    {
-                        // Find already exist.
-                        let mut found: bool = false;
-                        for acl in &app.current_directory.as_ref().unwrap().acls {
-                            if let Qualifier::User(_) = acl.qualifier {
-                                if acl.qualifier_cn.as_ref().unwrap().eq(&user.id.clone()) {
-                                    found = true;
-                                }
-                            }
-                        }
-
-                        if !found {
-                            app.current_directory.as_mut().unwrap().acls.push(AclEntry {
-                                qualifier: storm_daenerys_common::types::acl::Qualifier::User(0), // FIXME
-                                qualifier_cn: Some(user.id.clone()),
-                                qualifier_display: Some(user.id.clone()),
-                                perm: 7,
-                            });
-
-                            app.user_search = "".to_string();
-                            app.users = None;
-                        }
+            .show_rows(ui, row_height, users.len(), |ui, range| {
+                for user in &users[range] {
+                    if ui.link(format!("{} [{}]", user.display, user.id)).clicked() {
+                        clicked_user = Some(user.id.clone());
                     }
                 }
             });
+
+        // Unless already in the edited ACL.
+        let acls = app.acl(app.edited_acl).unwrap();
+        if let Some(uid) = clicked_user.filter(|uid| {
+            !acls
+                .iter()
+                .filter_map(AclQualifier::of)
+                .any(|qualifier| qualifier == AclQualifier::User(uid.clone()))
+        }) {
+            app.acl_mut(app.edited_acl).unwrap().push(AclEntry {
+                qualifier: storm_daenerys_common::types::acl::Qualifier::User(0), // FIXME
+                qualifier_cn: Some(uid.clone()),
+                qualifier_display: Some(uid),
+                perm: 7,
+            });
+            app.acls_changed();
+
+            app.user_search = "".to_string();
+            app.store.invalidate(EntityKind::Users);
+        }
     }
 
     // Done button.
@@ -78,20 +74,5 @@ pub fn render_add_user(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui)
     // }
 
     // Save button.
-    let button_label = format!("{} {}", AF_SAVE_CODE, "save");
-    let button = egui::Button::new(button_label);
-
-    if ui.add_sized([150., 30.], button).clicked() {
-        let directory_name = app.current_directory.as_ref().unwrap().name.clone();
-
-        app.current_info = Some(format!("saving acl for {}", directory_name));
-
-        let set_acl = SetAcl {
-            name: directory_name,
-            acls: app.current_directory.as_ref().unwrap().acls.clone(),
-        };
-
-        app.is_working = true;
-        app.save_directory_acl_promise = Some(save_acl(ctx, set_acl, app.api_url.clone()));
-    }
+    render_save_button(app, ui);
 }
@@ -0,0 +1,127 @@
+use crate::{
+    api::acljob::AclJobState,
+    defines::{AF_CANCEL_CODE, AF_CONFIRM_CODE, AF_REFRESH_CODE, AF_SAVE_CODE, AF_WARNING_CODE},
+    ui::daenerys::DaenerysApp,
+};
+use egui::{Color32, Ui};
+
+// Failed paths listed before the report is needed.
+const SHOWN_FAILURES: usize = 5;
+
+/// Progress and failures of the recursive ACL job started on the directory
+/// `path` or one of its parents.
+pub fn render_acl_job(app: &mut DaenerysApp, ui: &mut Ui, path: &str) {
+    let Some(acl_job) = app
+        .acl_job
+        .as_ref()
+        .filter(|acl_job| path == acl_job.name || path.starts_with(&format!("{}/", acl_job.name)))
+    else {
+        return;
+    };
+
+    let mut cancel = false;
+    let mut follow = false;
+    let mut save_report = false;
+    let mut dismiss = false;
+
+    ui.add_space(10.0);
+
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            match acl_job.state {
+                AclJobState::Running => {
+                    ui.add(egui::widgets::Spinner::new());
+                    ui.label(format!("applying the acl below {}", acl_job.name));
+                }
+                AclJobState::Done => {
+                    ui.label(format!(
+                        "{} acl applied below {}",
+                        AF_CONFIRM_CODE, acl_job.name
+                    ));
+                }
+                AclJobState::Cancelled => {
+                    ui.label(format!("acl application below {} cancelled", acl_job.name));
+                }
+            }
+
+            // Files processed, out of the total once counted.
+            let processed = match acl_job.total {
+                Some(total) => format!("{} / {} processed", acl_job.processed, total),
+                None => format!("{} processed", acl_job.processed),
+            };
+            ui.label(processed);
+
+            if let Some(fraction) = acl_job.fraction() {
+                ui.add(
+                    egui::ProgressBar::new(fraction)
+                        .desired_width(150.)
+                        .show_percentage(),
+                );
+            }
+        });
+
+        if !acl_job.failures.is_empty() {
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} {} failed",
+                    AF_WARNING_CODE,
+                    acl_job.failures.len()
+                ))
+                .color(Color32::RED),
+            );
+
+            for failure in acl_job.failures.iter().take(SHOWN_FAILURES) {
+                ui.label(
+                    egui::RichText::new(format!("{}: {}", failure.path, failure.error)).weak(),
+                );
+            }
+            if acl_job.failures.len() > SHOWN_FAILURES {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "and {} more in the report",
+                        acl_job.failures.len() - SHOWN_FAILURES
+                    ))
+                    .weak(),
+                );
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if acl_job.is_running() {
+                // The status is not requested anymore after a failure.
+                if !app.is_following_acl_job() {
+                    let button =
+                        egui::Button::new(format!("{} {}", AF_REFRESH_CODE, "check again"));
+                    follow = ui.add_sized([150., 25.], button).clicked();
+                }
+
+                let button = egui::Button::new(format!("{} {}", AF_CANCEL_CODE, "cancel"));
+                cancel = ui.add_sized([150., 25.], button).clicked();
+            } else {
+                let button = egui::Button::new(format!("{} {}", AF_CONFIRM_CODE, "dismiss"));
+                dismiss = ui.add_sized([150., 25.], button).clicked();
+            }
+
+            if !acl_job.failures.is_empty() {
+                let button = egui::Button::new(format!("{} {}", AF_SAVE_CODE, "save the report"));
+                save_report = ui
+                    .add_sized([150., 25.], button)
+                    .on_hover_text("the failed paths and their error, in the download directory")
+                    .clicked();
+            }
+        });
+    });
+
+    if cancel {
+        app.cancel_acl_job();
+    }
+    if follow {
+        app.follow_acl_job();
+    }
+    if save_report {
+        app.save_acl_report();
+    }
+    if dismiss {
+        app.dismiss_acl_job();
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    acl::{diff, perm_string, AclChange},
+    defines::{AF_CANCEL_CODE, AF_SAVE_CODE, AF_WARNING_CODE},
+    store::AclQualifier,
+    ui::daenerys::{Action, DaenerysApp},
+};
+use egui::{Color32, Ui};
+
+/// Choice of an ACL template and the changes it makes to the access ACL of
+/// the current directory, saved once confirmed.
+pub fn render_apply_template(app: &mut DaenerysApp, ui: &mut Ui) {
+    let mut selected = None;
+
+    ui.horizontal(|ui| {
+        ui.label("template");
+
+        let selected_name = app
+            .acl_template
+            .and_then(|index| app.acl_templates.get(index))
+            .map_or("choose a template", |template| template.name.as_str());
+
+        egui::ComboBox::from_id_salt("acl_template")
+            .selected_text(selected_name)
+            .show_ui(ui, |ui| {
+                for (index, template) in app.acl_templates.iter().enumerate() {
+                    if ui
+                        .selectable_label(app.acl_template == Some(index), &template.name)
+                        .clicked()
+                    {
+                        selected = Some(index);
+                    }
+                }
+            });
+    });
+
+    if let Some(index) = selected {
+        app.select_acl_template(index);
+    }
+
+    ui.add_space(10.0);
+
+    if let Some(index) = app.acl_template {
+        let acls = app.template_acl(index);
+        let changes = diff(&app.current_directory.as_ref().unwrap().acls, &acls);
+
+        // Template groups missing from the STORM groups, once loaded.
+        let missing: Vec<String> = acls
+            .iter()
+            .filter_map(|acl| match AclQualifier::of(acl)? {
+                AclQualifier::Group(cn) => Some(cn),
+                AclQualifier::User(_) => None,
+            })
+            .filter(|cn| app.store.groups().is_some() && app.store.group(cn).is_none())
+            .collect();
+
+        if changes.is_empty() {
+            ui.label(egui::RichText::new("no change: the access rights already match").italics());
+        }
+
+        egui::Grid::new("acl_template_diff")
+            .num_columns(3)
+            .show(ui, |ui| {
+                for change in &changes {
+                    let (sign, qualifier, rights, color) = match change {
+                        AclChange::Added(qualifier, perm) => {
+                            ("+", qualifier, perm_string(*perm), Color32::GREEN)
+                        }
+                        AclChange::Removed(qualifier, perm) => {
+                            ("-", qualifier, perm_string(*perm), Color32::RED)
+                        }
+                        AclChange::Changed(qualifier, from, to) => (
+                            "~",
+                            qualifier,
+                            format!("{} -> {}", perm_string(*from), perm_string(*to)),
+                            Color32::YELLOW,
+                        ),
+                    };
+                    let name = match qualifier {
+                        AclQualifier::User(uid) => format!("user {}", uid),
+                        AclQualifier::Group(cn) => format!("group {}", cn),
+                    };
+
+                    ui.label(egui::RichText::new(sign).monospace().color(color));
+                    ui.label(egui::RichText::new(name).color(color));
+                    ui.label(egui::RichText::new(rights).monospace().color(color));
+                    ui.end_row();
+                }
+            });
+
+        for cn in missing {
+            ui.label(
+                egui::RichText::new(format!("{} no group {}", AF_WARNING_CODE, cn))
+                    .color(Color32::YELLOW),
+            );
+        }
+    }
+
+    ui.add_space(20.0);
+
+    ui.horizontal_top(|ui| {
+        let button_label = format!("{} {}", AF_SAVE_CODE, "save");
+        let button = egui::Button::new(button_label);
+
+        // Not before the gids of the template groups are known.
+        let enabled = app.can_apply_acl_template();
+        let saving = ui
+            .add_enabled_ui(enabled, |ui| {
+                ui.add_sized([150., 30.], button)
+                    .on_disabled_hover_text("choose a template, the gids of its groups resolved")
+            })
+            .inner;
+
+        if saving.clicked() {
+            app.apply_acl_template();
+        }
+
+        let button_label = format!("{} {}", AF_CANCEL_CODE, "cancel");
+        let button = egui::Button::new(button_label);
+
+        if ui.add_sized([150., 30.], button).clicked() {
+            app.acl_template = None;
+            app.active_action = Action::DirectoryEdit;
+        }
+    });
+}
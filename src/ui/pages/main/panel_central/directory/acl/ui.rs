@@ -1,216 +1,122 @@
-use super::{add_group::render_add_group, add_user::render_add_user};
+use super::{
+    add_group::render_add_group, add_user::render_add_user, template::render_apply_template,
+};
 use crate::{
-    api::acl::save_acl,
+    acl::{effective, mask, perm_string, AclKind, EXECUTE, READ, WRITE},
+    api::acljob::AclJobStatus,
     defines::{
-        AF_ADD_CODE, AF_ADMIN_CODE, AF_DELETE_CODE, AF_EYE_CODE, AF_GROUP_CODE, AF_SAVE_CODE,
+        AF_ADD_CODE, AF_ADMIN_CODE, AF_COPY_CODE, AF_DELETE_CODE, AF_GROUP_CODE, AF_SAVE_CODE,
         AF_USER_CODE,
     },
-    ui::daenerys::{Action, DaenerysApp},
+    store::{AclQualifier, EntityKind},
+    ui::{
+        daenerys::{Action, DaenerysApp},
+        rows::button_row_height,
+    },
 };
 use egui::{Color32, Ui};
-use storm_daenerys_common::types::acl::SetAcl;
+use storm_daenerys_common::types::acl::{AclEntry, Qualifier};
 
 pub fn render_show_edit_acl(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
     ui.add_space(20.0);
 
-    let scroll_height = ui.available_height() - 50.;
+    // The template preview instead of the entries.
+    if app.active_action == Action::DirectoryEditAclTemplate {
+        render_apply_template(app, ui);
+        return;
+    }
 
-    egui::ScrollArea::vertical()
-        .id_salt("acl_list_scroll")
-        .max_height(scroll_height)
-        .show(ui, |ui| {
-            egui::Grid::new("acl_list").num_columns(3).show(ui, |ui| {
-                ui.add_enabled_ui(app.active_action == Action::DirectoryEditAcl, |ui| {
-                    egui::Grid::new("acl_list_edit")
-                        .num_columns(4)
-                        .show(ui, |ui| {
-                            // Sort current directory ACLs.
-                            app.current_directory
-                                .as_mut()
-                                .unwrap()
-                                .acls
-                                .sort_by(|a, b| a.qualifier_display.cmp(&b.qualifier_display));
-
-                            // Loop throught non mutable clone of ACLs.
-                            for acl in app.current_directory.as_ref().unwrap().acls.clone() {
-                                let is_admin: bool = acl.is_admin(&app.admin.clone().unwrap());
-                                let mut read_only: bool = acl.is_readonly();
-
-                                match acl.qualifier {
-                                    storm_daenerys_common::types::acl::Qualifier::User(_) => {
-                                        // User icon.
-                                        ui.label(AF_USER_CODE.to_string());
-
-                                        // User cn.
-                                        let user_cn = acl.qualifier_display.as_ref().unwrap();
-                                        let color = if user_cn.starts_with('<') {
-                                            Color32::RED
-                                        } else {
-                                            Color32::WHITE
-                                        };
-
-                                        let qualifier_display = if read_only {
-                                            format!("{} {}", user_cn, AF_EYE_CODE)
-                                        } else {
-                                            user_cn.to_string()
-                                        };
-
-                                        ui.label(
-                                            egui::RichText::new(qualifier_display).color(color),
-                                        );
-
-                                        if app.active_action == Action::DirectoryEditAcl
-                                            && !is_admin
-                                        {
-                                            if ui
-                                                .checkbox(
-                                                    &mut read_only,
-                                                    egui::RichText::new("read only").italics(),
-                                                )
-                                                .changed()
-                                            {
-                                                // Loop throught mutable ACLs of current directory
-                                                // and toggle read only on the one with the cn been checked.
-                                                for app_current_directory_acl in app
-                                                    .current_directory
-                                                    .as_mut()
-                                                    .unwrap()
-                                                    .acls
-                                                    .iter_mut()
-                                                {
-                                                    if app_current_directory_acl
-                                                        .qualifier_cn
-                                                        .as_ref()
-                                                        .unwrap()
-                                                        .eq(acl.qualifier_cn.as_ref().unwrap())
-                                                    {
-                                                        app_current_directory_acl
-                                                            .toggle_read_only();
-                                                    }
-                                                }
-                                            };
-                                        } else if is_admin {
-                                            // Admin icon.
-                                            ui.label(AF_ADMIN_CODE.to_string());
-                                        }
-                                    }
-                                    storm_daenerys_common::types::acl::Qualifier::Group(_) => {
-                                        // Group icon.
-                                        ui.label(AF_GROUP_CODE.to_string());
-
-                                        // Group cn.
-                                        let group_cn = acl.qualifier_cn.as_ref().unwrap();
-
-                                        let qualifier_display = if acl.is_read_only() {
-                                            format!("{} {}", group_cn, AF_EYE_CODE)
-                                        } else {
-                                            group_cn.to_string()
-                                        };
-
-                                        ui.label(qualifier_display);
-
-                                        if app.active_action == Action::DirectoryEditAcl
-                                            && !is_admin
-                                        {
-                                            if ui
-                                                .checkbox(
-                                                    &mut read_only,
-                                                    egui::RichText::new("read only").italics(),
-                                                )
-                                                .changed()
-                                            {
-                                                // Loop throught mutable ACLs of current directory
-                                                // and toggle read only on the one with the cn been checked.
-                                                for app_active_directory_acl in app
-                                                    .current_directory
-                                                    .as_mut()
-                                                    .unwrap()
-                                                    .acls
-                                                    .iter_mut()
-                                                {
-                                                    if app_active_directory_acl
-                                                        .qualifier_cn
-                                                        .as_ref()
-                                                        .unwrap()
-                                                        .eq(acl.qualifier_cn.as_ref().unwrap())
-                                                    {
-                                                        app_active_directory_acl.toggle_read_only();
-                                                    }
-                                                }
-                                            };
-                                        } else if is_admin {
-                                            // Admin icon.
-                                            ui.label(AF_ADMIN_CODE.to_string());
-                                        }
-                                    }
-                                    _ => (),
-                                }
+    let editing = app.active_action == Action::DirectoryEditAcl;
+    let with_default = app.can_edit_default_acl();
 
-                                // Delete acl button.
-                                if app.active_action == Action::DirectoryEditAcl && !is_admin {
-                                    let button_label =
-                                        format!("{} {}", AF_DELETE_CODE, "delete entry");
-                                    let button = egui::Button::new(button_label);
-
-                                    if ui.add_sized([150., 25.], button).clicked() {
-                                        app.current_directory.as_mut().unwrap().acls.retain(|a| {
-                                            match a.qualifier_cn.clone() {
-                                                Some(qualidier_cn) => qualidier_cn
-                                                    .ne(acl.qualifier_cn.as_ref().unwrap()),
-                                                None => true, // non User(u) or Group(g) acl
-                                            }
-                                        });
-                                    }
-                                }
+    // Both sections share the height.
+    let sections = if with_default { 2. } else { 1. };
+    let scroll_height = ((ui.available_height() - 150.) / sections).max(100.);
 
-                                ui.end_row();
-                            }
-                        });
-                });
-            });
-        });
+    ui.heading("access");
+    render_acl(app, ui, AclKind::Access, editing, scroll_height);
+
+    if with_default {
+        ui.add_space(20.0);
+        ui.heading("default");
+        ui.label(
+            egui::RichText::new(
+                "inherited by the files and subdirectories created in the directory",
+            )
+            .italics(),
+        );
+
+        match app.current_default_acl.as_ref() {
+            None => {
+                ui.label("loading the default acl");
+            }
+            Some(default_acl) if default_acl.is_empty() => {
+                ui.label(
+                    egui::RichText::new(
+                        "no default acl: the new entries do not inherit these rights",
+                    )
+                    .italics(),
+                );
+            }
+            Some(_) => render_acl(app, ui, AclKind::Default, editing, scroll_height),
+        }
+    }
 
     // Add user, add group and save buttons.
     if app.active_action == Action::DirectoryEditAcl {
         ui.add_space(20.0);
 
-        ui.horizontal_top(|ui| {
-            let button_label = format!("{} {}", AF_ADD_CODE, "add user");
-            let button = egui::Button::new(button_label);
+        let kinds: &[AclKind] = if app.current_default_acl.is_some() {
+            &[AclKind::Access, AclKind::Default]
+        } else {
+            &[AclKind::Access]
+        };
 
-            if ui.add_sized([150., 30.], button).clicked() {
-                app.active_action = Action::DirectoryEditAclAddUser;
-                app.users = None;
-            }
+        for kind in kinds {
+            ui.horizontal_top(|ui| {
+                let section = match kind {
+                    AclKind::Access => "access",
+                    AclKind::Default => "default",
+                };
 
-            let button_label = format!("{} {}", AF_ADD_CODE, "add group");
-            let button = egui::Button::new(button_label);
+                let button_label = format!("{} add user to {}", AF_ADD_CODE, section);
+                let button = egui::Button::new(button_label);
 
-            if ui.add_sized([150., 30.], button).clicked() {
-                app.active_action = Action::DirectoryEditAclAddGroup;
-            }
-        });
+                if ui.add_sized([200., 30.], button).clicked() {
+                    app.edited_acl = *kind;
+                    app.active_action = Action::DirectoryEditAclAddUser;
+                    app.store.invalidate(EntityKind::Users);
+                }
 
-        ui.add_space(20.0);
+                let button_label = format!("{} add group to {}", AF_ADD_CODE, section);
+                let button = egui::Button::new(button_label);
 
-        // Save button.
-        if app.active_action == Action::DirectoryEditAcl {
-            let button_label = format!("{} {}", AF_SAVE_CODE, "save");
-            let button = egui::Button::new(button_label);
+                if ui.add_sized([200., 30.], button).clicked() {
+                    app.edited_acl = *kind;
+                    app.active_action = Action::DirectoryEditAclAddGroup;
+                }
 
-            if ui.add_sized([150., 30.], button).clicked() {
-                let directory_name = app.current_directory.as_ref().unwrap().name.clone();
+                // Copy of the access entries.
+                if *kind == AclKind::Default {
+                    let button_label = format!("{} {}", AF_COPY_CODE, "make default match access");
+                    let button = egui::Button::new(button_label);
 
-                app.current_info = Some(format!("saving acl for {}", directory_name));
+                    if ui.add_sized([250., 30.], button).clicked() {
+                        app.current_default_acl = app
+                            .current_directory
+                            .as_ref()
+                            .map(|directory| directory.acls.clone());
+                        app.acls_changed();
+                    }
+                }
+            });
+        }
 
-                let set_acl = SetAcl {
-                    name: directory_name,
-                    acls: app.current_directory.as_ref().unwrap().acls.clone(),
-                };
+        ui.add_space(20.0);
 
-                app.is_working = true;
-                app.save_directory_acl_promise = Some(save_acl(ctx, set_acl, app.api_url.clone()));
-            }
+        // Save button.
+        if app.active_action == Action::DirectoryEditAcl {
+            render_save_button(app, ui);
         }
     }
 
@@ -221,6 +127,223 @@ pub fn render_show_edit_acl(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut
 
     // Add group.
     if app.active_action == Action::DirectoryEditAclAddGroup {
-        render_add_group(app, ctx, ui)
+        render_add_group(app, ui)
+    }
+}
+
+/// Save button of the access and default ACLs, disabled until the gids of
+/// the added groups are known.
+pub fn render_save_button(app: &mut DaenerysApp, ui: &mut Ui) {
+    let button_label = format!("{} {}", AF_SAVE_CODE, "save");
+    let button = egui::Button::new(button_label);
+
+    let saving = ui
+        .horizontal(|ui| {
+            let saving = ui
+                .add_enabled_ui(app.pending_gids.is_empty(), |ui| {
+                    ui.add_sized([150., 30.], button)
+                        .on_disabled_hover_text("resolving the gid of the added groups")
+                })
+                .inner;
+
+            // One recursive application at a time.
+            if app.can_apply_recursively() {
+                let running = app.acl_job.as_ref().is_some_and(AclJobStatus::is_running);
+
+                ui.add_enabled(
+                    !running,
+                    egui::Checkbox::new(&mut app.apply_recursively, "apply recursively"),
+                )
+                .on_hover_text("set the acl of every file and directory below this one too")
+                .on_disabled_hover_text("an acl is already being applied recursively");
+            }
+
+            saving
+        })
+        .inner;
+
+    if saving.clicked() {
+        app.save_acls();
+    }
+}
+
+/// Mask and entries of the ACL of `kind`.
+fn render_acl(app: &mut DaenerysApp, ui: &mut Ui, kind: AclKind, editing: bool, height: f32) {
+    // Mask, limiting the rights of the user and group entries.
+    render_mask(app, ui, kind, editing);
+
+    ui.add_space(10.0);
+
+    let row_height = button_row_height(ui).max(25.);
+    let count = app.acl_rows(kind).len();
+    let mask = mask(app.acl(kind).unwrap());
+    let mut changed = None;
+    let mut deleted = None;
+
+    egui::ScrollArea::vertical()
+        .id_salt(("acl_list_scroll", kind))
+        .max_height(height)
+        .show_rows(ui, row_height, count, |ui, range| {
+            // Only the visible entries are drawn.
+            let rows = app.acl_rows(kind)[range].to_vec();
+            let acls: Vec<AclEntry> = rows
+                .into_iter()
+                .map(|index| app.acl(kind).unwrap()[index].clone())
+                .collect();
+
+            egui::Grid::new(("acl_list_edit", kind))
+                .num_columns(5)
+                .show(ui, |ui| {
+                    for acl in acls {
+                        let Some(qualifier) = AclQualifier::of(&acl) else {
+                            continue;
+                        };
+                        let is_admin: bool = acl.is_admin(&app.admin.clone().unwrap());
+
+                        match qualifier {
+                            AclQualifier::User(_) => {
+                                // User icon.
+                                ui.label(AF_USER_CODE.to_string());
+
+                                // User display name.
+                                let user_display =
+                                    acl.qualifier_display.clone().unwrap_or_default();
+                                let color = if user_display.starts_with('<') {
+                                    Color32::RED
+                                } else {
+                                    Color32::WHITE
+                                };
+
+                                ui.label(egui::RichText::new(user_display).color(color));
+                            }
+                            AclQualifier::Group(ref cn) => {
+                                // Group icon.
+                                ui.label(AF_GROUP_CODE.to_string());
+
+                                // Group cn.
+                                ui.label(cn);
+                            }
+                        }
+
+                        // Rights.
+                        if editing && !is_admin {
+                            let mut perm = acl.perm;
+                            if ui.horizontal(|ui| perm_checkboxes(ui, &mut perm)).inner {
+                                changed = Some((qualifier.clone(), perm));
+                            }
+                        } else {
+                            ui.horizontal(|ui| {
+                                ui.monospace(perm_string(acl.perm));
+
+                                if is_admin {
+                                    // Admin icon.
+                                    ui.label(AF_ADMIN_CODE.to_string());
+                                }
+                            });
+                        }
+
+                        // Rights once the mask applied.
+                        let effective_perm = effective(acl.perm, mask);
+                        if effective_perm == acl.perm {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "effective {}",
+                                    perm_string(effective_perm)
+                                ))
+                                .monospace()
+                                .weak(),
+                            );
+                        } else {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "effective {}",
+                                    perm_string(effective_perm)
+                                ))
+                                .monospace()
+                                .color(Color32::YELLOW),
+                            )
+                            .on_hover_text(format!(
+                                "{} granted, the mask {} removes {}",
+                                perm_string(acl.perm),
+                                perm_string(mask.unwrap_or_default()),
+                                perm_string(acl.perm & !effective_perm),
+                            ));
+                        }
+
+                        // Delete acl button.
+                        if editing && !is_admin {
+                            let button_label = format!("{} {}", AF_DELETE_CODE, "delete entry");
+                            let button = egui::Button::new(button_label);
+
+                            if ui.add_sized([150., 25.], button).clicked() {
+                                deleted = Some(qualifier);
+                            }
+                        }
+
+                        ui.end_row();
+                    }
+                });
+        });
+
+    let acls = app.acl_mut(kind).unwrap();
+    if let Some((qualifier, perm)) = changed {
+        for acl in acls.iter_mut() {
+            if AclQualifier::of(acl).as_ref() == Some(&qualifier) {
+                acl.perm = perm;
+            }
+        }
+    }
+    if let Some(qualifier) = deleted {
+        acls.retain(|acl| AclQualifier::of(acl).as_ref() != Some(&qualifier));
+        app.acls_changed();
     }
 }
+
+/// The mask entry and how it limits the other entries.
+fn render_mask(app: &mut DaenerysApp, ui: &mut Ui, kind: AclKind, editing: bool) {
+    let acls = app.acl_mut(kind).unwrap();
+
+    let Some(mask) = acls.iter_mut().find(|acl| acl.qualifier == Qualifier::Mask) else {
+        if acls.iter().any(|acl| AclQualifier::of(acl).is_some()) {
+            ui.label(
+                egui::RichText::new("no mask: each user and group has the rights of its entry")
+                    .italics(),
+            );
+        }
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("mask");
+
+        if editing {
+            perm_checkboxes(ui, &mut mask.perm);
+        } else {
+            ui.monospace(perm_string(mask.perm));
+        }
+    });
+
+    ui.label(
+        egui::RichText::new(
+            "the mask is the most a user or group entry grants: \
+             its effective rights are the ones both in the entry and in the mask",
+        )
+        .italics(),
+    );
+}
+
+/// Read, write and execute checkboxes of `perm`, returns true if changed.
+fn perm_checkboxes(ui: &mut Ui, perm: &mut u32) -> bool {
+    let mut changed = false;
+
+    for (bit, label) in [(READ, "r"), (WRITE, "w"), (EXECUTE, "x")] {
+        let mut granted = *perm & bit != 0;
+
+        if ui.checkbox(&mut granted, label).changed() {
+            *perm ^= bit;
+            changed = true;
+        }
+    }
+
+    changed
+}
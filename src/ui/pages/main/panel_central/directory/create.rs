@@ -1,9 +1,9 @@
 use egui::Ui;
 use storm_daenerys_common::types::directory::CreateDirectory;
 
-use crate::{api, defines::AF_CREATE_CODE, ui::daenerys::DaenerysApp};
+use crate::{defines::AF_CREATE_CODE, ui::daenerys::DaenerysApp};
 
-pub fn render_create_directory(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
+pub fn render_create_directory(app: &mut DaenerysApp, ui: &mut Ui) {
     // Directory name.
     ui.vertical(|ui| {
         ui.horizontal(|ui| {
@@ -41,12 +41,7 @@ pub fn render_create_directory(app: &mut DaenerysApp, ctx: &egui::Context, ui: &
                     name: app.create_directory_name.clone(),
                 };
 
-                app.is_working = true;
-                app.create_directory_promise = Some(api::directory::create_directory(
-                    ctx,
-                    create_directory,
-                    app.api_url.clone(),
-                ));
+                app.create_directory(create_directory);
 
                 app.create_directory_name.clear();
             }
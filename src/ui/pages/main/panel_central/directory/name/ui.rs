@@ -1,8 +1,8 @@
-use crate::{api, defines::AF_RENAME_CODE, ui::daenerys::DaenerysApp};
+use crate::{defines::AF_RENAME_CODE, ui::daenerys::DaenerysApp};
 use egui::Ui;
 use storm_daenerys_common::types::directory::RenameDirectory;
 
-pub fn render_rename(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
+pub fn render_rename(app: &mut DaenerysApp, ui: &mut Ui) {
     // Directory name.
     ui.vertical(|ui| {
         ui.horizontal(|ui| {
@@ -41,12 +41,7 @@ pub fn render_rename(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
                     new_name: app.create_directory_name.clone(),
                 };
 
-                app.is_working = true;
-                app.rename_directory_promise = Some(api::directory::rename_directory(
-                    ctx,
-                    rename_directory,
-                    app.api_url.clone(),
-                ));
+                app.rename_directory(rename_directory);
 
                 app.create_directory_name.clear();
             }
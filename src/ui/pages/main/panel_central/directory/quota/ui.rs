@@ -1,12 +1,9 @@
 use egui::Ui;
 use storm_daenerys_common::types::quota::{QuotaUnit, SetQuota};
 
-use crate::{
-    api::quota::save_quota, defines::AF_SAVE_CODE, error::apperror::AppError,
-    ui::daenerys::DaenerysApp,
-};
+use crate::{defines::AF_SAVE_CODE, error::apperror::AppError, ui::daenerys::DaenerysApp};
 
-pub fn render_edit_quota(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
+pub fn render_edit_quota(app: &mut DaenerysApp, ui: &mut Ui) {
     ui.label("Set quota to 0 to remove it.");
 
     ui.add_space(10.0);
@@ -72,9 +69,7 @@ pub fn render_edit_quota(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui
                     quota: new_quota,
                 };
 
-                app.is_working = true;
-                app.save_directory_quota_promise =
-                    Some(save_quota(ctx, set_quota, app.api_url.clone()));
+                app.save_quota(set_quota);
             }
         }
     });
@@ -1,19 +1,63 @@
-use super::{acl::ui::render_show_edit_acl, name::ui::render_rename, quota::ui::render_edit_quota};
+use super::{
+    acl::{job::render_acl_job, ui::render_show_edit_acl},
+    name::ui::render_rename,
+    quota::ui::render_edit_quota,
+};
 use crate::{
-    api,
-    defines::{AF_DELETE_CODE, AF_EDIT_CODE, AF_FOLDER_CODE, AF_QUOTA_CODE, AF_RENAME_CODE},
-    ui::daenerys::{Action, DaenerysApp},
+    acl::{inheritance, Inheritance},
+    defines::{
+        AF_DELETE_CODE, AF_EDIT_CODE, AF_FOLDER_CODE, AF_QUOTA_CODE, AF_RENAME_CODE,
+        AF_TEMPLATE_CODE,
+    },
+    ui::daenerys::{parent_path, Action, DaenerysApp},
 };
 use egui::Ui;
 use human_bytes::human_bytes;
 use storm_daenerys_common::types::directory::CreateDirectory;
 
 pub fn render_show_directory(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
+    let path = app.current_directory.as_ref().unwrap().name.clone();
+    let is_root = parent_path(&path).is_none();
+
+    // Breadcrumbs, the loaded parents can be opened.
+    if !is_root {
+        let mut opened = None;
+
+        ui.horizontal(|ui| {
+            let mut ancestor = String::new();
+
+            for component in path.split('/') {
+                if !ancestor.is_empty() {
+                    ui.label("/");
+                    ancestor.push('/');
+                }
+                ancestor.push_str(component);
+
+                match app.store.directory(&ancestor).filter(|_| ancestor != path) {
+                    Some(directory) => {
+                        if ui.link(component).clicked() {
+                            opened = Some(directory.clone());
+                        }
+                    }
+                    None => {
+                        ui.label(component);
+                    }
+                }
+            }
+        });
+
+        if let Some(directory) = opened {
+            app.open_directory(directory);
+            return;
+        }
+    }
+
     // Directory name.
     ui.heading(format!(
         "{} {}",
         AF_FOLDER_CODE,
-        app.current_directory.as_ref().unwrap().name
+        path.rsplit_once('/')
+            .map_or(path.as_str(), |(_, name)| name)
     ));
     if let Some(quota) = app.current_directory.as_ref().unwrap().quota {
         if quota.ne(&0) {
@@ -21,13 +65,36 @@ pub fn render_show_directory(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mu
         }
     }
 
+    // ACL compared with the one of the parent.
+    if let Some(parent) = parent_path(&path).and_then(|parent| app.store.directory(parent)) {
+        let status = match inheritance(&app.current_directory.as_ref().unwrap().acls, &parent.acls)
+        {
+            Inheritance::Inherited => format!("access rights inherited from {}", parent.name),
+            Inheritance::Changed { own, removed } => {
+                let mut status = format!("access rights changed from {}", parent.name);
+                if !own.is_empty() {
+                    status.push_str(&format!(", own: {}", own.join(", ")));
+                }
+                if !removed.is_empty() {
+                    status.push_str(&format!(", removed: {}", removed.join(", ")));
+                }
+                status
+            }
+        };
+
+        ui.label(egui::RichText::new(status).italics());
+    }
+
+    // Recursive ACL application of the directory or a parent.
+    render_acl_job(app, ui, &path);
+
     // ACLs details and edition.
     if app.active_action.to_string().starts_with("directory_edit") {
         render_show_edit_acl(app, ctx, ui);
     }
 
     // ACLs, quota edit, rename and delete buttons.
-    if app.active_action == Action::DirectoryEdit {
+    if app.active_action == Action::DirectoryEdit && !app.is_read_only() {
         ui.add_space(20.0);
 
         ui.horizontal_top(|ui| {
@@ -38,6 +105,21 @@ pub fn render_show_directory(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mu
                 app.active_action = Action::DirectoryEditAcl;
             }
 
+            if !app.acl_templates.is_empty() {
+                let button_label = format!("{} {}", AF_TEMPLATE_CODE, "apply a template");
+                let button = egui::Button::new(button_label);
+
+                if ui.add_sized([150., 30.], button).clicked() {
+                    app.acl_template = None;
+                    app.active_action = Action::DirectoryEditAclTemplate;
+                }
+            }
+
+            // The quota, name and existence of the root directories only.
+            if !is_root {
+                return;
+            }
+
             let button_label = format!("{} {}", AF_EDIT_CODE, "edit quota");
             let button = egui::Button::new(button_label);
 
@@ -80,12 +162,7 @@ pub fn render_show_directory(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mu
                         name: current_directory,
                     };
 
-                    app.is_working = true;
-                    app.delete_directory_promise = Some(api::directory::delete_directory(
-                        ctx,
-                        delete_directory,
-                        app.api_url.clone(),
-                    ));
+                    app.delete_directory(delete_directory);
                 }
             }
         });
@@ -93,11 +170,11 @@ pub fn render_show_directory(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mu
 
     // Quota edition.
     if app.active_action == Action::DirectoryEditQuota {
-        render_edit_quota(app, ctx, ui);
+        render_edit_quota(app, ui);
     }
 
     // Name edition.
     if app.active_action == Action::DirectoryEditRename {
-        render_rename(app, ctx, ui);
+        render_rename(app, ui);
     }
 }
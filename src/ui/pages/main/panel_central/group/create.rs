@@ -1,9 +1,9 @@
 use egui::Ui;
 use storm_daenerys_common::types::group::Group;
 
-use crate::{api, defines::AF_CREATE_CODE, ui::daenerys::DaenerysApp};
+use crate::{defines::AF_CREATE_CODE, ui::daenerys::DaenerysApp};
 
-pub fn render_create_group(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
+pub fn render_create_group(app: &mut DaenerysApp, ui: &mut Ui) {
     // Group name.
     ui.vertical(|ui| {
         ui.horizontal(|ui| {
@@ -53,12 +53,7 @@ pub fn render_create_group(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut
                     member: None,
                 };
 
-                app.is_working = true;
-                app.create_group_promise = Some(api::group::create_group(
-                    ctx,
-                    create_group,
-                    app.api_url.clone(),
-                ));
+                app.create_group(create_group);
 
                 app.create_group_name.clear();
                 app.create_group_description.clear();
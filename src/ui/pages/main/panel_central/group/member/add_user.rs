@@ -1,8 +1,8 @@
-use egui::{Key, Ui};
+use egui::{Key, TextStyle, Ui};
 
 use crate::{
-    api,
     defines::{AF_CANCEL_CODE, AF_SEARCH_CODE},
+    store::EntityKind,
     ui::daenerys::{Action, DaenerysApp},
 };
 
@@ -20,56 +20,45 @@ pub fn render_add_user(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui)
         let button = egui::Button::new(button_label);
 
         if ui.add_sized([150., 30.], button).clicked() || ctx.input(|i| i.key_pressed(Key::Enter)) {
-            app.is_working = true;
-            app.get_users_promise = Some(api::user::get_users(
-                ctx,
-                app.user_search.clone(),
-                app.api_url.clone(),
-            ));
+            app.search_users(app.user_search.clone());
         }
     });
 
     // User list.
     let scroll_height = ui.available_height() - 50.;
+    let row_height = ui.text_style_height(&TextStyle::Body);
+
+    if let Some(users) = app.store.users() {
+        let mut clicked_user = None;
 
-    if app.users.is_some() {
         egui::ScrollArea::vertical()
             .id_salt("group_search_user_scroll")
             .max_height(scroll_height)
-            .show(ui, |ui| {
-                for user in app.users.as_ref().unwrap().clone() {
-                    if ui
-                        .link(format!("{} [{}]", user.clone().display, user.clone().id))
-                        .clicked()
-                    {
-                        // Find already exist.
-                        let mut found: bool = false;
-
-                        if app.current_group.as_ref().unwrap().member.is_some() {
-                            for m in app.current_group.as_ref().unwrap().member.as_ref().unwrap() {
-                                if m.eq(&user.id.clone()) {
-                                    found = true;
-                                }
-                            }
-                        } else {
-                            app.current_group.as_mut().unwrap().member = Some(Vec::new());
-                        }
-
-                        if !found {
-                            app.current_group
-                                .as_mut()
-                                .unwrap()
-                                .member
-                                .as_mut()
-                                .unwrap()
-                                .push(user.id.clone().to_string());
-
-                            app.user_search = "".to_string();
-                            app.users = None;
-                        }
+            .show_rows(ui, row_height, users.len(), |ui, range| {
+                for user in &users[range] {
+                    if ui.link(format!("{} [{}]", user.display, user.id)).clicked() {
+                        clicked_user = Some(user.id.clone());
                     }
                 }
             });
+
+        if let Some(uid) = clicked_user {
+            let members = app
+                .current_group
+                .as_mut()
+                .unwrap()
+                .member
+                .get_or_insert_with(Vec::new);
+
+            // Unless already a member.
+            if !members.contains(&uid) {
+                members.push(uid);
+                app.members_changed();
+
+                app.user_search = "".to_string();
+                app.store.invalidate(EntityKind::Users);
+            }
+        }
     }
 
     // Done button.
@@ -1,10 +1,10 @@
 use super::member::ui::render_show_edit_member;
 use crate::{
-    api::group::delete_group,
-    defines::{AF_CONFIRM_CODE, AF_DELETE_CODE, AF_EDIT_CODE, AF_GROUP_CODE},
+    defines::{AF_CONFIRM_CODE, AF_DELETE_CODE, AF_EDIT_CODE, AF_GROUP_CODE, AF_WARNING_CODE},
+    store::AclQualifier,
     ui::daenerys::{Action, DaenerysApp},
 };
-use egui::Ui;
+use egui::{Color32, Ui};
 use storm_daenerys_common::types::group::Group;
 
 pub fn render_show_group(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui) {
@@ -68,9 +68,9 @@ pub fn render_show_group(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui
     ui.add_space(20.0);
 
     // Edit members and delete group buttons.
-    if app.active_action.to_string().starts_with("group_edit") {
+    if app.active_action.to_string().starts_with("group_edit") && !app.is_read_only() {
         ui.horizontal_top(|ui| {
-            if !app.is_working && app.active_action == Action::GroupEdit {
+            if !app.tasks.is_busy() && app.active_action == Action::GroupEdit {
                 let button_label = format!("{} {}", AF_EDIT_CODE, "edit members");
                 let button = egui::Button::new(button_label);
 
@@ -86,27 +86,44 @@ pub fn render_show_group(app: &mut DaenerysApp, ctx: &egui::Context, ui: &mut Ui
 
                 if !is_group_invite
                     && !is_group_auto
-                    && !app.is_working
+                    && !app.tasks.is_busy()
                     && ui.add_sized([150., 30.], button).clicked()
                 {
                     app.active_action = Action::GroupEditDeleteConfirm;
                 }
             }
 
-            if !app.is_working && app.active_action == Action::GroupEditDeleteConfirm {
+            if !app.tasks.is_busy() && app.active_action == Action::GroupEditDeleteConfirm {
                 let button_label = format!("{} {}", AF_CONFIRM_CODE, "confirm deletion");
                 let button = egui::Button::new(button_label);
                 if ui.add_sized([150., 30.], button).clicked() {
-                    app.is_working = true;
-                    app.delete_group_promise = Some(delete_group(
-                        ctx,
-                        app.current_group.as_ref().unwrap().cn.clone(),
-                        app.api_url.clone(),
-                    ));
+                    app.delete_group(app.current_group.as_ref().unwrap().cn.clone());
 
                     app.active_action = Action::Home;
                 }
             }
         });
+
+        // Directories losing the access of the group.
+        if app.active_action == Action::GroupEditDeleteConfirm {
+            let group = AclQualifier::Group(app.current_group.as_ref().unwrap().cn.clone());
+            let directories: Vec<&str> = app
+                .store
+                .directories_with(&group)
+                .map(|directory| directory.name.as_str())
+                .collect();
+
+            if !directories.is_empty() {
+                ui.add_space(10.0);
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} used in the acl of {}",
+                        AF_WARNING_CODE,
+                        directories.join(", ")
+                    ))
+                    .color(Color32::YELLOW),
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,49 @@
+use eframe::egui::{self, Color32, RichText};
+
+use crate::{
+    api::version::Compatibility,
+    defines::{AF_ERROR_CODE, AF_WARNING_CODE},
+    ui::daenerys::DaenerysApp,
+};
+
+// What an incompatible backend can not be asked to do.
+const DISABLED_ACTIONS: &str = "creating, renaming and deleting directories, \
+    editing access rights and quotas, creating, editing and deleting groups";
+
+/// Full-width banner of a degraded or incompatible backend.
+pub fn render_protocol_banner(app: &DaenerysApp, ui: &mut egui::Ui) {
+    let Some(negotiation) = &app.negotiation else {
+        return;
+    };
+    let Some(message) = negotiation.message() else {
+        return;
+    };
+
+    let (icon, fill, text_color) = match negotiation.compatibility {
+        Compatibility::Incompatible => (AF_ERROR_CODE, Color32::DARK_RED, Color32::WHITE),
+        _ => (
+            AF_WARNING_CODE,
+            Color32::from_rgb(0x80, 0x60, 0x00),
+            Color32::WHITE,
+        ),
+    };
+
+    egui::Frame::none()
+        .fill(fill)
+        .inner_margin(8.0)
+        .rounding(4.0)
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+
+            ui.label(RichText::new(format!("{} {}", icon, message)).color(text_color));
+            if negotiation.is_incompatible() {
+                ui.label(
+                    RichText::new(format!("disabled: {}", DISABLED_ACTIONS))
+                        .color(text_color)
+                        .strong(),
+                );
+            }
+        });
+
+    ui.add_space(10.0);
+}
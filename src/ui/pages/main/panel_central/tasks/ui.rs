@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use eframe::egui::{self, Context, RichText};
+
+use crate::{defines::AF_CANCEL_CODE, tasks::TaskInfo, ui::daenerys::DaenerysApp};
+
+pub fn render_tasks(app: &mut DaenerysApp, ctx: &Context, ui: &mut egui::Ui) {
+    let running: Vec<TaskInfo> = app
+        .tasks
+        .running()
+        .filter(|info| !info.background)
+        .cloned()
+        .collect();
+
+    if running.is_empty() {
+        ui.add_sized([0., 40.], egui::Label::new(""));
+        return;
+    }
+
+    for info in running {
+        ui.horizontal(|ui| {
+            ui.add(egui::widgets::Spinner::new());
+            ui.label(&info.label);
+            ui.label(RichText::new(format!("{}s", info.elapsed().as_secs())).weak());
+
+            if let Some(progress) = info.progress {
+                ui.add(
+                    egui::ProgressBar::new(progress)
+                        .desired_width(100.)
+                        .show_percentage(),
+                );
+            }
+
+            // Only the loads can be cancelled, not the changes.
+            if info.cancellable {
+                let button = egui::Button::new(format!("{} {}", AF_CANCEL_CODE, "cancel"));
+                if ui.add_sized([100., 20.], button).clicked() {
+                    app.tasks.cancel(info.id);
+                }
+            }
+        });
+    }
+
+    // Refresh the elapsed times.
+    ctx.request_repaint_after(Duration::from_secs(1));
+}
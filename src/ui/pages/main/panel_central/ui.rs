@@ -3,6 +3,8 @@ use super::{
     du::ui::render_disk_usage,
     group::{create::render_create_group, ui::render_show_group},
     home::ui::render_home,
+    protocol::ui::render_protocol_banner,
+    tasks::ui::render_tasks,
 };
 use crate::ui::daenerys::{Action, DaenerysApp};
 use eframe::egui::{self, Context};
@@ -20,12 +22,11 @@ pub fn render_central_panel(app: &mut DaenerysApp, ctx: &Context, _frame: &mut e
         .show(ctx, |ui| {
             app.central_panel_available_size = ui.available_size();
 
-            // Show a spinner on REST requests or any other time consuming work.
-            if app.is_working {
-                ui.add_sized([0., 40.], egui::widgets::Spinner::new());
-            } else {
-                ui.add_sized([0., 40.], egui::Label::new(""));
-            }
+            // A backend not speaking the protocol of the application.
+            render_protocol_banner(app, ui);
+
+            // Operations in flight, with a spinner.
+            render_tasks(app, ctx, ui);
 
             // Disk usage.
             if app.active_action == Action::DiskUsage {
@@ -39,12 +40,12 @@ pub fn render_central_panel(app: &mut DaenerysApp, ctx: &Context, _frame: &mut e
 
             // Create directory form.
             if app.active_action == Action::DirectoryCreate {
-                render_create_directory(app, ctx, ui);
+                render_create_directory(app, ui);
             }
 
             // Create group form.
             if app.active_action == Action::GroupCreate {
-                render_create_group(app, ctx, ui);
+                render_create_group(app, ui);
             }
 
             // Directory details and edition.
@@ -0,0 +1,119 @@
+use crate::{
+    api::inspector::CallRecord,
+    defines::{AF_BUG_CODE, AF_DELETE_CODE},
+    ui::daenerys::DaenerysApp,
+};
+use egui::{Color32, Context, Frame, RichText, Ui};
+
+pub fn render_inspector_panel(app: &mut DaenerysApp, ctx: &Context) {
+    egui::TopBottomPanel::bottom("inspector_panel")
+        .resizable(true)
+        .default_height(250.)
+        .min_height(100.)
+        .frame(Frame {
+            inner_margin: 10.0.into(),
+            ..Default::default()
+        })
+        .show(ctx, |ui| {
+            let inspector = app.api_client.inspector().clone();
+            let calls = inspector.calls();
+
+            ui.horizontal(|ui| {
+                ui.heading(format!("{} API calls", AF_BUG_CODE));
+                ui.label(RichText::new(format!("{} recorded", calls.len())).weak());
+
+                if ui
+                    .button(format!("{} {}", AF_DELETE_CODE, "clear"))
+                    .clicked()
+                {
+                    inspector.clear();
+                }
+            });
+
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_salt("inspector_scroll")
+                .auto_shrink([false, false])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for call in &calls {
+                        render_call(ui, call);
+                    }
+                });
+
+            // Show the responses as they arrive.
+            if calls.iter().any(|call| call.is_pending()) {
+                ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            }
+        });
+}
+
+fn render_call(ui: &mut Ui, call: &CallRecord) {
+    let status = match call.status {
+        _ if call.is_pending() => "…".to_string(),
+        Some(status) => status.to_string(),
+        // No response, such as a network error.
+        None => "failed".to_string(),
+    };
+    let latency = call
+        .latency
+        .map(|latency| format!("{} ms", latency.as_millis()))
+        .unwrap_or_default();
+
+    let color = if call.is_pending() {
+        Color32::GRAY
+    } else if call.error.is_some() {
+        Color32::RED
+    } else {
+        Color32::WHITE
+    };
+
+    let title = RichText::new(format!(
+        "{} {} {} {}",
+        call.method, call.url, status, latency
+    ))
+    .monospace()
+    .color(color);
+
+    egui::CollapsingHeader::new(title)
+        .id_salt(("inspector_call", call.id))
+        .show(ui, |ui| {
+            if ui.button("copy as curl").clicked() {
+                ui.ctx().copy_text(call.curl());
+            }
+
+            if let Some(error) = &call.error {
+                ui.label(RichText::new(error).color(Color32::RED));
+            }
+
+            ui.label(RichText::new("request headers").strong());
+            for (key, value) in &call.request_headers {
+                ui.label(RichText::new(format!("{}: {}", key, value)).monospace());
+            }
+
+            render_body(ui, "request body", call.request_body.as_deref());
+            render_body(ui, "response body", call.response_body.as_deref());
+        });
+}
+
+fn render_body(ui: &mut Ui, title: &str, body: Option<&str>) {
+    let Some(body) = body else {
+        return;
+    };
+
+    ui.label(RichText::new(title).strong());
+
+    // Selectable, not editable.
+    let mut body = body;
+    egui::ScrollArea::vertical()
+        .id_salt(title)
+        .max_height(150.)
+        .show(ui, |ui| {
+            ui.add(
+                egui::TextEdit::multiline(&mut body)
+                    .code_editor()
+                    .desired_width(f32::INFINITY),
+            );
+        });
+}
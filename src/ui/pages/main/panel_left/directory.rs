@@ -1,18 +1,24 @@
+use std::collections::HashSet;
+
 use crate::{
-    api,
-    defines::{AF_ADD_CODE, AF_FOLDER_CODE, AF_QUOTA_CODE, AF_REFRESH_CODE, AF_WARNING_CODE},
-    ui::daenerys::{Action, DaenerysApp},
+    defines::{
+        AF_ADD_CODE, AF_COLLAPSE_CODE, AF_EXPAND_CODE, AF_FOLDER_CODE, AF_QUOTA_CODE,
+        AF_REFRESH_CODE, AF_WARNING_CODE,
+    },
+    store::Store,
+    ui::{
+        daenerys::{Action, DaenerysApp},
+        rows::button_row_height,
+    },
 };
 use egui::{vec2, Color32, Layout, Ui};
 use human_bytes::human_bytes;
-use storm_daenerys_common::types::quota::QuotaUnit;
-
-pub fn render_directory_list(
-    app: &mut DaenerysApp,
-    ctx: &egui::Context,
-    ui: &mut Ui,
-    scroll_height: f32,
-) {
+use storm_daenerys_common::types::directory::Directory;
+
+// Width of a level of the tree.
+const INDENT: f32 = 16.0;
+
+pub fn render_directory_list(app: &mut DaenerysApp, ui: &mut Ui, scroll_height: f32) {
     ui.style_mut().spacing.item_spacing = vec2(16.0, 16.0);
 
     ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
@@ -28,21 +34,20 @@ pub fn render_directory_list(
             // Reload button.
             let button = egui::Button::new(format!("{} reload", AF_REFRESH_CODE));
             if ui.add_sized([30., 30.], button).clicked() {
-                app.get_directories_promise = Some(api::directory::get_root_directories(
-                    ctx,
-                    app.api_url.clone(),
-                ));
+                app.reload_directories();
             }
 
             // Create directory button.
             let button_label = format!("{} {}", AF_ADD_CODE, "create directory");
             let button = egui::Button::new(button_label);
 
-            if ui.add_sized([150., 30.], button).clicked() {
+            if !app.is_read_only() && ui.add_sized([150., 30.], button).clicked() {
                 app.active_action = Action::DirectoryCreate;
 
                 app.current_directory = None;
                 app.current_group = None;
+                app.acls_changed();
+                app.members_changed();
                 app.du = None;
 
                 app.create_directory_name.clear();
@@ -52,67 +57,126 @@ pub fn render_directory_list(
 
     // ui.separator();
 
-    // Directory list.
+    // Directory tree, only the visible rows are drawn.
+    ui.style_mut().spacing.item_spacing = vec2(5.0, 5.0);
+    let row_height = button_row_height(ui);
+    let can_browse = app.can_browse_subdirectories();
+    let rows = tree_rows(&app.store, &app.expanded_directories);
+    let mut toggled = None;
+    let mut opened = None;
+
     egui::ScrollArea::vertical()
         .id_salt("directory_scroll")
         .max_height(scroll_height)
-        .show(ui, |ui| {
-            ui.style_mut().spacing.item_spacing = vec2(5.0, 5.0);
-
-            if app.directories.is_some() {
-                ui.with_layout(Layout::top_down(egui::Align::LEFT), |ui| {
-                    for directory in app.directories.as_ref().unwrap().iter() {
-                        // Icon.
-                        let directory_icon = if directory.valid {
-                            format!("{}", AF_FOLDER_CODE)
-                        } else {
-                            format!("{}", AF_WARNING_CODE)
-                        };
-
-                        // Directory quota.
-                        let quota = match directory.quota {
-                            Some(quota) => {
-                                if quota.ne(&0) {
-                                    format!("[{}:{}]", AF_QUOTA_CODE, human_bytes(quota as f64))
+        .show_rows(ui, row_height, rows.len(), |ui, range| {
+            ui.with_layout(Layout::top_down(egui::Align::LEFT), |ui| {
+                for (depth, directory) in &rows[range] {
+                    // Icon.
+                    let directory_icon = if directory.valid {
+                        format!("{}", AF_FOLDER_CODE)
+                    } else {
+                        format!("{}", AF_WARNING_CODE)
+                    };
+
+                    // Directory quota.
+                    let quota = match directory.quota {
+                        Some(quota) => {
+                            if quota.ne(&0) {
+                                format!("[{}:{}]", AF_QUOTA_CODE, human_bytes(quota as f64))
+                            } else {
+                                "".to_string()
+                            }
+                        }
+                        None => "".to_string(),
+                    };
+
+                    // Last component of the path.
+                    let name = directory
+                        .name
+                        .rsplit_once('/')
+                        .map_or(directory.name.as_str(), |(_, name)| name);
+
+                    // Disable button id directory is invalid.
+                    let enabled = directory.valid;
+                    ui.horizontal(|ui| {
+                        ui.add_space(*depth as f32 * INDENT);
+
+                        // Expand button, unless listed without subdirectories.
+                        if can_browse {
+                            let expanded = app.expanded_directories.contains(&directory.name);
+                            let subdirectories = app.store.subdirectories(&directory.name);
+
+                            if subdirectories
+                                .is_some_and(|subdirectories| subdirectories.is_empty())
+                            {
+                                ui.add_space(INDENT);
+                            } else {
+                                let icon = if expanded {
+                                    AF_COLLAPSE_CODE
                                 } else {
-                                    "".to_string()
+                                    AF_EXPAND_CODE
+                                };
+
+                                if ui
+                                    .add_sized(
+                                        [INDENT, row_height],
+                                        egui::Button::new(icon.to_string()).frame(false),
+                                    )
+                                    .clicked()
+                                {
+                                    toggled = Some(directory.name.clone());
                                 }
                             }
-                            None => "".to_string(),
-                        };
-
-                        // Disable button id directory is invalid.
-                        let enabled = directory.valid;
-                        ui.horizontal(|ui| {
-                            ui.add_enabled_ui(enabled, |ui| {
-                                let button_label =
-                                    format!("{} {} {}", directory_icon, directory.name, quota);
-                                let button = egui::Button::new(button_label);
-
-                                // if ui.add_sized([100., 20.], button).clicked() {
-                                if ui.add(button).clicked() {
-                                    // Save the clicked directory.
-                                    app.active_action = Action::DirectoryEdit;
-                                    app.current_directory = Some(Box::new(directory.clone()));
-
-                                    // And its quota in bytes to populate the quota edition input text.
-                                    if let Some(quota) = directory.quota {
-                                        let quota_in_mb = quota / 1024 / 1024;
-                                        app.edited_directory_quota = quota_in_mb.to_string()
-                                    } else {
-                                        app.edited_directory_quota = 0.to_string();
-                                    }
-
-                                    app.edited_directory_quota_unit = QuotaUnit::Megabyte;
-                                    app.current_group = None;
-                                    app.current_error = None;
-                                    app.current_info = None;
-                                    app.du = None;
-                                };
-                            });
+                        }
+
+                        ui.add_enabled_ui(enabled, |ui| {
+                            let button_label = format!("{} {} {}", directory_icon, name, quota);
+                            let button = egui::Button::new(button_label);
+
+                            // if ui.add_sized([100., 20.], button).clicked() {
+                            if ui.add(button).clicked() {
+                                // Save the clicked directory.
+                                opened = Some((*directory).clone());
+                            };
                         });
-                    }
-                });
-            }
+                    });
+                }
+            });
         });
+
+    if let Some(path) = toggled {
+        app.toggle_directory(&path);
+    }
+    if let Some(directory) = opened {
+        app.open_directory(directory);
+    }
+}
+
+/// The directories of the tree with their depth, the subdirectories of the
+/// expanded ones after them.
+fn tree_rows<'a>(store: &'a Store, expanded: &HashSet<String>) -> Vec<(usize, &'a Directory)> {
+    let mut rows = Vec::new();
+    let mut stack: Vec<(usize, &Directory)> = store
+        .directories()
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .map(|directory| (0, directory))
+        .collect();
+
+    while let Some((depth, directory)) = stack.pop() {
+        rows.push((depth, directory));
+
+        if expanded.contains(&directory.name) {
+            let subdirectories = store.subdirectories(&directory.name).unwrap_or_default();
+            stack.extend(
+                subdirectories
+                    .iter()
+                    .rev()
+                    .map(|subdirectory| (depth + 1, subdirectory)),
+            );
+        }
+    }
+
+    rows
 }
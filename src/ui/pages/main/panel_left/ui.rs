@@ -22,6 +22,6 @@ pub fn render_left_panel(app: &mut DaenerysApp, ctx: &Context) {
             let scroll_height = available_height - 100.;
 
             // Directory list.
-            render_directory_list(app, ctx, ui, scroll_height)
+            render_directory_list(app, ui, scroll_height)
         });
 }
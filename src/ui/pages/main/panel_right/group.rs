@@ -1,16 +1,13 @@
 use crate::{
-    api,
     defines::{AF_ADD_CODE, AF_GROUP_CODE, AF_HALF_LOCK_CODE, AF_LOCK_CODE, AF_REFRESH_CODE},
-    ui::daenerys::{Action, DaenerysApp},
+    ui::{
+        daenerys::{Action, DaenerysApp},
+        rows::button_row_height,
+    },
 };
 use egui::{vec2, Color32, Ui};
 
-pub fn render_group_list(
-    app: &mut DaenerysApp,
-    ctx: &egui::Context,
-    ui: &mut Ui,
-    scroll_height: f32,
-) {
+pub fn render_group_list(app: &mut DaenerysApp, ui: &mut Ui, scroll_height: f32) {
     ui.style_mut().spacing.item_spacing = vec2(16.0, 16.0);
 
     ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
@@ -26,18 +23,20 @@ pub fn render_group_list(
             // Reload button.
             let button = egui::Button::new(format!("{} reload", AF_REFRESH_CODE));
             if ui.add_sized([30., 30.], button).clicked() {
-                app.get_groups_promise = Some(api::group::get_groups(ctx, app.api_url.clone()));
+                app.reload_groups();
             }
 
             // Create group button.
             let button_label = format!("{} {}", AF_ADD_CODE, "create group");
             let button = egui::Button::new(button_label);
 
-            if ui.add_sized([150., 30.], button).clicked() {
+            if !app.is_read_only() && ui.add_sized([150., 30.], button).clicked() {
                 app.active_action = Action::GroupCreate;
 
                 app.current_directory = None;
                 app.current_group = None;
+                app.acls_changed();
+                app.members_changed();
                 app.du = None;
 
                 app.create_group_name.clear();
@@ -48,81 +47,87 @@ pub fn render_group_list(
 
     // ui.separator();
 
-    // Group list.
+    // Group list, only the visible rows are drawn.
+    ui.style_mut().spacing.item_spacing = vec2(5.0, 5.0);
+    let row_height = button_row_height(ui);
+    let count = app.store.groups().map_or(0, |groups| groups.len());
+    let mut clicked_group = None;
+
     egui::ScrollArea::vertical()
         .id_salt("group_scroll")
         .max_height(scroll_height)
-        .show(ui, |ui| {
-            ui.style_mut().spacing.item_spacing = vec2(5.0, 5.0);
-
-            if app.groups.is_some() {
-                egui::Grid::new("group_list").num_columns(2).show(ui, |ui| {
-                    for group in app.groups.as_ref().unwrap().iter() {
-                        let mut is_group_auto: bool = false;
-                        let mut is_group_invite: bool = false;
-
-                        match &app.root_groups {
-                            Some(root_groups) => {
-                                for root_group in root_groups {
-                                    if group.cn.eq(&format!(
-                                        "{}-{}",
-                                        app.group_prefix.as_ref().unwrap(),
-                                        root_group,
-                                    )) {
-                                        is_group_auto = true;
-                                        break;
-                                    }
-
-                                    if group.cn.eq(&format!(
-                                        "{}-{}-invite",
-                                        app.group_prefix.as_ref().unwrap(),
-                                        root_group,
-                                    )) {
-                                        is_group_invite = true;
-                                        break;
-                                    }
-                                }
+        .show_rows(ui, row_height, count, |ui, range| {
+            let Some(groups) = app.store.groups() else {
+                return;
+            };
+
+            for group in &groups[range] {
+                let mut is_group_auto: bool = false;
+                let mut is_group_invite: bool = false;
+
+                match &app.root_groups {
+                    Some(root_groups) => {
+                        for root_group in root_groups {
+                            if group.cn.eq(&format!(
+                                "{}-{}",
+                                app.group_prefix.as_ref().unwrap(),
+                                root_group,
+                            )) {
+                                is_group_auto = true;
+                                break;
                             }
-                            None => {
-                                is_group_auto = group.cn.eq(app.group_prefix.as_ref().unwrap());
-                                is_group_invite = group
-                                    .cn
-                                    .eq(&format!("{}-invite", app.group_prefix.as_ref().unwrap()));
+
+                            if group.cn.eq(&format!(
+                                "{}-{}-invite",
+                                app.group_prefix.as_ref().unwrap(),
+                                root_group,
+                            )) {
+                                is_group_invite = true;
+                                break;
                             }
                         }
+                    }
+                    None => {
+                        is_group_auto = group.cn.eq(app.group_prefix.as_ref().unwrap());
+                        is_group_invite = group
+                            .cn
+                            .eq(&format!("{}-invite", app.group_prefix.as_ref().unwrap()));
+                    }
+                }
 
-                        // ui.add_sized([30., 30.], egui::Label::new(format!("{}", AF_GROUP_CODE)));
-
-                        ui.horizontal(|ui| {
-                            let mut button_label = format!("{} {}", AF_GROUP_CODE, group.cn);
-
-                            if is_group_auto {
-                                button_label =
-                                    format!("{} {} {}", AF_GROUP_CODE, AF_LOCK_CODE, group.cn)
-                            }
-                            if is_group_invite {
-                                button_label =
-                                    format!("{} {} {}", AF_GROUP_CODE, AF_HALF_LOCK_CODE, group.cn)
-                            }
+                // ui.add_sized([30., 30.], egui::Label::new(format!("{}", AF_GROUP_CODE)));
 
-                            let button = egui::Button::new(button_label);
+                ui.horizontal(|ui| {
+                    let mut button_label = format!("{} {}", AF_GROUP_CODE, group.cn);
 
-                            // Save the clicked group name.
-                            // if ui.add_sized([200., 30.], button).clicked() {
-                            if ui.add(button).clicked() {
-                                app.active_action = Action::GroupEdit;
-                                app.current_group = Some(Box::new(group.clone()));
+                    if is_group_auto {
+                        button_label = format!("{} {} {}", AF_GROUP_CODE, AF_LOCK_CODE, group.cn)
+                    }
+                    if is_group_invite {
+                        button_label =
+                            format!("{} {} {}", AF_GROUP_CODE, AF_HALF_LOCK_CODE, group.cn)
+                    }
 
-                                app.current_directory = None;
-                                app.current_error = None;
-                                app.current_info = None;
-                                app.du = None;
-                            }
-                        });
+                    let button = egui::Button::new(button_label);
 
-                        ui.end_row()
+                    // Save the clicked group name.
+                    // if ui.add_sized([200., 30.], button).clicked() {
+                    if ui.add(button).clicked() {
+                        clicked_group = Some(group.clone());
                     }
                 });
             }
         });
+
+    if let Some(group) = clicked_group {
+        app.active_action = Action::GroupEdit;
+        app.current_group = Some(Box::new(group));
+        app.members_changed();
+
+        app.current_directory = None;
+        app.acls_changed();
+        app.current_error = None;
+        app.current_info = None;
+        app.du = None;
+    }
 }
@@ -22,6 +22,6 @@ pub fn render_right_panel(app: &mut DaenerysApp, ctx: &Context) {
             let scroll_height = available_height - 100.;
 
             // Group list.
-            render_group_list(app, ctx, ui, scroll_height)
+            render_group_list(app, ui, scroll_height)
         });
 }
@@ -2,9 +2,11 @@ use eframe::egui::{self, Context, RichText};
 use egui::{Color32, Frame};
 
 use crate::{
-    api,
+    api::version::Compatibility,
     defines::{
-        AF_CONNECTED_USER_CODE, AF_ERROR_CODE, AF_GAUGE_CODE, AF_INFO_CODE, AF_TOGGLE_OFF_CODE,
+        AF_BUG_CODE, AF_CONNECTED_USER_CODE, AF_ERROR_CODE, AF_EYE_CODE, AF_GAUGE_CODE,
+        AF_HALF_LOCK_CODE, AF_INFO_CODE, AF_LOCK_CODE, AF_REFRESH_CODE, AF_TOGGLE_OFF_CODE,
+        AF_WARNING_CODE,
     },
     ui::daenerys::DaenerysApp,
 };
@@ -43,6 +45,35 @@ pub fn render_top_panel(app: &mut DaenerysApp, ctx: &Context, _frame: &mut efram
                             )));
                         }
 
+                        // TLS trust settings, flagged when the connection is not verified.
+                        render_tls(app, ui);
+
+                        // Backend version, flagged when it does not match.
+                        render_version(app, ui);
+
+                        // Requests failing on an unavailable backend.
+                        render_retrying(app, ui);
+
+                        // Offline mode, showing the last snapshot.
+                        if let Some(snapshot) = &app.offline {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "{} offline, data of {}",
+                                    AF_WARNING_CODE,
+                                    snapshot.saved_at_label()
+                                ))
+                                .color(Color32::RED),
+                            )
+                            .on_hover_text(
+                                "the backend is unreachable, changes are disabled until it answers",
+                            );
+                        }
+
+                        // Read-only mode.
+                        if app.read_only {
+                            ui.label(egui::RichText::new(format!("{} read-only", AF_EYE_CODE)));
+                        }
+
                         // Root quota.
                         render_quota(app, ui);
                     })
@@ -72,10 +103,28 @@ pub fn render_top_panel(app: &mut DaenerysApp, ctx: &Context, _frame: &mut efram
                 // Disk usage button.
                 let button = egui::Button::new(format!("{} show disk usage", AF_GAUGE_CODE));
 
-                if ui.add_sized([150., 30.], button).clicked() {
-                    app.is_working = true;
+                if ui
+                    .add_enabled_ui(app.offline.is_none(), |ui| {
+                        ui.add_sized([150., 30.], button)
+                    })
+                    .inner
+                    .clicked()
+                {
+                    app.load_du();
+                };
+
+                // Flush user display cache button.
+                let button = egui::Button::new(format!("{} flush user cache", AF_REFRESH_CODE));
 
-                    app.get_du_promise = Some(api::root::get_du(ctx, app.api_url.clone()));
+                if ui
+                    .add_sized([150., 30.], button)
+                    .on_hover_text(format!(
+                        "{} user display names cached",
+                        app.store.user_displays().len()
+                    ))
+                    .clicked()
+                {
+                    app.flush_user_displays();
                 };
 
                 // Toggle show/hide side panels.
@@ -86,12 +135,26 @@ pub fn render_top_panel(app: &mut DaenerysApp, ctx: &Context, _frame: &mut efram
                     app.toggle_side_panels = !app.toggle_side_panels;
                 };
 
+                // Toggle the API calls panel.
+                let button = egui::Button::new(format!("{} show/hide API calls", AF_BUG_CODE));
+
+                if ui.add_sized([150., 30.], button).clicked() {
+                    app.show_inspector = !app.show_inspector;
+                    app.api_client.inspector().set_enabled(app.show_inspector);
+                };
+
                 // Current error label.
                 if let Some(current_error) = &app.current_error {
                     ui.label(
                         RichText::new(format!("{} {}", AF_ERROR_CODE, current_error))
                             .color(Color32::RED),
-                    );
+                    )
+                    .on_hover_text(format!("{:?}", current_error));
+
+                    // Advice depending on the kind of API error.
+                    if let Some(hint) = current_error.api_error().and_then(|e| e.hint()) {
+                        ui.label(RichText::new(hint).italics().color(Color32::RED));
+                    }
                 }
 
                 // Current info label.
@@ -104,3 +167,77 @@ pub fn render_top_panel(app: &mut DaenerysApp, ctx: &Context, _frame: &mut efram
             });
         });
 }
+
+fn render_retrying(app: &DaenerysApp, ui: &mut egui::Ui) {
+    let retrying = app.api_client.retrying();
+    if retrying == 0 {
+        return;
+    }
+
+    ui.label(
+        RichText::new(format!(
+            "{} retrying {} request{}",
+            AF_REFRESH_CODE,
+            retrying,
+            if retrying > 1 { "s" } else { "" }
+        ))
+        .color(Color32::YELLOW),
+    )
+    .on_hover_text("the backend did not answer, the requests are sent again shortly");
+}
+
+fn render_version(app: &DaenerysApp, ui: &mut egui::Ui) {
+    let Some(negotiation) = &app.negotiation else {
+        return;
+    };
+
+    match negotiation.compatibility {
+        Compatibility::Full => {
+            ui.label(RichText::new(format!("backend {}", negotiation.backend_version())).weak());
+        }
+        Compatibility::Degraded(_) => {
+            ui.label(
+                RichText::new(format!("{} degraded backend", AF_WARNING_CODE))
+                    .color(Color32::YELLOW),
+            )
+            .on_hover_text(negotiation.message().unwrap_or_default());
+        }
+        Compatibility::Incompatible => {
+            ui.label(
+                RichText::new(format!("{} incompatible backend", AF_ERROR_CODE))
+                    .color(Color32::RED),
+            )
+            .on_hover_text(negotiation.message().unwrap_or_default());
+        }
+    }
+}
+
+fn render_tls(app: &DaenerysApp, ui: &mut egui::Ui) {
+    let api_url = app.api_client.api_url();
+
+    if api_url.starts_with("http://") {
+        ui.label(
+            RichText::new(format!("{} unencrypted connection", AF_HALF_LOCK_CODE))
+                .color(Color32::RED),
+        )
+        .on_hover_text(format!("{} is not using HTTPS", api_url));
+    } else if app.tls.insecure {
+        ui.label(
+            RichText::new(format!("{} {}", AF_HALF_LOCK_CODE, app.tls.summary()))
+                .color(Color32::RED),
+        )
+        .on_hover_text(
+            "insecure mode: the server certificate is not verified, for development only",
+        );
+    } else {
+        let label = ui.label(RichText::new(format!(
+            "{} {}",
+            AF_LOCK_CODE,
+            app.tls.summary()
+        )));
+
+        if let Some(ca_bundle) = &app.tls.ca_bundle {
+            label.on_hover_text(format!("CA bundle: {}", ca_bundle.display()));
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui::{self, Context, RichText};
+use egui::{Color32, Frame};
+
+use crate::{
+    defines::{AF_CONFIRM_CODE, AF_ERROR_CODE, AF_INFO_CODE, AF_REFRESH_CODE},
+    ui::daenerys::{DaenerysApp, TASK_CONFIG},
+};
+
+pub fn update(app: &mut DaenerysApp, ctx: &Context) {
+    egui::CentralPanel::default()
+        .frame(Frame {
+            inner_margin: 15.0.into(),
+            ..Default::default()
+        })
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                // STORM logo.
+                ui.add_sized(
+                    [200., 70.],
+                    egui::Image::new(egui::include_image!("../../media/storm-logo.svg")),
+                );
+
+                ui.add_space(40.0);
+
+                ui.heading(format!("connecting to {}", app.api_client.api_url()));
+
+                ui.add_space(20.0);
+
+                // A mismatching backend explains the decoding errors.
+                if let Some(message) = app
+                    .negotiation
+                    .as_ref()
+                    .filter(|negotiation| negotiation.is_incompatible())
+                    .and_then(|negotiation| negotiation.message())
+                {
+                    ui.label(
+                        RichText::new(format!("{} {}", AF_ERROR_CODE, message)).color(Color32::RED),
+                    );
+
+                    ui.add_space(20.0);
+                }
+
+                if app.tasks.is_running(TASK_CONFIG) {
+                    ui.add(egui::widgets::Spinner::new());
+                    ui.label("loading the configuration");
+
+                    // The backend did not answer yet.
+                    if app.api_client.retrying() > 0 {
+                        ui.label(
+                            RichText::new(format!("{} retrying", AF_REFRESH_CODE))
+                                .color(Color32::YELLOW),
+                        );
+                    }
+                } else if let Some(next_retry) = app.next_startup_retry {
+                    let remaining = next_retry.saturating_duration_since(Instant::now());
+
+                    ui.label(format!(
+                        "attempt {} failed, retrying in {}s",
+                        app.startup_attempts,
+                        remaining.as_secs() + 1
+                    ));
+
+                    // Refresh the countdown.
+                    ctx.request_repaint_after(Duration::from_secs(1));
+
+                    ui.add_space(10.0);
+
+                    let button = egui::Button::new(format!("{} {}", AF_REFRESH_CODE, "retry now"));
+                    if ui.add_sized([150., 30.], button).clicked() {
+                        app.load_config();
+                    }
+                }
+
+                ui.add_space(20.0);
+
+                // Backend URL.
+                ui.label("backend URL");
+                ui.add(
+                    egui::TextEdit::singleline(&mut app.startup_api_url)
+                        .hint_text("https://storm.example.org/api"),
+                );
+
+                ui.add_space(10.0);
+
+                let api_url = app.startup_api_url.trim().trim_end_matches('/').to_string();
+                let changed = !api_url.is_empty() && api_url != app.api_client.api_url();
+
+                ui.add_enabled_ui(changed, |ui| {
+                    let button = egui::Button::new(format!("{} {}", AF_CONFIRM_CODE, "connect"));
+                    if ui.add_sized([150., 30.], button).clicked() {
+                        app.connect(&api_url);
+                    }
+                });
+
+                ui.add_space(20.0);
+
+                // Reason of the last failure.
+                if let Some(current_error) = &app.current_error {
+                    ui.label(
+                        RichText::new(format!("{} {}", AF_ERROR_CODE, current_error))
+                            .color(Color32::RED),
+                    )
+                    .on_hover_text(format!("{:?}", current_error));
+
+                    // Advice depending on the kind of API error.
+                    if let Some(hint) = current_error.api_error().and_then(|e| e.hint()) {
+                        ui.label(RichText::new(hint).italics().color(Color32::RED));
+                    }
+                }
+
+                // Current info label.
+                if let Some(current_info) = &app.current_info {
+                    ui.label(
+                        RichText::new(format!("{} {}", AF_INFO_CODE, current_info))
+                            .color(Color32::GREEN),
+                    );
+                }
+            });
+        });
+}
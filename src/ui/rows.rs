@@ -0,0 +1,51 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use egui::{TextStyle, Ui};
+
+/// Rows built from some data, such as sorted, kept until the data changes.
+#[derive(Debug)]
+pub struct RowCache<T> {
+    // Key of the data the rows were built from.
+    key: Option<u64>,
+    rows: Vec<T>,
+}
+
+impl<T> Default for RowCache<T> {
+    fn default() -> Self {
+        Self {
+            key: None,
+            rows: Vec::new(),
+        }
+    }
+}
+
+impl<T> RowCache<T> {
+    /// The rows of the data of `key`, built again by `build` if the key
+    /// changed since the last call.
+    pub fn get(&mut self, key: u64, build: impl FnOnce() -> Vec<T>) -> &[T] {
+        if self.key != Some(key) {
+            self.rows = build();
+            self.key = Some(key);
+        }
+
+        &self.rows
+    }
+
+    /// The rows built by the last `get`.
+    pub fn rows(&self) -> &[T] {
+        &self.rows
+    }
+}
+
+/// Key of `data`, changing with it.
+pub fn data_key(data: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Height of a row of buttons in `ui`, for `ScrollArea::show_rows`.
+pub fn button_row_height(ui: &Ui) -> f32 {
+    let text_height = ui.text_style_height(&TextStyle::Button);
+    (text_height + 2.0 * ui.spacing().button_padding.y).max(ui.spacing().interact_size.y)
+}
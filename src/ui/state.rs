@@ -1,8 +1,13 @@
 // Applications pages.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq)]
 pub enum Page {
     #[default]
     Main,
+    // Shown until the backend configuration is loaded, with the reason of
+    // the failures.
+    Startup,
+    // Shown when the backend requires the user to authenticate.
+    Login,
 }
 
 /// Application state.
@@ -1,2 +1,4 @@
 pub mod builder;
+pub mod displays;
+pub mod handle;
 pub mod message;
@@ -1,70 +1,352 @@
-use std::sync::mpsc::{Receiver, Sender};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
 
 use eframe::egui::Context;
-use log::{error, info};
+use log::{debug, info, warn};
+use poll_promise::Promise;
 
 use crate::{
-    error::apperror::AppError,
-    worker::message::{ToAppMessage, ToWorkerMessage},
+    api::{
+        client::ApiClient, conditional::ConditionalList, userdisplay::resolver::UserDisplayResolver,
+    },
+    error::{apierror::ApiError, apperror::AppError},
+    tasks::Progress,
+    worker::message::{Job, JobReply, ToAppMessage, ToWorkerMessage, UserDisplayUpdate},
 };
+use storm_daenerys_common::types::{directory::Directory, group::Group};
 
 use super::message::{ToApp, ToWorker};
 
-#[allow(dead_code)]
+// Delay between two checks of the requests in flight.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+// A job whose requests are in flight.
+struct RunningJob {
+    promises: Vec<Promise<Result<(), ApiError>>>,
+    // First failure, the other requests are still sent.
+    error: Option<ApiError>,
+    progress: Progress,
+    reply: JobReply,
+}
+
+/// Runs the background I/O of the app in its own thread: periodic reload of
+/// the lists, user display name resolution, jobs and cache persistence.
+///
+/// Stops on `ToWorkerMessage::Shutdown` or once the app dropped its end of
+/// a channel.
 pub struct Worker {
     sender: Sender<ToApp>,
     receiver: Receiver<ToWorker>,
     egui_ctx: Context,
+    client: ApiClient,
+    user_displays: UserDisplayResolver,
+    // Uids requested by the app and what it was sent.
+    watched: HashSet<String>,
+    sent_displays: HashMap<String, Option<String>>,
+    sent_errors: HashSet<String>,
+    // Uids requested again, their name is sent again once resolved.
+    revalidated: HashSet<String>,
+    sent_len: usize,
+    sent_idle: bool,
+    // Background reload of the lists, `None` when disabled.
+    refresh_interval: Option<Duration>,
+    next_refresh: Option<Instant>,
+    directories: Option<Promise<Result<ConditionalList<Directory>, ApiError>>>,
+    groups: Option<Promise<Result<ConditionalList<Group>, ApiError>>>,
+    jobs: Vec<RunningJob>,
 }
 
 impl Worker {
-    #[allow(dead_code)]
-    pub fn new(sender: Sender<ToApp>, receiver: Receiver<ToWorker>, egui_ctx: Context) -> Self {
+    pub fn new(
+        sender: Sender<ToApp>,
+        receiver: Receiver<ToWorker>,
+        egui_ctx: Context,
+        client: ApiClient,
+    ) -> Self {
         Self {
             sender,
             receiver,
             egui_ctx,
+            user_displays: UserDisplayResolver::new(client.clone()),
+            client,
+            watched: HashSet::new(),
+            sent_displays: HashMap::new(),
+            sent_errors: HashSet::new(),
+            revalidated: HashSet::new(),
+            sent_len: 0,
+            sent_idle: true,
+            refresh_interval: None,
+            next_refresh: None,
+            directories: None,
+            groups: None,
+            jobs: Vec::new(),
         }
     }
 
-    #[allow(dead_code)]
+    /// Resolve the display names with `user_displays`, such as one started
+    /// from a persisted cache.
+    pub fn with_user_displays(mut self, user_displays: UserDisplayResolver) -> Self {
+        self.sent_len = user_displays.len();
+        self.user_displays = user_displays;
+        self
+    }
+
     pub fn init(&mut self) {
         info!("Worker starting up.");
 
-        // Wait for <ToWorker> messages giving work to do.
+        // Wait for <ToWorker> messages giving work to do, checking the
+        // requests in flight meanwhile.
         // Can send back <ToApp> messages to the GUI.
-        // FIXME:
-        // In case of a send error we can not "send" an AppError
-        // to the app. We just log it. To be improved.
+        // Once the app is gone nobody can be told of a channel failure:
+        // the worker stops and the app reports the closed channel.
         loop {
-            let maybe_message = self.receiver.recv();
-
-            match maybe_message {
-                Ok(message) => match message.message {
-                    ToWorkerMessage::Ping => {
-                        if self
-                            .sender
-                            .send(ToApp {
-                                message: ToAppMessage::Pong,
-                            })
-                            .is_err()
-                        {
-                            error!("failed to send ToAppMessage::Pong");
+            let received = match self.wait_timeout() {
+                Some(timeout) => self.receiver.recv_timeout(timeout),
+                None => self
+                    .receiver
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            let result = match received {
+                Ok(ToWorker {
+                    message: ToWorkerMessage::Shutdown,
+                }) => break,
+                Ok(message) => self.handle(message.message),
+                Err(RecvTimeoutError::Timeout) => Ok(()),
+                Err(RecvTimeoutError::Disconnected) => {
+                    debug!("app channel closed");
+                    break;
+                }
+            };
+
+            if let Err(e) = result.and_then(|()| self.poll()) {
+                warn!("worker stopping: {}", e);
+                break;
+            }
+        }
+
+        info!("Worker stopped.");
+    }
+
+    /// Time to wait for a message, `None` to wait until one is received.
+    fn wait_timeout(&self) -> Option<Duration> {
+        if !self.user_displays.is_idle()
+            || !self.jobs.is_empty()
+            || self.directories.is_some()
+            || self.groups.is_some()
+        {
+            return Some(POLL_INTERVAL);
+        }
+
+        self.next_refresh
+            .map(|next_refresh| next_refresh.saturating_duration_since(Instant::now()))
+    }
+
+    fn send(&self, message: ToAppMessage) -> Result<(), AppError> {
+        self.sender
+            .send(ToApp { message })
+            .map_err(|_| AppError::ChannelSendError)?;
+
+        self.egui_ctx.request_repaint();
+        Ok(())
+    }
+
+    fn handle(&mut self, message: ToWorkerMessage) -> Result<(), AppError> {
+        match message {
+            ToWorkerMessage::Ping => self.send(ToAppMessage::Pong)?,
+            ToWorkerMessage::SetClient(client) => {
+                self.user_displays.switch_backend(client.clone());
+                self.client = client;
+                self.forget_sent();
+                // Not to send the lists of the previous backend.
+                self.directories = None;
+                self.groups = None;
+            }
+            ToWorkerMessage::SetRefreshInterval(interval) => {
+                self.refresh_interval = interval;
+                self.next_refresh = interval.map(|interval| Instant::now() + interval);
+            }
+            ToWorkerMessage::ResolveUserDisplays(uids) => {
+                for uid in uids {
+                    self.user_displays.request(&uid);
+                    if self.sent_displays.contains_key(&uid) {
+                        self.revalidated.insert(uid.clone());
+                    }
+                    self.watched.insert(uid);
+                }
+            }
+            ToWorkerMessage::SetBatchSupported(supported) => {
+                self.user_displays.set_batch_supported(supported)
+            }
+            ToWorkerMessage::ClearUserDisplayErrors => {
+                self.user_displays.clear_errors();
+                self.sent_errors.clear();
+            }
+            ToWorkerMessage::FlushUserDisplays => {
+                self.forget_sent();
+
+                if let Err(e) = self.user_displays.flush() {
+                    self.send(ToAppMessage::Error(AppError::InternalError(format!(
+                        "can not flush the user display cache: {}",
+                        e
+                    ))))?;
+                }
+            }
+            ToWorkerMessage::SaveSnapshot(snapshot, path) => match snapshot.save(&path) {
+                Ok(()) => debug!("snapshot saved to {}", path.display()),
+                Err(e) => warn!("can not save snapshot {}: {}", path.display(), e),
+            },
+            ToWorkerMessage::Job(job) => self.start_job(job),
+            // Handled by the loop.
+            ToWorkerMessage::Shutdown => (),
+        }
+
+        Ok(())
+    }
+
+    /// Forget the uids requested by the app, such as when it dropped its
+    /// names.
+    fn forget_sent(&mut self) {
+        self.watched.clear();
+        self.sent_displays.clear();
+        self.sent_errors.clear();
+        self.revalidated.clear();
+    }
+
+    fn start_job(&mut self, job: Job) {
+        match job {
+            Job::SaveGroup {
+                group_backup,
+                group,
+                progress,
+                reply,
+            } => {
+                let promises = self.client.save_group(group_backup, group);
+                progress.set_total(promises.len());
+
+                self.jobs.push(RunningJob {
+                    promises,
+                    error: None,
+                    progress,
+                    reply,
+                });
+            }
+        }
+    }
+
+    /// Collect the finished requests, send the new ones and tell the app.
+    fn poll(&mut self) -> Result<(), AppError> {
+        self.poll_refresh()?;
+        self.poll_jobs();
+
+        self.user_displays.update();
+        if let Some(update) = self.user_display_update() {
+            self.send(ToAppMessage::UserDisplays(update))?;
+        }
+
+        Ok(())
+    }
+
+    fn poll_refresh(&mut self) -> Result<(), AppError> {
+        if let Some(next_refresh) = self.next_refresh {
+            let now = Instant::now();
+
+            if now >= next_refresh && self.directories.is_none() && self.groups.is_none() {
+                debug!("reloading the lists");
+
+                self.directories = Some(self.client.get_root_directories());
+                self.groups = Some(self.client.get_groups());
+                self.next_refresh = self.refresh_interval.map(|interval| now + interval);
+            }
+        }
+
+        if let Some(result) = take_ready(&mut self.directories) {
+            self.send(ToAppMessage::Directories(result))?;
+        }
+        if let Some(result) = take_ready(&mut self.groups) {
+            self.send(ToAppMessage::Groups(result))?;
+        }
+
+        Ok(())
+    }
+
+    fn poll_jobs(&mut self) {
+        for mut job in std::mem::take(&mut self.jobs) {
+            for promise in std::mem::take(&mut job.promises) {
+                match promise.try_take() {
+                    Ok(result) => {
+                        job.progress.advance();
+                        if let Err(e) = result {
+                            job.error.get_or_insert(e);
                         }
                     }
-                },
-                Err(e) => {
-                    if self
-                        .sender
-                        .send(ToApp {
-                            message: ToAppMessage::Error(AppError::ChannelReceiveError),
-                        })
-                        .is_err()
-                    {
-                        error!("failed to send ToAppMessage::Error for {}", e);
-                    };
+                    Err(promise) => job.promises.push(promise),
+                }
+            }
+
+            if job.promises.is_empty() {
+                job.reply.send(job.error.map_or(Ok(()), Err));
+            } else {
+                self.jobs.push(job);
+            }
+        }
+    }
+
+    /// Names and errors of the watched uids not sent yet, `None` if nothing
+    /// changed.
+    fn user_display_update(&mut self) -> Option<UserDisplayUpdate> {
+        let mut update = UserDisplayUpdate {
+            len: self.user_displays.len(),
+            idle: self.user_displays.is_idle(),
+            ..Default::default()
+        };
+
+        for uid in &self.watched {
+            if let Some(display) = self.user_displays.get(uid) {
+                let revalidated =
+                    !self.user_displays.is_pending(uid) && self.revalidated.remove(uid);
+
+                if revalidated || self.sent_displays.get(uid) != Some(display) {
+                    self.sent_displays.insert(uid.clone(), display.clone());
+                    update.resolved.insert(uid.clone(), display.clone());
+                    update.expires.insert(
+                        uid.clone(),
+                        self.user_displays.expires_in(uid).unwrap_or_default(),
+                    );
                 }
             }
+
+            if let Some(e) = self.user_displays.error(uid) {
+                if self.sent_errors.insert(uid.clone()) {
+                    update.errors.insert(uid.clone(), e.to_string());
+                }
+            }
+        }
+
+        if update.resolved.is_empty()
+            && update.errors.is_empty()
+            && update.len == self.sent_len
+            && update.idle == self.sent_idle
+        {
+            return None;
+        }
+
+        self.sent_len = update.len;
+        self.sent_idle = update.idle;
+
+        Some(update)
+    }
+}
+
+/// The result of `promise` if ready, leaving `None` in its place.
+fn take_ready<T: Send + 'static>(promise: &mut Option<Promise<T>>) -> Option<T> {
+    match promise.take()?.try_take() {
+        Ok(result) => Some(result),
+        Err(pending) => {
+            *promise = Some(pending);
+            None
         }
     }
 }
@@ -0,0 +1,151 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use super::message::UserDisplayUpdate;
+
+/// The user display names known by the app, resolved by the worker.
+///
+/// The uids requested during a frame are queued until `take_requests`,
+/// sent to the worker at the end of the frame. Names past their TTL are
+/// still returned, and requested again.
+#[derive(Debug, Default)]
+pub struct UserDisplays {
+    // Resolved display names, `None` for an unknown account.
+    names: HashMap<String, Option<String>>,
+    // When each name becomes stale.
+    expires_at: HashMap<String, Instant>,
+    // The first of them not passed yet.
+    next_expiry: Option<Instant>,
+    // Error of the uids that could not be resolved.
+    errors: HashMap<String, String>,
+    // Uids sent to the worker, not resolved yet.
+    requested: HashSet<String>,
+    // Uids to send to the worker.
+    queue: Vec<String>,
+    // Display names known by the worker.
+    len: usize,
+    // The worker has uids queued or in flight.
+    worker_busy: bool,
+    // Changed with the names and errors, for the views caching them.
+    generation: u64,
+}
+
+impl UserDisplays {
+    /// Display name of `uid` if resolved, `Some(None)` for an unknown account.
+    pub fn get(&self, uid: &str) -> Option<&Option<String>> {
+        self.names.get(uid)
+    }
+
+    /// Error of the last resolution of `uid`, if it failed.
+    pub fn error(&self, uid: &str) -> Option<&str> {
+        self.errors.get(uid).map(|e| e.as_str())
+    }
+
+    /// Queue `uid` unless it is known and not stale, failed or already
+    /// requested.
+    pub fn request(&mut self, uid: &str) {
+        let stale = self
+            .expires_at
+            .get(uid)
+            .is_some_and(|expires_at| *expires_at <= Instant::now());
+
+        if (self.names.contains_key(uid) && !stale)
+            || self.errors.contains_key(uid)
+            || !self.requested.insert(uid.to_string())
+        {
+            return;
+        }
+
+        self.queue.push(uid.to_string());
+    }
+
+    /// The uids queued since the last call.
+    pub fn take_requests(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.queue)
+    }
+
+    /// Apply the names resolved by the worker.
+    /// Returns true if display names were resolved.
+    pub fn apply(&mut self, update: UserDisplayUpdate) -> bool {
+        self.len = update.len;
+        self.worker_busy = !update.idle;
+
+        for uid in update.resolved.keys().chain(update.errors.keys()) {
+            self.requested.remove(uid);
+        }
+
+        let resolved = !update.resolved.is_empty();
+        if resolved || !update.errors.is_empty() {
+            self.generation += 1;
+        }
+        let now = Instant::now();
+        for (uid, expires) in update.expires {
+            let expires_at = now + expires;
+            self.next_expiry = Some(self.next_expiry.map_or(expires_at, |e| e.min(expires_at)));
+            self.expires_at.insert(uid, expires_at);
+        }
+        self.errors.extend(update.errors);
+        self.names.extend(update.resolved);
+
+        resolved
+    }
+
+    /// Change the generation once a name is stale, for the views caching
+    /// the names to request it again.
+    /// Returns when the next name becomes stale.
+    pub fn expire(&mut self) -> Option<Instant> {
+        let now = Instant::now();
+
+        if self
+            .next_expiry
+            .is_some_and(|next_expiry| next_expiry <= now)
+        {
+            self.generation += 1;
+            self.next_expiry = self
+                .expires_at
+                .values()
+                .filter(|expires_at| **expires_at > now)
+                .min()
+                .copied();
+        }
+
+        self.next_expiry
+    }
+
+    /// Forget the failures, the failed uids are requested again on demand.
+    pub fn clear_errors(&mut self) {
+        self.errors.clear();
+        self.generation += 1;
+    }
+
+    /// Forget every display name, such as after a flush or for another
+    /// backend.
+    pub fn clear(&mut self) {
+        *self = Self {
+            generation: self.generation + 1,
+            ..Self::default()
+        };
+    }
+
+    /// Changes each time names or errors are added or dropped.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Number of display names known by the worker, stale or not.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// True if every requested uid is resolved or failed.
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty() && self.requested.is_empty() && !self.worker_busy
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use log::debug;
+
+use crate::error::apperror::AppError;
+
+use super::builder::Worker;
+use super::message::{ToApp, ToAppMessage, ToWorker, ToWorkerMessage};
+
+/// The app end of the channels of a worker running in its own thread.
+///
+/// The worker is asked to stop when the handle is dropped.
+pub struct WorkerHandle {
+    sender: Sender<ToWorker>,
+    receiver: Receiver<ToApp>,
+}
+
+impl WorkerHandle {
+    /// Run the worker built by `build` with the worker ends of the channels.
+    pub fn spawn<F>(build: F) -> Result<Self, AppError>
+    where
+        F: FnOnce(Sender<ToApp>, Receiver<ToWorker>) -> Worker + Send + 'static,
+    {
+        let (app_tx, app_rx) = mpsc::channel();
+        let (worker_tx, worker_rx) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(move || build(worker_tx, app_rx).init())
+            .map_err(|e| AppError::InternalError(format!("can not start the worker: {}", e)))?;
+
+        Ok(Self {
+            sender: app_tx,
+            receiver: worker_rx,
+        })
+    }
+
+    /// Send `message` to the worker, failing if it stopped.
+    pub fn send(&self, message: ToWorkerMessage) -> Result<(), AppError> {
+        self.sender
+            .send(ToWorker { message })
+            .map_err(|_| AppError::ChannelSendError)
+    }
+
+    /// Next message of the worker, `None` if there is none yet, failing if
+    /// it stopped.
+    pub fn try_recv(&self) -> Result<Option<ToAppMessage>, AppError> {
+        match self.receiver.try_recv() {
+            Ok(message) => Ok(Some(message.message)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(AppError::ChannelClosed),
+        }
+    }
+}
+
+impl Drop for WorkerHandle {
+    fn drop(&mut self) {
+        if self.send(ToWorkerMessage::Shutdown).is_err() {
+            debug!("worker already stopped");
+        }
+    }
+}
@@ -1,24 +1,105 @@
-use crate::error::apperror::AppError;
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use poll_promise::Sender;
+use storm_daenerys_common::types::{directory::Directory, group::Group};
+
+use crate::api::client::ApiClient;
+use crate::api::conditional::ConditionalList;
+use crate::error::{apierror::ApiError, apperror::AppError};
+use crate::snapshot::Snapshot;
+use crate::tasks::Progress;
 
 #[derive(Debug)]
 pub struct ToApp {
     pub message: ToAppMessage,
 }
 
-#[derive(Debug)]
 pub struct ToWorker {
     pub message: ToWorkerMessage,
 }
 
-#[allow(dead_code)]
 #[derive(Debug)]
 pub enum ToAppMessage {
     Pong,
+    // Directories of a background reload.
+    Directories(Result<ConditionalList<Directory>, ApiError>),
+    // Groups of a background reload.
+    Groups(Result<ConditionalList<Group>, ApiError>),
+    // Display names resolved or failed since the last message.
+    UserDisplays(UserDisplayUpdate),
     Error(AppError),
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
 pub enum ToWorkerMessage {
+    #[allow(dead_code)]
     Ping,
+    // Send the next requests with this client, such as for another backend.
+    SetClient(ApiClient),
+    // Reload the directories and groups at this interval, `None` to stop.
+    SetRefreshInterval(Option<Duration>),
+    // Resolve the display names of these uids.
+    ResolveUserDisplays(Vec<String>),
+    // Use GET /userdisplays or not, depending on the backend support.
+    SetBatchSupported(bool),
+    // Resolve the failed uids again when requested.
+    ClearUserDisplayErrors,
+    // Forget every display name, including the persisted ones.
+    FlushUserDisplays,
+    // Write the snapshot to the file.
+    SaveSnapshot(Box<Snapshot>, PathBuf),
+    Job(Job),
+    // Stop the worker.
+    Shutdown,
+}
+
+/// Requests sent together, such as the changes of a form.
+pub enum Job {
+    // Add and remove the members of a group changed from its backup.
+    SaveGroup {
+        group_backup: Group,
+        group: Group,
+        progress: Progress,
+        reply: JobReply,
+    },
+}
+
+/// Sends the result of a job to the app.
+///
+/// A job dropped without result, such as by a stopped worker, is answered
+/// `ApiError::Cancelled`: the app never waits for it.
+pub struct JobReply(Option<Sender<Result<(), ApiError>>>);
+
+impl JobReply {
+    pub fn new(sender: Sender<Result<(), ApiError>>) -> Self {
+        Self(Some(sender))
+    }
+
+    pub fn send(mut self, result: Result<(), ApiError>) {
+        if let Some(sender) = self.0.take() {
+            sender.send(result);
+        }
+    }
+}
+
+impl Drop for JobReply {
+    fn drop(&mut self) {
+        if let Some(sender) = self.0.take() {
+            sender.send(Err(ApiError::Cancelled));
+        }
+    }
+}
+
+/// Display names of the uids requested by the app that changed.
+#[derive(Debug, Default, PartialEq)]
+pub struct UserDisplayUpdate {
+    // Resolved names, `None` for an unknown account.
+    pub resolved: HashMap<String, Option<String>>,
+    // Time left before each resolved name is stale and requested again.
+    pub expires: HashMap<String, Duration>,
+    // Error of the uids that could not be resolved.
+    pub errors: HashMap<String, String>,
+    // Display names known by the worker, stale or not.
+    pub len: usize,
+    // Nothing queued nor in flight.
+    pub idle: bool,
 }
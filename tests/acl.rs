@@ -0,0 +1,245 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{common_error_body, Stub, StubResponse};
+use storm_daenerys_common::types::{
+    acl::{AclEntry, Qualifier},
+    directory::Directory,
+};
+use storm_daenerys_front::{
+    acl::{
+        diff, effective, inheritance, mask, parse_perm, perm_string, AclChange, AclKind,
+        Inheritance,
+    },
+    store::AclQualifier,
+    ui::daenerys::{Action, DaenerysApp},
+};
+
+fn acl(qualifier: Qualifier, cn: &str, perm: u32) -> AclEntry {
+    AclEntry {
+        qualifier,
+        qualifier_cn: Some(cn.to_string()),
+        qualifier_display: None,
+        perm,
+    }
+}
+
+fn parent() -> Vec<AclEntry> {
+    vec![
+        acl(Qualifier::User(1000), "jdoe", 7),
+        acl(Qualifier::Group(2000), "storm", 5),
+        acl(Qualifier::Mask, "mask", 7),
+    ]
+}
+
+#[test]
+fn inherited() {
+    // In any order, the other entries are ignored.
+    let acls = vec![
+        acl(Qualifier::Group(2000), "storm", 5),
+        acl(Qualifier::User(1000), "jdoe", 7),
+    ];
+
+    assert_eq!(inheritance(&acls, &parent()), Inheritance::Inherited);
+}
+
+#[test]
+fn changed() {
+    let acls = vec![
+        acl(Qualifier::User(1000), "jdoe", 7),
+        // Rights changed.
+        acl(Qualifier::Group(2000), "storm", 7),
+        acl(Qualifier::User(1001), "asmith", 5),
+    ];
+
+    assert_eq!(
+        inheritance(&acls, &parent()),
+        Inheritance::Changed {
+            own: vec!["storm".to_string(), "asmith".to_string()],
+            removed: vec![],
+        }
+    );
+
+    // A user and a group of the same name differ.
+    let acls = vec![
+        acl(Qualifier::Group(1000), "jdoe", 7),
+        acl(Qualifier::Group(2000), "storm", 5),
+    ];
+
+    assert_eq!(
+        inheritance(&acls, &parent()),
+        Inheritance::Changed {
+            own: vec!["jdoe".to_string()],
+            removed: vec!["jdoe".to_string()],
+        }
+    );
+}
+
+#[test]
+fn perm_strings() {
+    assert_eq!(perm_string(7), "rwx");
+    assert_eq!(perm_string(5), "r-x");
+    assert_eq!(perm_string(2), "-w-");
+    assert_eq!(perm_string(0), "---");
+}
+
+#[test]
+fn effective_rights() {
+    assert_eq!(mask(&parent()), Some(7));
+    assert_eq!(mask(&parent()[..2]), None);
+
+    // Without mask, the rights of the entry.
+    assert_eq!(effective(7, None), 7);
+    // Only the rights both in the entry and the mask.
+    assert_eq!(effective(7, Some(5)), 5);
+    assert_eq!(effective(6, Some(5)), 4);
+}
+
+#[test]
+fn parsed_perms() {
+    assert_eq!(parse_perm("rwx"), Some(7));
+    assert_eq!(parse_perm("r-x"), Some(5));
+    assert_eq!(parse_perm("---"), Some(0));
+
+    assert_eq!(parse_perm("xwr"), None);
+    assert_eq!(parse_perm("rw"), None);
+    assert_eq!(parse_perm("rwxr"), None);
+    assert_eq!(parse_perm("7"), None);
+}
+
+#[test]
+fn diffs() {
+    let after = vec![
+        acl(Qualifier::User(1000), "jdoe", 7),
+        acl(Qualifier::Group(2000), "storm", 7),
+        acl(Qualifier::Group(2001), "storm-invite", 5),
+        acl(Qualifier::Mask, "mask", 5),
+    ];
+
+    // The mask is ignored.
+    assert_eq!(
+        diff(&parent(), &after),
+        vec![
+            AclChange::Changed(AclQualifier::Group("storm".to_string()), 5, 7),
+            AclChange::Added(AclQualifier::Group("storm-invite".to_string()), 5),
+        ]
+    );
+    assert_eq!(
+        diff(&after, &parent()[..1]),
+        vec![
+            AclChange::Removed(AclQualifier::Group("storm".to_string()), 7),
+            AclChange::Removed(AclQualifier::Group("storm-invite".to_string()), 5),
+        ]
+    );
+    assert!(diff(&parent(), &parent()).is_empty());
+}
+
+#[test]
+fn unresolved_groups() {
+    let mut app = DaenerysApp::default();
+    app.current_directory = Some(Box::new(Directory {
+        name: "project-a".to_string(),
+        acls: vec![
+            acl(Qualifier::User(1000), "jdoe", 7),
+            acl(Qualifier::Group(0), "root", 5),
+        ],
+        valid: true,
+        quota: None,
+    }));
+
+    // Without GET /gid, the group is added by its cn.
+    app.add_group_to_acl("storm-unknown".to_string());
+    assert!(app.current_error.is_none());
+    assert!(app.pending_gids.is_empty());
+    let added = app.acl(AclKind::Access).unwrap().last().unwrap();
+    assert_eq!(added.qualifier, Qualifier::Group(0));
+    assert_eq!(added.qualifier_cn.as_deref(), Some("storm-unknown"));
+
+    // The group:root entry does not block the save.
+    app.save_acls();
+    assert_eq!(
+        app.current_info.as_deref(),
+        Some("saving acl for project-a")
+    );
+
+    // Only a gid being resolved does.
+    app.current_info = None;
+    app.pending_gids.insert("storm-unknown".to_string());
+    app.save_acls();
+    assert!(app.current_info.is_none());
+}
+
+/// Apply the completions of the tasks until none is running.
+fn run_tasks(app: &mut DaenerysApp) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.tasks.running().next().is_some() {
+        assert!(Instant::now() < deadline, "tasks still running");
+        thread::sleep(Duration::from_millis(10));
+        for completion in app.tasks.poll() {
+            completion(app);
+        }
+    }
+}
+
+#[test]
+fn default_acl_saved_again_alone() {
+    let default_failed = Arc::new(AtomicBool::new(false));
+
+    let stub_default_failed = default_failed.clone();
+    let stub = Stub::start(
+        move |request| match (request.method.as_str(), request.url.as_str()) {
+            ("POST", "/defaultacls") if !stub_default_failed.swap(true, Ordering::SeqCst) => {
+                StubResponse::new(500, &common_error_body("disk full"))
+            }
+            ("GET", "/directories") => StubResponse::new(
+                200,
+                r#"[{"name": "project-a", "acls": [], "valid": true, "quota": null}]"#,
+            ),
+            _ => StubResponse::new(200, ""),
+        },
+    );
+
+    let mut app = DaenerysApp::default();
+    app.api_client = stub.client();
+    app.current_directory = Some(Box::new(Directory {
+        name: "project-a".to_string(),
+        acls: parent(),
+        valid: true,
+        quota: None,
+    }));
+    app.current_default_acl = Some(parent());
+    app.active_action = Action::DirectoryEditAcl;
+
+    app.save_acls();
+    run_tasks(&mut app);
+    assert!(app.current_error.is_some());
+    assert_eq!(
+        app.saved_access_acl,
+        Some(("project-a".to_string(), parent()))
+    );
+
+    // The directories are reloaded once the access ACL is saved.
+    let sent = |method: &str, url: &str| {
+        stub.requests()
+            .iter()
+            .filter(|request| request.method == method && request.url == url)
+            .count()
+    };
+    assert_eq!(sent("GET", "/directories"), 1);
+
+    app.save_acls();
+    run_tasks(&mut app);
+    assert_eq!(app.current_info.as_deref(), Some("acl set successfully"));
+    assert!(app.saved_access_acl.is_none());
+
+    assert_eq!(sent("POST", "/acls"), 1);
+    assert_eq!(sent("POST", "/defaultacls"), 2);
+}
@@ -0,0 +1,78 @@
+use storm_daenerys_common::types::{acl::Qualifier, directory::Directory};
+use storm_daenerys_front::{
+    acltemplate::{AclTemplate, TemplateValues},
+    ui::daenerys::DaenerysApp,
+};
+
+fn values() -> TemplateValues {
+    TemplateValues {
+        prefix: "storm".to_string(),
+        name: "project-a".to_string(),
+        admin: "root".to_string(),
+    }
+}
+
+#[test]
+fn project_template() {
+    let template = AclTemplate::project();
+    assert!(template.validate().is_ok());
+
+    let acls = template.resolve(&values());
+    let entries: Vec<(Qualifier, Option<&str>, u32)> = acls
+        .iter()
+        .map(|acl| (acl.qualifier.clone(), acl.qualifier_cn.as_deref(), acl.perm))
+        .collect();
+
+    assert_eq!(
+        entries,
+        vec![
+            (Qualifier::User(0), Some("root"), 7),
+            (Qualifier::Group(0), Some("storm-project-a"), 7),
+            (Qualifier::Group(0), Some("storm-project-a-invite"), 5),
+        ]
+    );
+}
+
+#[test]
+fn invalid_templates() {
+    let mut template = AclTemplate::project();
+    template.entries[1].name = "{prefix}-{project}".to_string();
+    assert!(template.validate().is_err());
+
+    let mut template = AclTemplate::project();
+    template.entries[0].perm = "all".to_string();
+    assert!(template.validate().is_err());
+
+    let mut template = AclTemplate::project();
+    template.entries[0].name = " ".to_string();
+    assert!(template.validate().is_err());
+
+    template.entries.clear();
+    assert!(template.validate().is_err());
+}
+
+#[test]
+fn applied_once_gids_resolved() {
+    let mut app = DaenerysApp::default();
+    app.group_prefix = Some("storm".to_string());
+    app.acl_templates = vec![AclTemplate::project()];
+    app.current_directory = Some(Box::new(Directory {
+        name: "project-a".to_string(),
+        acls: Vec::new(),
+        valid: true,
+        quota: None,
+    }));
+
+    // Without GET /gid, the groups are sent by their cn.
+    app.select_acl_template(0);
+    assert!(app.pending_gids.is_empty());
+    assert!(app.can_apply_acl_template());
+
+    // Not while a gid is being resolved.
+    app.pending_gids.insert("storm-project-a".to_string());
+    assert!(!app.can_apply_acl_template());
+
+    app.apply_acl_template();
+    assert!(app.current_directory.as_ref().unwrap().acls.is_empty());
+    assert_eq!(app.acl_template, Some(0));
+}
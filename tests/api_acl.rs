@@ -0,0 +1,144 @@
+mod common;
+
+use common::{
+    assert_common_error, assert_network_error, assert_raw_error, common_error_body, refused_client,
+    Stub,
+};
+use storm_daenerys_common::types::acl::{AclEntry, Qualifier, SetAcl};
+
+fn set_acl() -> SetAcl {
+    SetAcl {
+        name: "project-a".to_string(),
+        acls: vec![AclEntry {
+            qualifier: Qualifier::User(1000),
+            qualifier_cn: Some("jdoe".to_string()),
+            qualifier_display: None,
+            perm: 7,
+        }],
+    }
+}
+
+#[test]
+fn save_acl() {
+    let stub = Stub::respond(200, "");
+
+    assert!(stub.client().save_acl(set_acl()).block_and_take().is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].url, "/acls");
+
+    let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(body["name"], "project-a");
+    assert_eq!(body["acls"][0]["qualifier_cn"], "jdoe");
+}
+
+#[test]
+fn save_acl_empty_body() {
+    // Empty body on a non 200 status: only the status is known.
+    let stub = Stub::respond(500, "");
+
+    match stub.client().save_acl(set_acl()).block_and_take() {
+        Err(e) => assert_eq!(e.to_string(), "500 Internal Server Error"),
+        Ok(_) => panic!("expected an error"),
+    }
+}
+
+#[test]
+fn save_acl_errors() {
+    let stub = Stub::respond(403, &common_error_body("forbidden"));
+    assert_common_error(stub.client().save_acl(set_acl()).block_and_take(), 403);
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(stub.client().save_acl(set_acl()).block_and_take(), "oops");
+
+    assert_network_error(refused_client().save_acl(set_acl()).block_and_take());
+}
+
+#[test]
+fn get_default_acl() {
+    let stub = Stub::respond(
+        200,
+        r#"[{"qualifier": {"Group": 2000}, "qualifier_cn": "storm", "qualifier_display": null, "perm": 5}]"#,
+    );
+
+    let default_acl = stub
+        .client()
+        .get_default_acl("project-a")
+        .block_and_take()
+        .unwrap();
+    assert_eq!(default_acl.len(), 1);
+    assert_eq!(default_acl[0].qualifier, Qualifier::Group(2000));
+    assert_eq!(default_acl[0].perm, 5);
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].url, "/defaultacls?name=project-a");
+
+    let stub = Stub::respond(200, "");
+    let default_acl = stub.client().get_default_acl("project a/data");
+    assert!(default_acl.block_and_take().unwrap().is_empty());
+    assert_eq!(stub.requests()[0].url, "/defaultacls?name=project+a%2Fdata");
+}
+
+#[test]
+fn get_default_acl_empty_body() {
+    // No default ACL.
+    let stub = Stub::respond(200, "");
+
+    let default_acl = stub
+        .client()
+        .get_default_acl("project-a")
+        .block_and_take()
+        .unwrap();
+    assert!(default_acl.is_empty());
+}
+
+#[test]
+fn get_default_acl_errors() {
+    let stub = Stub::respond(404, &common_error_body("no such directory"));
+    assert_common_error(
+        stub.client().get_default_acl("project-a").block_and_take(),
+        404,
+    );
+
+    assert_network_error(
+        refused_client()
+            .get_default_acl("project-a")
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn save_default_acl() {
+    let stub = Stub::respond(200, "");
+
+    assert!(stub
+        .client()
+        .save_default_acl(set_acl())
+        .block_and_take()
+        .is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].url, "/defaultacls");
+
+    let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(body["name"], "project-a");
+    assert_eq!(body["acls"][0]["qualifier_cn"], "jdoe");
+}
+
+#[test]
+fn save_default_acl_errors() {
+    let stub = Stub::respond(403, &common_error_body("forbidden"));
+    assert_common_error(
+        stub.client().save_default_acl(set_acl()).block_and_take(),
+        403,
+    );
+
+    assert_network_error(
+        refused_client()
+            .save_default_acl(set_acl())
+            .block_and_take(),
+    );
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use std::env;
+
+use common::{assert_common_error, assert_network_error, common_error_body, refused_client, Stub};
+use storm_daenerys_common::types::acl::{AclEntry, Qualifier};
+use storm_daenerys_front::api::acljob::{
+    AclJob, AclJobFailure, AclJobState, AclJobStatus, RecursiveAcl,
+};
+
+const STATUS: &str = r#"{
+    "id": "42",
+    "name": "project-a",
+    "state": "Running",
+    "processed": 150,
+    "total": 600,
+    "failures": [{"path": "project-a/data/locked.dat", "error": "Operation not permitted"}]
+}"#;
+
+fn recursive_acl() -> RecursiveAcl {
+    RecursiveAcl {
+        name: "project-a".to_string(),
+        acls: vec![AclEntry {
+            qualifier: Qualifier::User(1000),
+            qualifier_cn: Some("jdoe".to_string()),
+            qualifier_display: None,
+            perm: 7,
+        }],
+        default_acls: None,
+    }
+}
+
+fn status(failures: &[&str]) -> AclJobStatus {
+    let mut status = AclJobStatus::started(
+        AclJob {
+            id: "42".to_string(),
+        },
+        "project-a/data",
+    );
+    status.failures = failures
+        .iter()
+        .map(|path| AclJobFailure {
+            path: path.to_string(),
+            error: "Operation not permitted".to_string(),
+        })
+        .collect();
+    status
+}
+
+#[test]
+fn start_recursive_acl() {
+    let stub = Stub::respond(200, r#"{"id": "42"}"#);
+
+    let job = stub
+        .client()
+        .start_recursive_acl(recursive_acl())
+        .block_and_take()
+        .unwrap();
+    assert_eq!(job.id, "42");
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].url, "/acls/recursive");
+
+    let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(body["name"], "project-a");
+    assert_eq!(body["acls"][0]["qualifier_cn"], "jdoe");
+    assert!(body["default_acls"].is_null());
+}
+
+#[test]
+fn start_recursive_acl_errors() {
+    // The job id is mandatory.
+    let stub = Stub::respond(200, "");
+    assert!(stub
+        .client()
+        .start_recursive_acl(recursive_acl())
+        .block_and_take()
+        .is_err());
+
+    let stub = Stub::respond(403, &common_error_body("forbidden"));
+    assert_common_error(
+        stub.client()
+            .start_recursive_acl(recursive_acl())
+            .block_and_take(),
+        403,
+    );
+
+    assert_network_error(
+        refused_client()
+            .start_recursive_acl(recursive_acl())
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn get_acl_job() {
+    let stub = Stub::respond(200, STATUS);
+
+    let status = stub.client().get_acl_job("42").block_and_take().unwrap();
+    assert_eq!(status.state, AclJobState::Running);
+    assert!(status.is_running());
+    assert_eq!(status.fraction(), Some(0.25));
+    assert_eq!(status.failures[0].path, "project-a/data/locked.dat");
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].url, "/acljobs?id=42");
+}
+
+#[test]
+fn get_acl_job_errors() {
+    let stub = Stub::respond(404, &common_error_body("no such job"));
+    assert_common_error(stub.client().get_acl_job("42").block_and_take(), 404);
+
+    assert_network_error(refused_client().get_acl_job("42").block_and_take());
+}
+
+#[test]
+fn cancel_acl_job() {
+    let stub = Stub::respond(200, "");
+
+    assert!(stub.client().cancel_acl_job("42").block_and_take().is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(requests[0].url, "/acljobs?id=42");
+}
+
+#[test]
+fn acl_job_id_encoded() {
+    let stub = Stub::respond(200, STATUS);
+    let client = stub.client();
+
+    client.get_acl_job("a/b&c").block_and_take().unwrap();
+    let _ = client.cancel_acl_job("a/b&c").block_and_take();
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].url, "/acljobs?id=a%2Fb%26c");
+    assert_eq!(requests[1].url, "/acljobs?id=a%2Fb%26c");
+}
+
+#[test]
+fn fraction_unknown_total() {
+    let mut status = status(&[]);
+    assert_eq!(status.fraction(), None);
+
+    status.total = Some(0);
+    assert_eq!(status.fraction(), None);
+
+    // Never more than done.
+    status.total = Some(10);
+    status.processed = 12;
+    assert_eq!(status.fraction(), Some(1.0));
+}
+
+#[test]
+fn report() {
+    let status = status(&["project-a/data/a.dat", "project-a/data/b.dat"]);
+
+    assert_eq!(
+        status.report(),
+        "project-a/data/a.dat\tOperation not permitted\n\
+         project-a/data/b.dat\tOperation not permitted\n"
+    );
+    assert_eq!(
+        status.report_file_name(),
+        "acl-report-project-a_data-42.tsv"
+    );
+
+    let path = env::temp_dir()
+        .join(format!("storm-acl-report-{}", std::process::id()))
+        .join(status.report_file_name());
+    status.save_report(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), status.report());
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
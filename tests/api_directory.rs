@@ -0,0 +1,230 @@
+mod common;
+
+use common::{
+    assert_common_error, assert_network_error, assert_raw_error, common_error_body, refused_client,
+    Stub,
+};
+use storm_daenerys_common::types::directory::{CreateDirectory, RenameDirectory};
+use storm_daenerys_front::api::conditional::Conditional;
+
+const DIRECTORIES: &str = r#"[
+    {"name": "project-a", "acls": [], "valid": true, "quota": 1024},
+    {"name": "project-b", "acls": [], "valid": false, "quota": null}
+]"#;
+
+#[test]
+fn get_root_directories() {
+    let stub = Stub::respond(200, DIRECTORIES);
+
+    let directories = stub
+        .client()
+        .get_root_directories()
+        .block_and_take()
+        .unwrap()
+        .modified()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(directories.len(), 2);
+    assert_eq!(directories[0].name, "project-a");
+    assert_eq!(directories[0].quota, Some(1024));
+    assert!(!directories[1].valid);
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].url, "/directories");
+}
+
+#[test]
+fn get_root_directories_empty_body() {
+    let stub = Stub::respond(200, "");
+
+    // An empty list is not an error.
+    let result = stub.client().get_root_directories().block_and_take();
+    assert!(matches!(result, Ok(Conditional::Modified(None))));
+}
+
+#[test]
+fn get_root_directories_common_error() {
+    let stub = Stub::respond(500, &common_error_body("ldap down"));
+
+    assert_common_error(stub.client().get_root_directories().block_and_take(), 500);
+}
+
+#[test]
+fn get_root_directories_garbage_error() {
+    let stub = Stub::respond(502, "Bad Gateway");
+
+    assert_raw_error(
+        stub.client().get_root_directories().block_and_take(),
+        "Bad Gateway",
+    );
+}
+
+#[test]
+fn get_root_directories_connection_refused() {
+    assert_network_error(refused_client().get_root_directories().block_and_take());
+}
+
+#[test]
+fn get_subdirectories() {
+    let stub = Stub::respond(
+        200,
+        r#"[{"name": "project-a/data", "acls": [], "valid": true, "quota": null}]"#,
+    );
+
+    let subdirectories = stub
+        .client()
+        .get_subdirectories("project-a")
+        .block_and_take()
+        .unwrap();
+
+    assert_eq!(subdirectories.len(), 1);
+    assert_eq!(subdirectories[0].name, "project-a/data");
+    assert_eq!(stub.requests()[0].url, "/subdirectories?path=project-a");
+
+    // An empty body means no subdirectory.
+    let stub = Stub::respond(200, "");
+    let subdirectories = stub.client().get_subdirectories("project-a/data");
+    assert!(subdirectories.block_and_take().unwrap().is_empty());
+    assert_eq!(
+        stub.requests()[0].url,
+        "/subdirectories?path=project-a%2Fdata"
+    );
+}
+
+#[test]
+fn get_subdirectories_errors() {
+    let stub = Stub::respond(404, &common_error_body("not found"));
+    assert_common_error(
+        stub.client()
+            .get_subdirectories("project-z")
+            .block_and_take(),
+        404,
+    );
+
+    assert_network_error(
+        refused_client()
+            .get_subdirectories("project-a")
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn create_directory() {
+    let stub = Stub::respond(200, "");
+
+    let result = stub
+        .client()
+        .create_directory(CreateDirectory {
+            name: "project-c".to_string(),
+        })
+        .block_and_take();
+    assert!(result.is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].url, "/directories");
+    assert_eq!(requests[0].header("Content-Type"), Some("application/json"));
+    assert_eq!(requests[0].body, r#"{"name":"project-c"}"#);
+}
+
+#[test]
+fn create_directory_errors() {
+    let create = || CreateDirectory {
+        name: "project-c".to_string(),
+    };
+
+    let stub = Stub::respond(409, &common_error_body("already exists"));
+    assert_common_error(
+        stub.client().create_directory(create()).block_and_take(),
+        409,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client().create_directory(create()).block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(refused_client().create_directory(create()).block_and_take());
+}
+
+#[test]
+fn rename_directory() {
+    let stub = Stub::respond(200, "");
+
+    let result = stub
+        .client()
+        .rename_directory(RenameDirectory {
+            name: "project-a".to_string(),
+            new_name: "project-z".to_string(),
+        })
+        .block_and_take();
+    assert!(result.is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].url, "/directories");
+}
+
+#[test]
+fn rename_directory_errors() {
+    let rename = || RenameDirectory {
+        name: "project-a".to_string(),
+        new_name: "project-z".to_string(),
+    };
+
+    let stub = Stub::respond(404, &common_error_body("not found"));
+    assert_common_error(
+        stub.client().rename_directory(rename()).block_and_take(),
+        404,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client().rename_directory(rename()).block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(refused_client().rename_directory(rename()).block_and_take());
+}
+
+#[test]
+fn delete_directory() {
+    let stub = Stub::respond(200, "");
+
+    let result = stub
+        .client()
+        .delete_directory(CreateDirectory {
+            name: "project-a".to_string(),
+        })
+        .block_and_take();
+    assert!(result.is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(requests[0].url, "/directories");
+    assert_eq!(requests[0].body, r#"{"name":"project-a"}"#);
+}
+
+#[test]
+fn delete_directory_errors() {
+    let delete = || CreateDirectory {
+        name: "project-a".to_string(),
+    };
+
+    let stub = Stub::respond(403, &common_error_body("forbidden"));
+    assert_common_error(
+        stub.client().delete_directory(delete()).block_and_take(),
+        403,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client().delete_directory(delete()).block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(refused_client().delete_directory(delete()).block_and_take());
+}
@@ -0,0 +1,308 @@
+mod common;
+
+use common::{
+    assert_common_error, assert_network_error, assert_raw_error, common_error_body, refused_client,
+    Stub,
+};
+use storm_daenerys_common::types::group::{AddDelUserToGroup, Group};
+use storm_daenerys_front::api::conditional::Conditional;
+
+const GROUPS: &str = r#"[
+    {"cn": "storm", "description": "STORM users", "owner": null, "member": ["jdoe"]},
+    {"cn": "storm-invite", "description": "STORM guests", "owner": null, "member": null}
+]"#;
+
+fn group(cn: &str, members: &[&str]) -> Group {
+    Group {
+        cn: cn.to_string(),
+        description: String::new(),
+        owner: None,
+        member: Some(members.iter().map(|m| m.to_string()).collect()),
+    }
+}
+
+fn add_del(user_cn: &str) -> AddDelUserToGroup {
+    AddDelUserToGroup {
+        group_cn: "storm".to_string(),
+        user_cn: user_cn.to_string(),
+    }
+}
+
+#[test]
+fn get_groups() {
+    let stub = Stub::respond(200, GROUPS);
+
+    let groups = stub
+        .client()
+        .get_groups()
+        .block_and_take()
+        .unwrap()
+        .modified()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].cn, "storm");
+    assert_eq!(groups[0].member, Some(vec!["jdoe".to_string()]));
+    assert_eq!(groups[1].member, None);
+    assert_eq!(stub.requests()[0].url, "/groups");
+}
+
+#[test]
+fn get_groups_empty_body() {
+    let stub = Stub::respond(200, "");
+
+    assert!(matches!(
+        stub.client().get_groups().block_and_take(),
+        Ok(Conditional::Modified(None))
+    ));
+}
+
+#[test]
+fn get_groups_errors() {
+    let stub = Stub::respond(500, &common_error_body("ldap down"));
+    assert_common_error(stub.client().get_groups().block_and_take(), 500);
+
+    let stub = Stub::respond(500, "<html>oops</html>");
+    assert_raw_error(
+        stub.client().get_groups().block_and_take(),
+        "<html>oops</html>",
+    );
+
+    assert_network_error(refused_client().get_groups().block_and_take());
+}
+
+#[test]
+fn get_group_gid() {
+    let stub = Stub::respond(200, "2001");
+
+    let gid = stub.client().get_group_gid("storm-invite").block_and_take();
+
+    assert_eq!(gid.unwrap(), 2001);
+    assert_eq!(stub.requests()[0].url, "/gid?q=storm-invite");
+
+    let stub = Stub::respond(200, "2002");
+    let gid = stub.client().get_group_gid("storm team&x").block_and_take();
+    assert_eq!(gid.unwrap(), 2002);
+    assert_eq!(stub.requests()[0].url, "/gid?q=storm+team%26x");
+}
+
+#[test]
+fn get_group_gid_errors() {
+    // The gid is mandatory.
+    let stub = Stub::respond(200, "");
+    assert!(stub
+        .client()
+        .get_group_gid("storm")
+        .block_and_take()
+        .is_err());
+
+    let stub = Stub::respond(404, &common_error_body("not found"));
+    assert_common_error(stub.client().get_group_gid("other").block_and_take(), 404);
+
+    assert_network_error(refused_client().get_group_gid("storm").block_and_take());
+}
+
+#[test]
+fn create_group() {
+    let stub = Stub::respond(200, "");
+
+    let result = stub
+        .client()
+        .create_group(group("analysis", &[]))
+        .block_and_take();
+    assert!(result.is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].url, "/groups");
+}
+
+#[test]
+fn create_group_errors() {
+    let stub = Stub::respond(409, &common_error_body("already exists"));
+    assert_common_error(
+        stub.client()
+            .create_group(group("analysis", &[]))
+            .block_and_take(),
+        409,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client()
+            .create_group(group("analysis", &[]))
+            .block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(
+        refused_client()
+            .create_group(group("analysis", &[]))
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn delete_group() {
+    let stub = Stub::respond(200, "");
+
+    let result = stub
+        .client()
+        .delete_group("storm-analysis".to_string())
+        .block_and_take();
+    assert!(result.is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(requests[0].url, "/groups/storm-analysis");
+    assert!(requests[0].body.is_empty());
+}
+
+#[test]
+fn delete_group_errors() {
+    let cn = || "storm-analysis".to_string();
+
+    let stub = Stub::respond(404, &common_error_body("not found"));
+    assert_common_error(stub.client().delete_group(cn()).block_and_take(), 404);
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(stub.client().delete_group(cn()).block_and_take(), "oops");
+
+    assert_network_error(refused_client().delete_group(cn()).block_and_take());
+}
+
+#[test]
+fn add_user_to_group() {
+    let stub = Stub::respond(200, "");
+
+    assert!(stub
+        .client()
+        .add_user_to_group(add_del("jdoe"))
+        .block_and_take()
+        .is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "PATCH");
+    assert_eq!(requests[0].url, "/groups/user");
+}
+
+#[test]
+fn add_user_to_group_errors() {
+    let stub = Stub::respond(404, &common_error_body("not found"));
+    assert_common_error(
+        stub.client()
+            .add_user_to_group(add_del("jdoe"))
+            .block_and_take(),
+        404,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client()
+            .add_user_to_group(add_del("jdoe"))
+            .block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(
+        refused_client()
+            .add_user_to_group(add_del("jdoe"))
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn del_user_from_group() {
+    let stub = Stub::respond(200, "");
+
+    assert!(stub
+        .client()
+        .del_user_from_group(add_del("jdoe"))
+        .block_and_take()
+        .is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(requests[0].url, "/groups/user");
+}
+
+#[test]
+fn del_user_from_group_errors() {
+    let stub = Stub::respond(404, &common_error_body("not found"));
+    assert_common_error(
+        stub.client()
+            .del_user_from_group(add_del("jdoe"))
+            .block_and_take(),
+        404,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client()
+            .del_user_from_group(add_del("jdoe"))
+            .block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(
+        refused_client()
+            .del_user_from_group(add_del("jdoe"))
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn save_group_sends_member_diff() {
+    let stub = Stub::respond(200, "");
+
+    let promises = stub.client().save_group(
+        group("storm-analysis", &["jdoe", "asmith"]),
+        group("storm-analysis", &["asmith", "bmartin"]),
+    );
+    assert_eq!(promises.len(), 2);
+    for promise in promises {
+        assert!(promise.block_and_take().is_ok());
+    }
+
+    let mut requests: Vec<(String, String)> = stub
+        .requests()
+        .into_iter()
+        .map(|r| (r.method, r.body))
+        .collect();
+    requests.sort();
+
+    assert_eq!(requests[0].0, "DELETE");
+    assert!(requests[0].1.contains("jdoe"));
+    assert_eq!(requests[1].0, "PATCH");
+    assert!(requests[1].1.contains("bmartin"));
+}
+
+#[test]
+fn save_group_unchanged() {
+    let stub = Stub::respond(200, "");
+
+    let promises = stub.client().save_group(
+        group("storm-analysis", &["jdoe"]),
+        group("storm-analysis", &["jdoe"]),
+    );
+    assert!(promises.is_empty());
+}
+
+#[test]
+fn save_group_errors() {
+    let stub = Stub::respond(500, &common_error_body("ldap down"));
+    for promise in stub.client().save_group(
+        group("storm-analysis", &["jdoe"]),
+        group("storm-analysis", &["asmith"]),
+    ) {
+        assert_common_error(promise.block_and_take(), 500);
+    }
+
+    for promise in refused_client().save_group(
+        group("storm-analysis", &["jdoe"]),
+        group("storm-analysis", &["asmith"]),
+    ) {
+        assert_network_error(promise.block_and_take());
+    }
+}
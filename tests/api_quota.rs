@@ -0,0 +1,44 @@
+mod common;
+
+use common::{
+    assert_common_error, assert_network_error, assert_raw_error, common_error_body, refused_client,
+    Stub,
+};
+use storm_daenerys_common::types::quota::SetQuota;
+
+fn set_quota() -> SetQuota {
+    SetQuota {
+        name: "project-a".to_string(),
+        quota: 1024 * 1024,
+    }
+}
+
+#[test]
+fn save_quota() {
+    let stub = Stub::respond(200, "");
+
+    assert!(stub
+        .client()
+        .save_quota(set_quota())
+        .block_and_take()
+        .is_ok());
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].url, "/quota");
+    assert_eq!(requests[0].body, r#"{"name":"project-a","quota":1048576}"#);
+}
+
+#[test]
+fn save_quota_errors() {
+    let stub = Stub::respond(400, &common_error_body("invalid quota"));
+    assert_common_error(stub.client().save_quota(set_quota()).block_and_take(), 400);
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client().save_quota(set_quota()).block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(refused_client().save_quota(set_quota()).block_and_take());
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::{
+    assert_common_error, assert_network_error, assert_raw_error, common_error_body, refused_client,
+    Stub,
+};
+use storm_daenerys_front::error::apierror::{ApiError, ApiErrorKind};
+
+const CONFIG: &str = r#"{
+    "admin": "jdoe",
+    "connected_user": "asmith",
+    "current_admin_restriction": null,
+    "users_dsi_api_group_prefix": "storm",
+    "root_groups": ["a", "b"],
+    "quota": {"total_space": 100, "available_space": 40}
+}"#;
+
+#[test]
+fn get_config() {
+    let stub = Stub::respond(200, CONFIG);
+
+    let config = stub.client().get_config().block_and_take().unwrap();
+
+    assert_eq!(config.admin, "jdoe");
+    assert_eq!(config.connected_user, "asmith");
+    assert_eq!(config.users_dsi_api_group_prefix, "storm");
+    assert_eq!(
+        config.root_groups,
+        Some(vec!["a".to_string(), "b".to_string()])
+    );
+    assert_eq!(stub.requests()[0].url, "/config");
+}
+
+#[test]
+fn get_config_empty_body() {
+    // Unlike the directory and group lists, an empty config is an error.
+    let stub = Stub::respond(200, "");
+
+    match stub.client().get_config().block_and_take() {
+        Err(ApiError::UnexpectedResponse(_)) => (),
+        other => panic!("expected an unexpected response error, got {:?}", other),
+    }
+}
+
+#[test]
+fn get_config_invalid_body() {
+    let stub = Stub::respond(200, r#"{"admin": "jdoe"}"#);
+
+    match stub.client().get_config().block_and_take() {
+        Err(e) => assert_eq!(e.kind(), ApiErrorKind::Protocol),
+        Ok(_) => panic!("expected a decode error"),
+    }
+}
+
+#[test]
+fn get_config_errors() {
+    let stub = Stub::respond(401, &common_error_body("unauthorized"));
+    match stub.client().get_config().block_and_take() {
+        Err(e) => assert_eq!(e.kind(), ApiErrorKind::Unauthorized),
+        Ok(_) => panic!("expected an error"),
+    }
+
+    let stub = Stub::respond(500, &common_error_body("ldap down"));
+    assert_common_error(stub.client().get_config().block_and_take(), 500);
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(stub.client().get_config().block_and_take(), "oops");
+
+    assert_network_error(refused_client().get_config().block_and_take());
+}
+
+#[test]
+fn get_version() {
+    let stub = Stub::respond(200, r#"{"version": "2.1.0", "protocol": 2}"#);
+
+    let version = stub
+        .client()
+        .get_version()
+        .block_and_take()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(version.version, "2.1.0");
+    assert_eq!(version.protocol, 2);
+    assert_eq!(stub.requests()[0].url, "/version");
+}
+
+#[test]
+fn get_version_legacy_backend() {
+    // Backends older than the protocol 2 have no version endpoint.
+    let stub = Stub::respond(404, &common_error_body("not found"));
+    assert!(matches!(
+        stub.client().get_version().block_and_take(),
+        Ok(None)
+    ));
+}
+
+#[test]
+fn get_version_errors() {
+    let stub = Stub::respond(200, r#"{"release": "2.1.0"}"#);
+    match stub.client().get_version().block_and_take() {
+        Err(e) => assert_eq!(e.kind(), ApiErrorKind::Protocol),
+        Ok(_) => panic!("expected an error"),
+    }
+
+    let stub = Stub::respond(500, &common_error_body("down"));
+    assert_common_error(stub.client().get_version().block_and_take(), 500);
+
+    assert_network_error(refused_client().get_version().block_and_take());
+}
+
+#[test]
+fn get_du() {
+    let stub = Stub::respond(200, "1.2T\t/storm/project-a\n");
+
+    let du = stub.client().get_du().block_and_take().unwrap();
+
+    assert_eq!(du, Some("1.2T\t/storm/project-a\n".to_string()));
+    assert_eq!(stub.requests()[0].url, "/du");
+}
+
+#[test]
+fn get_du_empty_body() {
+    let stub = Stub::respond(200, "");
+
+    match stub.client().get_du().block_and_take() {
+        Err(ApiError::UnexpectedResponse(_)) => (),
+        other => panic!("expected an unexpected response error, got {:?}", other),
+    }
+}
+
+#[test]
+fn get_du_errors() {
+    let stub = Stub::respond(500, &common_error_body("du failed"));
+    assert_common_error(stub.client().get_du().block_and_take(), 500);
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(stub.client().get_du().block_and_take(), "oops");
+
+    assert_network_error(refused_client().get_du().block_and_take());
+}
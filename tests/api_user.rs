@@ -0,0 +1,158 @@
+mod common;
+
+use common::{
+    assert_common_error, assert_network_error, assert_raw_error, common_error_body, refused_client,
+    Stub,
+};
+
+#[test]
+fn get_users() {
+    let stub = Stub::respond(
+        200,
+        r#"[{"id": "jdoe", "display": "John Doe"}, {"id": "jdupont", "display": "Jean Dupont"}]"#,
+    );
+
+    let users = stub
+        .client()
+        .get_users("jd".to_string())
+        .block_and_take()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[1].display, "Jean Dupont");
+    assert_eq!(stub.requests()[0].url, "/users?q=jd");
+}
+
+#[test]
+fn get_users_empty_body() {
+    let stub = Stub::respond(200, "");
+
+    assert!(matches!(
+        stub.client().get_users("jd".to_string()).block_and_take(),
+        Ok(None)
+    ));
+}
+
+#[test]
+fn get_users_errors() {
+    let stub = Stub::respond(500, &common_error_body("ldap down"));
+    assert_common_error(
+        stub.client().get_users("jd".to_string()).block_and_take(),
+        500,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client().get_users("jd".to_string()).block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(
+        refused_client()
+            .get_users("jd".to_string())
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn get_user_display() {
+    let stub = Stub::respond(200, r#""John Doe""#);
+
+    let display = stub
+        .client()
+        .get_user_display("jdoe".to_string())
+        .block_and_take();
+
+    assert_eq!(display.unwrap(), Some("John Doe".to_string()));
+    assert_eq!(stub.requests()[0].url, "/userdisplay?q=jdoe");
+}
+
+#[test]
+fn get_user_display_unknown_user() {
+    // The backend answers null for an unknown account.
+    let stub = Stub::respond(200, "null");
+
+    let display = stub
+        .client()
+        .get_user_display("ghost".to_string())
+        .block_and_take();
+
+    assert_eq!(display.unwrap(), None);
+}
+
+#[test]
+fn get_user_display_empty_body() {
+    let stub = Stub::respond(200, "");
+
+    let display = stub
+        .client()
+        .get_user_display("ghost".to_string())
+        .block_and_take();
+
+    assert_eq!(display.unwrap(), Some("invalid user".to_string()));
+}
+
+#[test]
+fn get_user_display_errors() {
+    let stub = Stub::respond(200, "not json");
+    assert!(stub
+        .client()
+        .get_user_display("jdoe".to_string())
+        .block_and_take()
+        .is_err());
+
+    let stub = Stub::respond(500, &common_error_body("ldap down"));
+    assert_common_error(
+        stub.client()
+            .get_user_display("jdoe".to_string())
+            .block_and_take(),
+        500,
+    );
+
+    let stub = Stub::respond(500, "oops");
+    assert_raw_error(
+        stub.client()
+            .get_user_display("jdoe".to_string())
+            .block_and_take(),
+        "oops",
+    );
+
+    assert_network_error(
+        refused_client()
+            .get_user_display("jdoe".to_string())
+            .block_and_take(),
+    );
+}
+
+#[test]
+fn get_user_displays() {
+    let stub = Stub::respond(200, r#"{"jdoe": "John Doe", "ghost": null}"#);
+
+    let displays = stub
+        .client()
+        .get_user_displays(&["jdoe".to_string(), "ghost".to_string()])
+        .block_and_take()
+        .unwrap();
+
+    assert_eq!(displays["jdoe"], Some("John Doe".to_string()));
+    assert_eq!(displays["ghost"], None);
+    assert_eq!(stub.requests()[0].url, "/userdisplays?q=jdoe,ghost");
+}
+
+#[test]
+fn query_values_are_encoded() {
+    let stub = Stub::respond(200, "{}");
+    let client = stub.client();
+
+    let _ = client
+        .get_users("john doe&x=1".to_string())
+        .block_and_take();
+    let _ = client
+        .get_user_displays(&["j,doe".to_string(), "é".to_string()])
+        .block_and_take();
+
+    let requests = stub.requests();
+    assert_eq!(requests[0].url, "/users?q=john+doe%26x%3D1");
+    assert_eq!(requests[1].url, "/userdisplays?q=j%2Cdoe,%C3%A9");
+}
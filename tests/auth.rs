@@ -0,0 +1,324 @@
+mod common;
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use common::{RecordedRequest, Stub, StubResponse};
+use eframe::egui;
+use storm_daenerys_front::{
+    api::auth::{
+        credentials::{AuthConfig, Credentials, OidcConfig, Token},
+        device::{DeviceFlow, PollStatus},
+    },
+    error::apierror::{ApiError, ApiErrorKind},
+};
+
+fn oidc_config(idp: &Stub) -> OidcConfig {
+    OidcConfig {
+        issuer: idp.url.clone(),
+        client_id: "storm-daenerys".to_string(),
+        scope: "openid offline_access".to_string(),
+    }
+}
+
+/// Discovery document pointing to the stub itself.
+fn discovery(request: &RecordedRequest) -> StubResponse {
+    let base = format!("http://{}", request.header("Host").unwrap());
+
+    StubResponse::new(
+        200,
+        &serde_json::json!({
+            "issuer": base,
+            "device_authorization_endpoint": format!("{}/device", base),
+            "token_endpoint": format!("{}/token", base),
+        })
+        .to_string(),
+    )
+}
+
+const DEVICE_AUTHORIZATION: &str = r#"{
+    "device_code": "dev-123",
+    "user_code": "ABCD-EFGH",
+    "verification_uri": "https://idp.example.org/device",
+    "verification_uri_complete": "https://idp.example.org/device?user_code=ABCD-EFGH",
+    "expires_in": 600,
+    "interval": 1
+}"#;
+
+const TOKEN: &str = r#"{
+    "access_token": "access-1",
+    "token_type": "Bearer",
+    "refresh_token": "refresh-1",
+    "expires_in": 3600
+}"#;
+
+fn oauth_error(error: &str) -> StubResponse {
+    StubResponse::new(400, &format!(r#"{{"error": "{}"}}"#, error))
+}
+
+/// A mock identity provider answering `token` to the token requests.
+fn identity_provider<F>(token: F) -> Stub
+where
+    F: Fn(&RecordedRequest) -> StubResponse + Send + 'static,
+{
+    Stub::start(move |request| match request.url.as_str() {
+        "/.well-known/openid-configuration" => discovery(request),
+        "/device" => StubResponse::new(200, DEVICE_AUTHORIZATION),
+        "/token" => token(request),
+        _ => StubResponse::new(404, ""),
+    })
+}
+
+fn authorization_header(request: &RecordedRequest) -> Option<String> {
+    request.header("Authorization").map(|h| h.to_string())
+}
+
+#[test]
+fn no_credentials() {
+    let backend = Stub::respond(200, "[]");
+
+    backend.client().get_groups().block_and_take().unwrap();
+
+    assert_eq!(authorization_header(&backend.requests()[0]), None);
+}
+
+#[test]
+fn bearer_token() {
+    let backend = Stub::respond(200, "[]");
+    let client = backend
+        .client()
+        .with_credentials(Credentials::new(AuthConfig::Bearer("s3cret".to_string())));
+
+    client.get_groups().block_and_take().unwrap();
+    client.get_groups().block_and_take().unwrap();
+
+    for request in backend.requests() {
+        assert_eq!(
+            authorization_header(&request),
+            Some("Bearer s3cret".to_string())
+        );
+    }
+}
+
+#[test]
+fn other_backend_same_credentials() {
+    let first = Stub::start(|_| StubResponse::new(200, "[]").with_header("ETag", "\"v1\""));
+    let second = Stub::respond(200, "[]");
+    let client = first
+        .client()
+        .with_credentials(Credentials::new(AuthConfig::Bearer("s3cret".to_string())));
+    client.get_groups().block_and_take().unwrap();
+
+    let other = client.with_api_url(&format!("{}/", second.url));
+    assert_eq!(other.api_url(), second.url);
+    other.get_groups().block_and_take().unwrap();
+
+    let request = &second.requests()[0];
+    assert_eq!(
+        authorization_header(request),
+        Some("Bearer s3cret".to_string())
+    );
+    // The validators of the first backend are not sent.
+    assert_eq!(request.header("If-None-Match"), None);
+}
+
+#[test]
+fn bearer_token_rejected() {
+    let backend = Stub::respond(401, "");
+    let client = backend
+        .client()
+        .with_credentials(Credentials::new(AuthConfig::Bearer("expired".to_string())));
+
+    match client.get_groups().block_and_take() {
+        Err(e) => assert_eq!(e.kind(), ApiErrorKind::Unauthorized),
+        Ok(_) => panic!("expected a 401"),
+    }
+}
+
+#[test]
+fn token_redacted() {
+    let config = AuthConfig::Bearer("s3cret".to_string());
+    let token = Token::new("s3cret".to_string());
+
+    assert!(!format!("{:?}", config).contains("s3cret"));
+    assert!(!format!("{:?}", token).contains("s3cret"));
+}
+
+#[test]
+fn device_flow_login() {
+    let polls = AtomicUsize::new(0);
+    let idp = identity_provider(move |_| match polls.fetch_add(1, Ordering::SeqCst) {
+        0 => oauth_error("authorization_pending"),
+        1 => oauth_error("slow_down"),
+        _ => StubResponse::new(200, TOKEN),
+    });
+    let flow = DeviceFlow::new(&egui::Context::default(), oidc_config(&idp));
+
+    // Device code.
+    let mut authorization = flow.start().block_and_take().unwrap();
+
+    assert_eq!(authorization.user_code, "ABCD-EFGH");
+    assert_eq!(authorization.token_endpoint, format!("{}/token", idp.url));
+    assert_eq!(authorization.interval.as_secs(), 1);
+    assert!(!authorization.is_expired());
+
+    let requests = idp.requests();
+    assert_eq!(requests[1].method, "POST");
+    assert_eq!(
+        requests[1].header("Content-Type"),
+        Some("application/x-www-form-urlencoded")
+    );
+    assert_eq!(
+        requests[1].body,
+        "client_id=storm-daenerys&scope=openid+offline_access"
+    );
+
+    // Token polling.
+    assert!(matches!(
+        flow.poll(&authorization).block_and_take(),
+        Ok(PollStatus::Pending)
+    ));
+    assert!(matches!(
+        flow.poll(&authorization).block_and_take(),
+        Ok(PollStatus::SlowDown)
+    ));
+    authorization.slow_down();
+    assert_eq!(authorization.interval.as_secs(), 6);
+
+    let token = match flow.poll(&authorization).block_and_take() {
+        Ok(PollStatus::Granted(token)) => token,
+        other => panic!("expected a token, got {:?}", other),
+    };
+    assert_eq!(token.access_token, "access-1");
+    assert_eq!(token.refresh_token, Some("refresh-1".to_string()));
+    assert!(!token.is_expired());
+
+    assert_eq!(
+        idp.requests()[2].body,
+        "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code\
+         &device_code=dev-123&client_id=storm-daenerys"
+    );
+
+    // The token is sent to the backend once logged in.
+    let credentials = Credentials::new(AuthConfig::Oidc(oidc_config(&idp)));
+    assert!(credentials.needs_login());
+
+    credentials.set_token(token, Some(authorization.token_endpoint));
+    assert!(!credentials.needs_login());
+
+    let backend = Stub::respond(200, "[]");
+    backend
+        .client()
+        .with_credentials(credentials)
+        .get_groups()
+        .block_and_take()
+        .unwrap();
+
+    assert_eq!(
+        authorization_header(&backend.requests()[0]),
+        Some("Bearer access-1".to_string())
+    );
+}
+
+#[test]
+fn device_flow_denied() {
+    let idp = identity_provider(|_| oauth_error("access_denied"));
+    let flow = DeviceFlow::new(&egui::Context::default(), oidc_config(&idp));
+
+    let authorization = flow.start().block_and_take().unwrap();
+
+    match flow.poll(&authorization).block_and_take() {
+        Err(ApiError::Auth { error, .. }) => assert_eq!(error, "access_denied"),
+        other => panic!("expected access_denied, got {:?}", other),
+    }
+}
+
+#[test]
+fn device_flow_unsupported() {
+    let idp = Stub::respond(200, r#"{"token_endpoint": "http://127.0.0.1/token"}"#);
+    let flow = DeviceFlow::new(&egui::Context::default(), oidc_config(&idp));
+
+    match flow.start().block_and_take() {
+        Err(e @ ApiError::Auth { .. }) => assert_eq!(e.kind(), ApiErrorKind::Unauthorized),
+        other => panic!("expected an auth error, got {:?}", other),
+    }
+}
+
+#[test]
+fn device_flow_unreachable() {
+    let idp = Stub::respond(500, "oops");
+    let flow = DeviceFlow::new(&egui::Context::default(), oidc_config(&idp));
+
+    match flow.start().block_and_take() {
+        Err(e) => assert_eq!(e.kind(), ApiErrorKind::Server),
+        Ok(_) => panic!("expected an error"),
+    }
+}
+
+/// Credentials holding an expired token with a refresh token.
+fn expired_credentials(idp: &Stub) -> Credentials {
+    let credentials = Credentials::new(AuthConfig::Oidc(oidc_config(idp)));
+    credentials.set_token(
+        Token {
+            access_token: "access-0".to_string(),
+            refresh_token: Some("refresh-0".to_string()),
+            expires_at: Some(Instant::now()),
+        },
+        Some(format!("{}/token", idp.url)),
+    );
+    credentials
+}
+
+#[test]
+fn refresh_expired_token() {
+    let idp = identity_provider(|_| StubResponse::new(200, r#"{"access_token": "access-1"}"#));
+    let backend = Stub::respond(200, "[]");
+    let client = backend.client().with_credentials(expired_credentials(&idp));
+
+    // Concurrent requests share a single refresh.
+    let promises = vec![
+        client.get_groups(),
+        client.get_groups(),
+        client.get_groups(),
+    ];
+    for promise in promises {
+        promise.block_and_take().unwrap();
+    }
+
+    let idp_requests = idp.requests();
+    assert_eq!(idp_requests.len(), 1);
+    assert_eq!(
+        idp_requests[0].body,
+        "grant_type=refresh_token&refresh_token=refresh-0&client_id=storm-daenerys"
+    );
+
+    for request in backend.requests() {
+        assert_eq!(
+            authorization_header(&request),
+            Some("Bearer access-1".to_string())
+        );
+    }
+
+    // The refreshed token does not expire, no further refresh.
+    client.get_groups().block_and_take().unwrap();
+    assert_eq!(idp.requests().len(), 1);
+}
+
+#[test]
+fn refresh_failure_logs_out() {
+    let idp = identity_provider(|_| oauth_error("invalid_grant"));
+    let backend = Stub::respond(401, "");
+    let credentials = expired_credentials(&idp);
+    let client = backend.client().with_credentials(credentials.clone());
+
+    match client.get_groups().block_and_take() {
+        Err(e) => assert_eq!(e.kind(), ApiErrorKind::Unauthorized),
+        Ok(_) => panic!("expected a 401"),
+    }
+
+    assert_eq!(authorization_header(&backend.requests()[0]), None);
+    assert!(!credentials.is_logged_in());
+    assert!(credentials.needs_login());
+}
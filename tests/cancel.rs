@@ -0,0 +1,50 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::Stub;
+use storm_daenerys_front::{
+    api::{cancel::CancelToken, retry::RetryPolicy},
+    error::apierror::{ApiError, ApiErrorKind},
+};
+
+#[test]
+fn cancelled_before_sending() {
+    let stub = Stub::respond(200, "[]");
+    let cancel = CancelToken::default();
+    cancel.cancel();
+
+    let result = stub
+        .client()
+        .with_cancel(&cancel)
+        .get_users("jd".to_string())
+        .block_and_take();
+
+    match result {
+        Err(e) => assert_eq!(e.kind(), ApiErrorKind::Cancelled),
+        other => panic!("expected a cancelled request, got {:?}", other),
+    }
+    assert!(stub.requests().is_empty());
+}
+
+#[test]
+fn cancelled_while_waiting_for_retry() {
+    let stub = Stub::respond(503, "Service Unavailable");
+    let cancel = CancelToken::default();
+
+    let promise = stub
+        .client()
+        .with_retry(RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_millis(200),
+        })
+        .with_cancel(&cancel)
+        .get_users("jd".to_string());
+
+    thread::sleep(Duration::from_millis(50));
+    cancel.cancel();
+
+    assert!(matches!(promise.block_and_take(), Err(ApiError::Cancelled)));
+    assert_eq!(stub.requests().len(), 1);
+}
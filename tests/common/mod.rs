@@ -0,0 +1,186 @@
+// Local HTTP stub used to test the API client.
+#![allow(dead_code)]
+
+use std::{
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use eframe::egui;
+use storm_daenerys_common::types::error::CommonError;
+use storm_daenerys_front::{
+    api::{client::ApiClient, retry::RetryPolicy},
+    error::apierror::{ApiError, ApiErrorKind},
+};
+use tiny_http::{Header, Server};
+
+/// A request received by the stub.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// Value of the header `key`, case insensitive.
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A response sent by the stub.
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubResponse {
+    pub fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// HTTP server answering every request with the response built by a handler.
+pub struct Stub {
+    pub url: String,
+    server: Arc<Server>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Stub {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> StubResponse + Send + 'static,
+    {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let requests: Arc<Mutex<Vec<RecordedRequest>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let thread_server = server.clone();
+        let thread_requests = requests.clone();
+        let handle = thread::spawn(move || {
+            for mut request in thread_server.incoming_requests() {
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+
+                let recorded = RecordedRequest {
+                    method: request.method().to_string(),
+                    url: request.url().to_string(),
+                    headers: request
+                        .headers()
+                        .iter()
+                        .map(|h| (h.field.to_string(), h.value.to_string()))
+                        .collect(),
+                    body,
+                };
+
+                let stub_response = handler(&recorded);
+                thread_requests.lock().unwrap().push(recorded);
+
+                let mut response = tiny_http::Response::from_string(stub_response.body)
+                    .with_status_code(stub_response.status);
+                for (key, value) in stub_response.headers {
+                    response.add_header(Header::from_bytes(key, value).unwrap());
+                }
+
+                let _ = request.respond(response);
+            }
+        });
+
+        Self {
+            url,
+            server,
+            requests,
+            handle: Some(handle),
+        }
+    }
+
+    /// A stub answering `status` and `body` to every request.
+    pub fn respond(status: u16, body: &str) -> Self {
+        let body = body.to_string();
+        Self::start(move |_| StubResponse::new(status, &body))
+    }
+
+    /// Requests received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Client of the stub, not retrying so that failures are seen at once.
+    pub fn client(&self) -> ApiClient {
+        ApiClient::new(&egui::Context::default(), self.url.clone()).with_retry(RetryPolicy::none())
+    }
+}
+
+impl Drop for Stub {
+    fn drop(&mut self) {
+        self.server.unblock();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// A client of an URL where nothing listens.
+pub fn refused_client() -> ApiClient {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    ApiClient::new(&egui::Context::default(), url).with_retry(RetryPolicy::none())
+}
+
+/// Body of a backend error.
+pub fn common_error_body(message: &str) -> String {
+    serde_json::to_string(&CommonError::InternalServerError(message.to_string())).unwrap()
+}
+
+/// Check that `result` is the error of a non 200 response with a `CommonError` body.
+pub fn assert_common_error<T: std::fmt::Debug>(result: Result<T, ApiError>, expected_status: u16) {
+    match result {
+        Err(ApiError::Http {
+            status,
+            error: Some(_),
+            body: None,
+            ..
+        }) => assert_eq!(status, expected_status),
+        other => panic!("expected a CommonError, got {:?}", other),
+    }
+}
+
+/// Check that `result` is the error of a non 200 response with an unparsable body.
+pub fn assert_raw_error<T: std::fmt::Debug>(result: Result<T, ApiError>, expected_body: &str) {
+    match result {
+        Err(ApiError::Http {
+            error: None,
+            body: Some(body),
+            ..
+        }) => assert_eq!(body, expected_body),
+        other => panic!("expected a raw body error, got {:?}", other),
+    }
+}
+
+pub fn assert_network_error<T: std::fmt::Debug>(result: Result<T, ApiError>) {
+    match result {
+        Err(ref e) if e.kind() == ApiErrorKind::Network => (),
+        other => panic!("expected a network error, got {:?}", other),
+    }
+}
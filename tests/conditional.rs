@@ -0,0 +1,266 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::{RecordedRequest, Stub, StubResponse};
+use storm_daenerys_front::{
+    api::{cancel::CancelToken, client::ApiClient, conditional::Conditional},
+    error::apierror::ApiError,
+    tasks::{Task, TaskManager},
+};
+
+const GROUPS: &str = r#"[{"cn": "storm", "description": "", "owner": null, "member": null}]"#;
+const GROUPS_V2: &str = r#"[
+    {"cn": "storm", "description": "", "owner": null, "member": null},
+    {"cn": "storm-invite", "description": "", "owner": null, "member": null}
+]"#;
+const DIRECTORIES: &str = r#"[{"name": "project-a", "acls": [], "valid": true, "quota": null}]"#;
+const LAST_MODIFIED: &str = "Wed, 21 Oct 2026 07:28:00 GMT";
+
+/// A backend revalidating `body` with the `etag` and `LAST_MODIFIED` validators.
+fn revalidating(etag: &'static str, body: &'static str) -> Stub {
+    Stub::start(move |request| {
+        if request.header("If-None-Match") == Some(etag) {
+            StubResponse::new(304, "").with_header("ETag", etag)
+        } else {
+            StubResponse::new(200, body)
+                .with_header("ETag", etag)
+                .with_header("Last-Modified", LAST_MODIFIED)
+        }
+    })
+}
+
+fn conditional_headers(request: &RecordedRequest) -> (Option<&str>, Option<&str>) {
+    (
+        request.header("If-None-Match"),
+        request.header("If-Modified-Since"),
+    )
+}
+
+#[test]
+fn groups_not_modified() {
+    let stub = revalidating("\"v1\"", GROUPS);
+    let client = stub.client();
+
+    let first = client.get_groups().block_and_take().unwrap();
+    assert_eq!(first.modified().unwrap().unwrap().len(), 1);
+
+    let second = client.get_groups().block_and_take().unwrap();
+    assert_eq!(second, Conditional::NotModified);
+
+    let requests = stub.requests();
+    assert_eq!(conditional_headers(&requests[0]), (None, None));
+    assert_eq!(
+        conditional_headers(&requests[1]),
+        (Some("\"v1\""), Some(LAST_MODIFIED))
+    );
+}
+
+#[test]
+fn directories_not_modified() {
+    let stub = revalidating("\"d1\"", DIRECTORIES);
+    let client = stub.client();
+
+    client.get_root_directories().block_and_take().unwrap();
+    let second = client.get_root_directories().block_and_take().unwrap();
+
+    assert_eq!(second, Conditional::NotModified);
+    assert_eq!(stub.requests()[1].header("If-None-Match"), Some("\"d1\""));
+}
+
+#[test]
+fn validators_per_path() {
+    let stub = revalidating("\"v1\"", GROUPS);
+    let client = stub.client();
+
+    client.get_groups().block_and_take().unwrap();
+    client.get_root_directories().block_and_take().ok();
+
+    // The validators of /groups are not sent to /directories.
+    assert_eq!(conditional_headers(&stub.requests()[1]), (None, None));
+}
+
+#[test]
+fn modified_after_change() {
+    let version = Arc::new(AtomicUsize::new(1));
+
+    let stub_version = version.clone();
+    let stub = Stub::start(move |request| {
+        let (etag, body) = match stub_version.load(Ordering::SeqCst) {
+            1 => ("\"v1\"", GROUPS),
+            _ => ("\"v2\"", GROUPS_V2),
+        };
+
+        if request.header("If-None-Match") == Some(etag) {
+            StubResponse::new(304, "")
+        } else {
+            StubResponse::new(200, body).with_header("ETag", etag)
+        }
+    });
+    let client = stub.client();
+
+    client.get_groups().block_and_take().unwrap();
+    version.store(2, Ordering::SeqCst);
+
+    let groups = client.get_groups().block_and_take().unwrap();
+    assert_eq!(groups.modified().unwrap().unwrap().len(), 2);
+
+    // The new validator is sent next.
+    let groups = client.get_groups().block_and_take().unwrap();
+    assert_eq!(groups, Conditional::NotModified);
+
+    let requests = stub.requests();
+    assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+    assert_eq!(requests[2].header("If-None-Match"), Some("\"v2\""));
+}
+
+/// A backend serving `GROUPS` until `version` is 2, then `GROUPS_V2`.
+fn versioned_groups(version: Arc<AtomicUsize>) -> Stub {
+    Stub::start(move |request| {
+        let (etag, body) = match version.load(Ordering::SeqCst) {
+            1 => ("\"v1\"", GROUPS),
+            _ => ("\"v2\"", GROUPS_V2),
+        };
+
+        if request.header("If-None-Match") == Some(etag) {
+            StubResponse::new(304, "")
+        } else {
+            StubResponse::new(200, body).with_header("ETag", etag)
+        }
+    })
+}
+
+/// Load the groups as the app does, superseding the load in flight, once
+/// the response arrived. The task keeps the number of groups.
+fn load_groups(tasks: &mut TaskManager<usize>, client: &ApiClient) {
+    tasks.remove("groups");
+
+    let cancel = CancelToken::default();
+    let promise = client.with_cancel(&cancel).get_groups();
+    promise.block_until_ready();
+
+    tasks.start(
+        Task::request(
+            "loading the groups",
+            promise,
+            |count: &mut usize, result| {
+                if let Ok(Conditional::Modified(groups)) = result {
+                    *count = groups.unwrap_or_default().len();
+                }
+            },
+        )
+        .with_key("groups")
+        .with_cancel(cancel),
+    );
+}
+
+fn apply(tasks: &mut TaskManager<usize>, count: &mut usize) {
+    for completion in tasks.poll() {
+        completion(count);
+    }
+}
+
+#[test]
+fn superseded_not_kept() {
+    let version = Arc::new(AtomicUsize::new(1));
+    let stub = versioned_groups(version.clone());
+    let client = stub.client();
+    let mut tasks = TaskManager::default();
+    let mut count = 0;
+
+    load_groups(&mut tasks, &client);
+    apply(&mut tasks, &mut count);
+    assert_eq!(count, 1);
+
+    // The new list arrives but its load is superseded.
+    version.store(2, Ordering::SeqCst);
+    load_groups(&mut tasks, &client);
+    load_groups(&mut tasks, &client);
+    apply(&mut tasks, &mut count);
+
+    assert_eq!(count, 2);
+    // The validators of the superseded load are not sent.
+    assert_eq!(stub.requests()[2].header("If-None-Match"), None);
+}
+
+#[test]
+fn cancelled_not_kept() {
+    let version = Arc::new(AtomicUsize::new(1));
+    let stub = versioned_groups(version.clone());
+    let client = stub.client();
+    let mut tasks = TaskManager::default();
+    let mut count = 0;
+
+    load_groups(&mut tasks, &client);
+    apply(&mut tasks, &mut count);
+
+    version.store(2, Ordering::SeqCst);
+    load_groups(&mut tasks, &client);
+    tasks.cancel_all();
+
+    load_groups(&mut tasks, &client);
+    apply(&mut tasks, &mut count);
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn no_validators() {
+    let stub = Stub::respond(200, GROUPS);
+    let client = stub.client();
+
+    client.get_groups().block_and_take().unwrap();
+    let second = client.get_groups().block_and_take().unwrap();
+
+    assert!(matches!(second, Conditional::Modified(Some(_))));
+    assert_eq!(conditional_headers(&stub.requests()[1]), (None, None));
+}
+
+#[test]
+fn unexpected_not_modified() {
+    let stub = Stub::respond(304, "");
+
+    // Nothing to keep without a previous response.
+    assert!(matches!(
+        stub.client().get_groups().block_and_take(),
+        Err(ApiError::Http { status: 304, .. })
+    ));
+}
+
+#[test]
+fn clear_validators() {
+    let stub = revalidating("\"v1\"", GROUPS);
+    let client = stub.client();
+
+    client.get_groups().block_and_take().unwrap();
+    client.clear_validators();
+
+    let second = client.get_groups().block_and_take().unwrap();
+    assert!(matches!(second, Conditional::Modified(Some(_))));
+    assert_eq!(conditional_headers(&stub.requests()[1]), (None, None));
+}
+
+#[test]
+fn validators_shared_by_clones() {
+    let stub = revalidating("\"v1\"", GROUPS);
+    let client = stub.client();
+
+    client.get_groups().block_and_take().unwrap();
+    let second = client.clone().get_groups().block_and_take().unwrap();
+
+    assert_eq!(second, Conditional::NotModified);
+}
+
+#[test]
+fn unparsable_response_not_kept() {
+    let stub = Stub::start(|_| StubResponse::new(200, "garbage").with_header("ETag", "\"bad\""));
+    let client = stub.client();
+
+    assert!(client.get_groups().block_and_take().is_err());
+    assert!(client.get_groups().block_and_take().is_err());
+
+    // A 304 would hide the broken list.
+    assert_eq!(stub.requests()[1].header("If-None-Match"), None);
+}
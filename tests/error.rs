@@ -0,0 +1,15 @@
+use storm_daenerys_front::error::{apierror::ApiError, apperror::AppError};
+
+#[test]
+fn debug_chain() {
+    let decode = serde_json::from_str::<u32>("oops").unwrap_err();
+    let e = AppError::Api(ApiError::Decode(decode));
+
+    // The API error once, then its source.
+    let debug = format!("{:?}", e);
+    assert_eq!(debug.matches("caused by").count(), 1, "{}", debug);
+    assert!(debug.starts_with("Api(Decode"), "{}", debug);
+
+    let debug = format!("{:?}", AppError::Api(ApiError::Cancelled));
+    assert!(!debug.contains("caused by"), "{}", debug);
+}
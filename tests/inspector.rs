@@ -0,0 +1,154 @@
+mod common;
+
+use common::{refused_client, Stub, StubResponse};
+use storm_daenerys_common::types::directory::CreateDirectory;
+use storm_daenerys_front::api::auth::credentials::{AuthConfig, Credentials};
+
+#[test]
+fn disabled_by_default() {
+    let stub = Stub::respond(200, "[]");
+    let client = stub.client();
+
+    client.get_groups().block_and_take().unwrap();
+
+    assert!(client.inspector().calls().is_empty());
+}
+
+#[test]
+fn records_calls() {
+    let stub = Stub::respond(
+        200,
+        r#"[{"cn": "storm", "description": "", "owner": null, "member": null}]"#,
+    );
+    let client = stub.client();
+    client.inspector().set_enabled(true);
+
+    client.get_groups().block_and_take().unwrap();
+    client
+        .create_directory(CreateDirectory {
+            name: "project-c".to_string(),
+        })
+        .block_and_take()
+        .ok();
+
+    // Shared by the clones.
+    let calls = client.clone().inspector().calls();
+    assert_eq!(calls.len(), 2);
+
+    assert_eq!(calls[0].method, "GET");
+    assert_eq!(calls[0].url, format!("{}/groups", stub.url));
+    assert_eq!(calls[0].status, Some(200));
+    assert!(calls[0].latency.is_some());
+    assert!(calls[0].response_body.as_ref().unwrap().contains("storm"));
+    assert_eq!(calls[0].error, None);
+
+    assert_eq!(calls[1].method, "POST");
+    assert_eq!(
+        calls[1].request_body.as_deref(),
+        Some(r#"{"name":"project-c"}"#)
+    );
+    assert!(calls[0].id < calls[1].id);
+}
+
+#[test]
+fn records_errors() {
+    let stub = Stub::respond(200, "garbage");
+    let client = stub.client();
+    client.inspector().set_enabled(true);
+    client.get_groups().block_and_take().unwrap_err();
+
+    let call = &client.inspector().calls()[0];
+    assert_eq!(call.status, Some(200));
+    assert!(call.error.as_ref().unwrap().contains("decode"));
+
+    let client = refused_client();
+    client.inspector().set_enabled(true);
+    client.get_groups().block_and_take().unwrap_err();
+
+    let call = &client.inspector().calls()[0];
+    assert_eq!(call.status, None);
+    assert!(call.error.as_ref().unwrap().contains("network"));
+}
+
+#[test]
+fn secrets_redacted() {
+    let stub = Stub::start(|_| {
+        StubResponse::new(
+            200,
+            r#"{"access_token": "s3cret", "nested": [{"password": "hunter2", "name": "jdoe"}]}"#,
+        )
+    });
+    // The query values are encoded, the secret comes with the API URL.
+    let client = stub
+        .client()
+        .with_api_url(&format!("{}/?token=abc&", stub.url))
+        .with_credentials(Credentials::new(AuthConfig::Bearer("s3cret".to_string())));
+    client.inspector().set_enabled(true);
+
+    client
+        .get_user_display("jdoe".to_string())
+        .block_and_take()
+        .ok();
+
+    let call = &client.inspector().calls()[0];
+    let everything = format!("{:?}", call);
+    assert!(!everything.contains("s3cret"), "{}", everything);
+    assert!(!everything.contains("hunter2"), "{}", everything);
+    assert!(!everything.contains("abc"), "{}", everything);
+
+    assert!(call
+        .request_headers
+        .contains(&("Authorization".to_string(), "Bearer <redacted>".to_string())));
+    assert!(call.url.contains("?token=<redacted>&"));
+    assert!(call.response_body.as_ref().unwrap().contains("jdoe"));
+}
+
+#[test]
+fn capacity() {
+    let stub = Stub::respond(200, "[]");
+    let client = stub.client();
+    client.inspector().set_enabled(true);
+
+    for _ in 0..250 {
+        client.get_groups().block_and_take().unwrap();
+    }
+
+    let calls = client.inspector().calls();
+    assert_eq!(calls.len(), 200);
+    assert_eq!(calls[0].id, 50);
+
+    client.inspector().clear();
+    assert!(client.inspector().calls().is_empty());
+}
+
+#[test]
+fn curl_command() {
+    let stub = Stub::respond(200, "");
+    let client = stub
+        .client()
+        .with_credentials(Credentials::new(AuthConfig::Bearer("s3cret".to_string())));
+    client.inspector().set_enabled(true);
+
+    client
+        .create_directory(CreateDirectory {
+            name: "it's".to_string(),
+        })
+        .block_and_take()
+        .unwrap();
+
+    let curl = client.inspector().calls()[0].curl();
+
+    assert!(
+        curl.starts_with(&format!("curl -X POST '{}/directories'", stub.url)),
+        "{}",
+        curl
+    );
+    assert!(curl.contains(" -H 'Content-Type: application/json'"));
+    assert!(curl.contains(" -H \"Authorization: Bearer $STORM_DAENERYS_AUTH_TOKEN\""));
+    assert!(
+        curl.ends_with(r#" --data-raw '{"name":"it'\''s"}'"#),
+        "{}",
+        curl
+    );
+    assert!(!curl.contains("s3cret"));
+}
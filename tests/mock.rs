@@ -0,0 +1,250 @@
+#![cfg(feature = "mock")]
+
+use storm_daenerys_common::types::{
+    acl::{AclEntry, Qualifier, SetAcl},
+    directory::{CreateDirectory, RenameDirectory},
+    group::{AddDelUserToGroup, Group},
+};
+use storm_daenerys_front::{
+    api::{
+        acljob::{AclJobState, RecursiveAcl},
+        client::ApiClient,
+        conditional::Conditional,
+        retry::RetryPolicy,
+    },
+    error::apierror::ApiError,
+    mock::{fixture::Fixture, server::MockServer},
+};
+
+fn start(fixture: Fixture) -> (MockServer, ApiClient) {
+    let server = MockServer::start("127.0.0.1:0", fixture).unwrap();
+    let client =
+        ApiClient::new(&egui::Context::default(), server.url()).with_retry(RetryPolicy::none());
+    (server, client)
+}
+
+fn group_acl(cn: &str, gid: u32) -> AclEntry {
+    AclEntry {
+        qualifier: Qualifier::Group(gid),
+        qualifier_cn: Some(cn.to_string()),
+        qualifier_display: None,
+        perm: 5,
+    }
+}
+
+fn root_names(client: &ApiClient) -> Vec<String> {
+    match client.get_root_directories().block_and_take().unwrap() {
+        Conditional::Modified(directories) => directories
+            .unwrap_or_default()
+            .into_iter()
+            .map(|directory| directory.name)
+            .collect(),
+        Conditional::NotModified => panic!("expected the directories"),
+    }
+}
+
+#[test]
+fn directories() {
+    let (_server, client) = start(Fixture::default());
+    assert_eq!(root_names(&client), vec!["project-a", "project-b"]);
+
+    let subdirectories = client
+        .get_subdirectories("project-a/data")
+        .block_and_take()
+        .unwrap();
+    let names: Vec<_> = subdirectories.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, vec!["project-a/data/raw", "project-a/data/shared"]);
+
+    let create = |name: &str| CreateDirectory {
+        name: name.to_string(),
+    };
+    client
+        .create_directory(create("project-c"))
+        .block_and_take()
+        .unwrap();
+    assert!(matches!(
+        client
+            .create_directory(create("project-c"))
+            .block_and_take(),
+        Err(ApiError::Http { status: 409, .. })
+    ));
+
+    client
+        .rename_directory(RenameDirectory {
+            name: "project-c".to_string(),
+            new_name: "project-d".to_string(),
+        })
+        .block_and_take()
+        .unwrap();
+    assert!(root_names(&client).contains(&"project-d".to_string()));
+
+    client
+        .delete_directory(create("project-d"))
+        .block_and_take()
+        .unwrap();
+    assert_eq!(root_names(&client), vec!["project-a", "project-b"]);
+}
+
+#[test]
+fn directories_not_modified() {
+    let (_server, client) = start(Fixture::default());
+
+    root_names(&client);
+    let second = client.get_root_directories().block_and_take().unwrap();
+    assert_eq!(second, Conditional::NotModified);
+}
+
+#[test]
+fn groups() {
+    let (_server, client) = start(Fixture::default());
+
+    // Prefixed by the backend.
+    client
+        .create_group(Group {
+            cn: "new".to_string(),
+            description: "new".to_string(),
+            owner: None,
+            member: None,
+        })
+        .block_and_take()
+        .unwrap();
+    client
+        .add_user_to_group(AddDelUserToGroup {
+            group_cn: "storm-new".to_string(),
+            user_cn: "jdoe".to_string(),
+        })
+        .block_and_take()
+        .unwrap();
+
+    let groups = client.get_groups().block_and_take().unwrap();
+    let groups = groups.modified().unwrap().unwrap();
+    let created = groups.iter().find(|group| group.cn == "storm-new").unwrap();
+    assert_eq!(created.member, Some(vec!["jdoe".to_string()]));
+
+    assert_eq!(
+        client.get_group_gid("storm").block_and_take().unwrap(),
+        2000
+    );
+    assert!(matches!(
+        client.get_group_gid("storm-new").block_and_take(),
+        Err(ApiError::Http { status: 404, .. })
+    ));
+
+    client
+        .delete_group("storm-new".to_string())
+        .block_and_take()
+        .unwrap();
+    let groups = client.get_groups().block_and_take().unwrap();
+    assert!(!groups
+        .modified()
+        .unwrap()
+        .unwrap()
+        .iter()
+        .any(|group| group.cn == "storm-new"));
+}
+
+#[test]
+fn acls() {
+    let (_server, client) = start(Fixture::default());
+    let set_acl = |name: &str| SetAcl {
+        name: name.to_string(),
+        acls: vec![group_acl("storm-invite", 2001)],
+    };
+
+    client
+        .save_acl(set_acl("project-b"))
+        .block_and_take()
+        .unwrap();
+    let directories = client.get_root_directories().block_and_take().unwrap();
+    let directories = directories.modified().unwrap().unwrap();
+    let project_b = directories.iter().find(|d| d.name == "project-b").unwrap();
+    assert_eq!(project_b.acls, vec![group_acl("storm-invite", 2001)]);
+
+    assert!(client
+        .get_default_acl("project-b")
+        .block_and_take()
+        .unwrap()
+        .is_empty());
+    client
+        .save_default_acl(set_acl("project-b"))
+        .block_and_take()
+        .unwrap();
+    assert_eq!(
+        client
+            .get_default_acl("project-b")
+            .block_and_take()
+            .unwrap(),
+        vec![group_acl("storm-invite", 2001)]
+    );
+
+    assert!(matches!(
+        client.save_acl(set_acl("project-z")).block_and_take(),
+        Err(ApiError::Http { status: 404, .. })
+    ));
+}
+
+#[test]
+fn acl_job() {
+    let mut fixture = Fixture::default();
+    // On the directory itself too.
+    fixture.acl_failures.push("project-a".to_string());
+    fixture
+        .acl_failures
+        .push("project-b/locked.dat".to_string());
+    let (_server, client) = start(fixture);
+
+    let job = client
+        .start_recursive_acl(RecursiveAcl {
+            name: "project-a".to_string(),
+            acls: vec![group_acl("storm", 2000)],
+            default_acls: None,
+        })
+        .block_and_take()
+        .unwrap();
+
+    let status = loop {
+        let status = client.get_acl_job(&job.id).block_and_take().unwrap();
+        if !status.is_running() {
+            break status;
+        }
+    };
+
+    assert_eq!(status.state, AclJobState::Done);
+    assert_eq!(status.processed, 6);
+    assert_eq!(status.total, Some(6));
+    let mut failed: Vec<_> = status.failures.iter().map(|f| f.path.as_str()).collect();
+    failed.sort();
+    assert_eq!(failed, vec!["project-a", "project-a/data/raw/locked.dat"]);
+
+    let subdirectories = client
+        .get_subdirectories("project-a")
+        .block_and_take()
+        .unwrap();
+    assert_eq!(subdirectories[0].acls, vec![group_acl("storm", 2000)]);
+}
+
+#[test]
+fn acl_job_cancelled() {
+    let (_server, client) = start(Fixture::default());
+
+    let job = client
+        .start_recursive_acl(RecursiveAcl {
+            name: "project-a".to_string(),
+            acls: vec![group_acl("storm", 2000)],
+            default_acls: None,
+        })
+        .block_and_take()
+        .unwrap();
+
+    client.get_acl_job(&job.id).block_and_take().unwrap();
+    client.cancel_acl_job(&job.id).block_and_take().unwrap();
+
+    let status = client.get_acl_job(&job.id).block_and_take().unwrap();
+    assert_eq!(status.state, AclJobState::Cancelled);
+    assert_eq!(status.processed, 1);
+
+    assert!(matches!(
+        client.get_acl_job("404").block_and_take(),
+        Err(ApiError::Http { status: 404, .. })
+    ));
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{assert_network_error, refused_client, Stub, StubResponse};
+use storm_daenerys_common::types::directory::CreateDirectory;
+use storm_daenerys_front::api::{
+    client::ApiClient, conditional::Conditional, retry::RetryPolicy, transport::Transport,
+};
+
+const DIRECTORIES: &str = r#"[{"name": "project-a", "acls": [], "valid": true, "quota": null}]"#;
+
+/// Retries without waiting long.
+fn fast(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(40),
+    }
+}
+
+/// A stub answering 503 to the first `failures` requests, then `body`.
+fn flaky(failures: usize, body: &'static str) -> Stub {
+    let count = AtomicUsize::new(0);
+
+    Stub::start(move |_| {
+        if count.fetch_add(1, Ordering::SeqCst) < failures {
+            StubResponse::new(503, "Service Unavailable")
+        } else {
+            StubResponse::new(200, body)
+        }
+    })
+}
+
+#[test]
+fn get_retried_until_success() {
+    let stub = flaky(2, DIRECTORIES);
+
+    let directories = stub
+        .client()
+        .with_retry(fast(3))
+        .get_root_directories()
+        .block_and_take()
+        .unwrap()
+        .modified()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(directories[0].name, "project-a");
+    assert_eq!(stub.requests().len(), 3);
+}
+
+#[test]
+fn get_max_retries() {
+    let stub = flaky(usize::MAX, DIRECTORIES);
+
+    let result = stub
+        .client()
+        .with_retry(fast(2))
+        .get_root_directories()
+        .block_and_take();
+
+    assert!(!matches!(result, Ok(Conditional::Modified(_))));
+    // First attempt and two retries.
+    assert_eq!(stub.requests().len(), 3);
+}
+
+#[test]
+fn get_not_retried_on_other_errors() {
+    let stub = Stub::respond(500, "oops");
+
+    let result = stub
+        .client()
+        .with_retry(fast(3))
+        .get_root_directories()
+        .block_and_take();
+
+    assert!(result.is_err());
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[test]
+fn post_never_retried() {
+    let stub = flaky(1, "");
+
+    let result = stub
+        .client()
+        .with_retry(fast(3))
+        .create_directory(CreateDirectory {
+            name: "project-c".to_string(),
+        })
+        .block_and_take();
+
+    assert!(result.is_err());
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[test]
+fn network_error_retried() {
+    let start = Instant::now();
+
+    let result = refused_client()
+        .with_retry(fast(2))
+        .get_root_directories()
+        .block_and_take();
+
+    assert_network_error(result);
+    // Two delays of at least half the base one.
+    assert!(start.elapsed() >= Duration::from_millis(10));
+}
+
+#[test]
+fn retrying_counter() {
+    let stub = flaky(1, DIRECTORIES);
+    let client = stub.client().with_retry(RetryPolicy {
+        max_retries: 1,
+        base_delay: Duration::from_millis(400),
+        max_delay: Duration::from_millis(400),
+    });
+
+    assert_eq!(client.retrying(), 0);
+    let promise = client.get_root_directories();
+
+    // Waiting between 200 and 400 ms before the retry.
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(client.retrying(), 1);
+
+    assert!(promise.block_and_take().is_ok());
+    assert_eq!(client.retrying(), 0);
+}
+
+#[test]
+fn timeout() {
+    let stub = Stub::start(|_| {
+        thread::sleep(Duration::from_millis(500));
+        StubResponse::new(200, DIRECTORIES)
+    });
+
+    let client = ApiClient::new(&Default::default(), stub.url.clone())
+        .with_transport(Transport::default().with_timeout(Duration::from_millis(100)))
+        .with_retry(RetryPolicy::none());
+
+    let start = Instant::now();
+    match client.get_root_directories().block_and_take() {
+        Err(e) => assert!(e.to_string().contains("timed out"), "{}", e),
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
+
+#[test]
+fn du_timeout() {
+    let stub = Stub::start(|_| {
+        thread::sleep(Duration::from_millis(300));
+        StubResponse::new(200, "1.2T\t/storm\n")
+    });
+
+    // The disk usage gets more time than the other requests.
+    let client = ApiClient::new(&Default::default(), stub.url.clone())
+        .with_transport(Transport::default().with_timeout(Duration::from_millis(100)))
+        .with_du_timeout(Duration::from_secs(5))
+        .with_retry(RetryPolicy::none());
+
+    assert_eq!(
+        client.get_du().block_and_take().unwrap(),
+        Some("1.2T\t/storm\n".to_string())
+    );
+}
+
+#[test]
+fn delay_bounds() {
+    let policy = RetryPolicy::default();
+
+    for retry in 0..6 {
+        let full = (Duration::from_millis(500) * 2u32.pow(retry)).min(Duration::from_secs(8));
+        let delay = policy.delay(retry);
+
+        assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+    }
+
+    assert_eq!(RetryPolicy::none().max_retries, 0);
+}
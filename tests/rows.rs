@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use storm_daenerys_common::types::group::Group;
+use storm_daenerys_front::{
+    ui::{
+        daenerys::DaenerysApp,
+        rows::{data_key, RowCache},
+    },
+    worker::{displays::UserDisplays, message::UserDisplayUpdate},
+};
+
+#[test]
+fn rows_built_once_per_key() {
+    let mut cache = RowCache::default();
+    let mut builds = 0;
+
+    let members = vec!["jdoe".to_string(), "asmith".to_string()];
+    let mut sorted = |members: &Vec<String>| {
+        builds += 1;
+        let mut rows = members.clone();
+        rows.sort();
+        rows
+    };
+
+    let rows = cache.get(data_key(&members), || sorted(&members)).to_vec();
+    assert_eq!(rows, ["asmith", "jdoe"]);
+    cache.get(data_key(&members), || sorted(&members));
+
+    // Built again once the data changed.
+    let members = vec!["jdoe".to_string()];
+    let rows = cache.get(data_key(&members), || sorted(&members)).to_vec();
+    assert_eq!(rows, ["jdoe"]);
+    assert_eq!(cache.rows(), ["jdoe"]);
+
+    assert_eq!(builds, 2);
+}
+
+#[test]
+fn user_display_generation() {
+    let mut user_displays = UserDisplays::default();
+    let generation = user_displays.generation();
+
+    // Nothing resolved.
+    user_displays.apply(UserDisplayUpdate {
+        idle: true,
+        ..Default::default()
+    });
+    assert_eq!(user_displays.generation(), generation);
+
+    user_displays.apply(UserDisplayUpdate {
+        resolved: HashMap::from([("jdoe".to_string(), Some("John Doe".to_string()))]),
+        len: 1,
+        idle: true,
+        ..Default::default()
+    });
+    let resolved = user_displays.generation();
+    assert_ne!(resolved, generation);
+
+    user_displays.clear();
+    assert_ne!(user_displays.generation(), resolved);
+}
+
+#[test]
+fn member_rows_built_again_once_changed() {
+    let mut app = DaenerysApp::default();
+    app.current_group = Some(Box::new(Group {
+        cn: "storm".to_string(),
+        description: String::new(),
+        owner: None,
+        member: Some(vec!["jdoe".to_string(), "asmith".to_string()]),
+    }));
+    app.members_changed();
+
+    let uids = |app: &mut DaenerysApp| -> Vec<String> {
+        app.member_rows()
+            .iter()
+            .map(|(_, uid)| uid.clone())
+            .collect()
+    };
+    assert_eq!(uids(&mut app), ["asmith", "jdoe"]);
+
+    // Kept until the members are marked as changed.
+    let group = app.current_group.as_mut().unwrap();
+    group.member.as_mut().unwrap().push("bwayne".to_string());
+    assert_eq!(uids(&mut app), ["asmith", "jdoe"]);
+
+    app.members_changed();
+    assert_eq!(uids(&mut app), ["asmith", "bwayne", "jdoe"]);
+}
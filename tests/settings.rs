@@ -0,0 +1,451 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use log::LevelFilter;
+use storm_daenerys_front::{
+    acltemplate::{AclTemplate, TemplateKind},
+    api::auth::credentials::AuthConfig,
+    error::settingserror::SettingsError,
+    settings::{Cli, Settings},
+};
+
+/// Write `content` in a configuration file unique to the test.
+fn config_file(test: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "storm-daenerys-{}-{}.toml",
+        std::process::id(),
+        test
+    ));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn load(args: &[&str]) -> Result<Settings, SettingsError> {
+    let cli = Cli::try_parse_from(["storm_daenerys_front"].iter().chain(args)).unwrap();
+    Settings::from_cli(cli)
+}
+
+#[test]
+fn defaults_with_empty_file() {
+    let path = config_file("empty", "");
+
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+
+    assert_eq!(settings.api_url, "http://localhost:3000");
+    assert_eq!(settings.window_size, [1024.0, 768.0]);
+    assert_eq!(settings.log_level, None);
+    assert!(!settings.read_only);
+    assert_eq!(settings.auth, AuthConfig::None);
+    assert!(settings.tls.is_default());
+    assert_eq!(settings.config_file, Some(path));
+}
+
+#[test]
+fn file_values() {
+    let path = config_file(
+        "file",
+        r#"
+api_url = "https://storm.example.org/api"
+window_width = 1280
+window_height = 1024
+log_level = "debug"
+read_only = true
+"#,
+    );
+
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+
+    assert_eq!(settings.api_url, "https://storm.example.org/api");
+    assert_eq!(settings.window_size, [1280.0, 1024.0]);
+    assert_eq!(settings.log_level, Some(LevelFilter::Debug));
+    assert!(settings.read_only);
+}
+
+#[test]
+fn cli_overrides_file() {
+    let path = config_file(
+        "override",
+        r#"
+api_url = "https://storm.example.org/api"
+window_width = 1280
+log_level = "debug"
+read_only = true
+"#,
+    );
+
+    let settings = load(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--api-url",
+        "http://127.0.0.1:3001",
+        "--log-level",
+        "warn",
+        "--read-only=false",
+    ])
+    .unwrap();
+
+    assert_eq!(settings.api_url, "http://127.0.0.1:3001");
+    assert_eq!(settings.window_size, [1280.0, 768.0]);
+    assert_eq!(settings.log_level, Some(LevelFilter::Warn));
+    assert!(!settings.read_only);
+}
+
+#[test]
+fn read_only_flag() {
+    let path = config_file("read-only", "");
+
+    let settings = load(&["--config", path.to_str().unwrap(), "--read-only"]).unwrap();
+
+    assert!(settings.read_only);
+}
+
+#[test]
+fn invalid_cli_values() {
+    let invalid: [&[&str]; 3] = [
+        &["--log-level", "loud"],
+        &["--window-width", "wide"],
+        &["--read-only=maybe"],
+    ];
+
+    for args in invalid {
+        assert!(
+            Cli::try_parse_from(["storm_daenerys_front"].iter().chain(args)).is_err(),
+            "{:?} should be rejected",
+            args
+        );
+    }
+}
+
+#[test]
+fn invalid_api_url() {
+    let path = config_file("url", "");
+
+    for api_url in ["localhost:3000", "ftp://storm", "http://", "https:///api"] {
+        match load(&["--config", path.to_str().unwrap(), "--api-url", api_url]) {
+            Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "api_url"),
+            other => panic!("{} should be rejected, got {:?}", api_url, other),
+        }
+    }
+}
+
+#[test]
+fn invalid_window_size() {
+    let path = config_file("window", "window_height = 10");
+
+    match load(&["--config", path.to_str().unwrap()]) {
+        Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "window_height"),
+        other => panic!("expected an invalid window height, got {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_file() {
+    let path = config_file("log-level", r#"log_level = "loud""#);
+    match load(&["--config", path.to_str().unwrap()]) {
+        Err(e @ SettingsError::Invalid { .. }) => {
+            assert_eq!(
+                e.to_string(),
+                "invalid log_level \"loud\": expected off, error, warn, info, debug or trace"
+            )
+        }
+        other => panic!("expected an invalid log level, got {:?}", other),
+    }
+
+    let path = config_file("unknown-key", r#"api_ulr = "http://localhost""#);
+    assert!(matches!(
+        load(&["--config", path.to_str().unwrap()]),
+        Err(SettingsError::Parse { .. })
+    ));
+
+    let path = config_file("syntax", "api_url = ");
+    assert!(matches!(
+        load(&["--config", path.to_str().unwrap()]),
+        Err(SettingsError::Parse { .. })
+    ));
+}
+
+#[test]
+fn acl_templates() {
+    // The project template without configuration.
+    let path = config_file("templates-default", "");
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+    assert_eq!(settings.acl_templates, vec![AclTemplate::project()]);
+
+    let path = config_file(
+        "templates",
+        r#"
+[[acl_templates]]
+name = "shared"
+entries = [
+    { kind = "group", name = "{prefix}-{name}", perm = "rwx" },
+    { kind = "user", name = "jdoe", perm = "r-x" },
+]
+"#,
+    );
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+    assert_eq!(settings.acl_templates.len(), 1);
+    assert_eq!(settings.acl_templates[0].name, "shared");
+    assert_eq!(
+        settings.acl_templates[0].entries[1].kind,
+        TemplateKind::User
+    );
+}
+
+#[test]
+fn invalid_acl_templates() {
+    for (test, content, reason) in [
+        (
+            "templates-perm",
+            r#"acl_templates = [{ name = "t", entries = [{ kind = "user", name = "jdoe", perm = "rw" }] }]"#,
+            "expected rights such as rwx or r-x",
+        ),
+        (
+            "templates-placeholder",
+            r#"acl_templates = [{ name = "t", entries = [{ kind = "group", name = "{project}", perm = "rwx" }] }]"#,
+            "unknown placeholder, expected {prefix}, {name} or {admin}",
+        ),
+        (
+            "templates-empty",
+            r#"acl_templates = [{ name = "t", entries = [] }]"#,
+            "a template needs at least one entry",
+        ),
+        (
+            "templates-duplicate",
+            r#"acl_templates = [
+                { name = "t", entries = [{ kind = "user", name = "jdoe", perm = "rwx" }] },
+                { name = "t", entries = [{ kind = "user", name = "asmith", perm = "rwx" }] },
+            ]"#,
+            "duplicate template name",
+        ),
+    ] {
+        let path = config_file(test, content);
+        match load(&["--config", path.to_str().unwrap()]) {
+            Err(SettingsError::Invalid {
+                name,
+                value,
+                reason: e,
+            }) => {
+                assert_eq!(name, "acl_templates");
+                assert_eq!(value, "t");
+                assert_eq!(e, reason);
+            }
+            other => panic!("expected an invalid template, got {:?}", other),
+        }
+    }
+
+    let path = config_file(
+        "templates-kind",
+        r#"acl_templates = [{ name = "t", entries = [{ kind = "other", name = "x", perm = "rwx" }] }]"#,
+    );
+    assert!(matches!(
+        load(&["--config", path.to_str().unwrap()]),
+        Err(SettingsError::Parse { .. })
+    ));
+}
+
+#[test]
+fn missing_explicit_file() {
+    let path = std::env::temp_dir().join("storm-daenerys-does-not-exist.toml");
+
+    assert!(matches!(
+        load(&["--config", path.to_str().unwrap()]),
+        Err(SettingsError::Read { .. })
+    ));
+}
+
+#[test]
+fn bearer_auth() {
+    let path = config_file("bearer", r#"auth_token = "from-file""#);
+
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+    assert_eq!(settings.auth, AuthConfig::Bearer("from-file".to_string()));
+    assert!(!format!("{:?}", settings).contains("from-file"));
+
+    let settings = load(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--auth-token",
+        "from-cli",
+    ])
+    .unwrap();
+    assert_eq!(settings.auth, AuthConfig::Bearer("from-cli".to_string()));
+}
+
+#[test]
+fn oidc_auth() {
+    let path = config_file(
+        "oidc",
+        r#"
+oidc_issuer = "https://idp.example.org/realms/uca"
+oidc_client_id = "storm-daenerys"
+"#,
+    );
+
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+
+    match settings.auth {
+        AuthConfig::Oidc(oidc) => {
+            assert_eq!(oidc.issuer, "https://idp.example.org/realms/uca");
+            assert_eq!(oidc.client_id, "storm-daenerys");
+            assert_eq!(oidc.scope, "openid offline_access");
+        }
+        other => panic!("expected an OIDC configuration, got {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_auth() {
+    let path = config_file("auth-conflict", r#"auth_token = "s3cret""#);
+    match load(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--oidc-issuer",
+        "https://idp.example.org",
+        "--oidc-client-id",
+        "storm-daenerys",
+    ]) {
+        Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "oidc_issuer"),
+        other => panic!("expected conflicting auth settings, got {:?}", other),
+    }
+
+    let path = config_file(
+        "auth-client-id",
+        r#"oidc_issuer = "https://idp.example.org""#,
+    );
+    match load(&["--config", path.to_str().unwrap()]) {
+        Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "oidc_client_id"),
+        other => panic!("expected a missing client id, got {:?}", other),
+    }
+
+    let path = config_file("auth-empty", r#"auth_token = " ""#);
+    match load(&["--config", path.to_str().unwrap()]) {
+        Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "auth_token"),
+        other => panic!("expected an empty token error, got {:?}", other),
+    }
+}
+
+#[test]
+fn tls_settings() {
+    let path = config_file(
+        "tls",
+        r#"
+tls_ca_bundle = "certs/ca.pem"
+tls_client_cert = "/etc/storm/client.pem"
+tls_client_key = "/etc/storm/client.key"
+"#,
+    );
+
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+
+    // Relative paths of the file are resolved against its directory.
+    assert_eq!(
+        settings.tls.ca_bundle,
+        Some(path.parent().unwrap().join("certs/ca.pem"))
+    );
+    assert_eq!(
+        settings.tls.client_cert,
+        Some(PathBuf::from("/etc/storm/client.pem"))
+    );
+    assert_eq!(
+        settings.tls.client_key,
+        Some(PathBuf::from("/etc/storm/client.key"))
+    );
+    assert!(!settings.tls.insecure);
+
+    let settings = load(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--tls-ca-bundle",
+        "ca.pem",
+        "--tls-insecure",
+    ])
+    .unwrap();
+
+    assert_eq!(settings.tls.ca_bundle, Some(PathBuf::from("ca.pem")));
+    assert!(settings.tls.insecure);
+}
+
+#[test]
+fn invalid_tls() {
+    let path = config_file("tls-cert", r#"tls_client_cert = "client.pem""#);
+    match load(&["--config", path.to_str().unwrap()]) {
+        Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "tls_client_key"),
+        other => panic!("expected a missing client key, got {:?}", other),
+    }
+
+    let path = config_file("tls-key", r#"tls_client_key = "client.key""#);
+    match load(&["--config", path.to_str().unwrap()]) {
+        Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "tls_client_cert"),
+        other => panic!("expected a missing client certificate, got {:?}", other),
+    }
+}
+
+#[test]
+fn cache_settings() {
+    let path = config_file(
+        "cache",
+        r#"
+cache_dir = "cache"
+user_display_ttl = 86400
+"#,
+    );
+
+    let settings = load(&["--config", path.to_str().unwrap()]).unwrap();
+    assert_eq!(
+        settings.cache_dir,
+        Some(path.parent().unwrap().join("cache"))
+    );
+    assert_eq!(settings.user_display_ttl, Duration::from_secs(86400));
+    assert_eq!(
+        settings.user_display_negative_ttl,
+        Duration::from_secs(3600)
+    );
+
+    let settings = load(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--user-display-negative-ttl",
+        "0",
+    ])
+    .unwrap();
+    assert_eq!(settings.user_display_negative_ttl, Duration::ZERO);
+}
+
+#[test]
+fn timeout_settings() {
+    let path = config_file(
+        "timeouts",
+        r#"
+request_timeout = 10
+max_retries = 0
+"#,
+    );
+    let config = path.to_str().unwrap();
+
+    let settings = load(&["--config", config]).unwrap();
+    assert_eq!(settings.request_timeout, Duration::from_secs(10));
+    assert_eq!(settings.du_timeout, Duration::from_secs(300));
+    assert_eq!(settings.max_retries, 0);
+    assert_eq!(settings.refresh_interval, Duration::from_secs(300));
+
+    // The command line wins.
+    let settings = load(&[
+        "--config",
+        config,
+        "--request-timeout",
+        "20",
+        "--max-retries",
+        "5",
+        "--refresh-interval",
+        "0",
+    ])
+    .unwrap();
+    assert_eq!(settings.request_timeout, Duration::from_secs(20));
+    assert_eq!(settings.max_retries, 5);
+    assert_eq!(settings.refresh_interval, Duration::ZERO);
+
+    match load(&["--config", config, "--du-timeout", "0"]) {
+        Err(SettingsError::Invalid { name, .. }) => assert_eq!(name, "du_timeout"),
+        other => panic!("expected an invalid setting, got {:?}", other),
+    }
+}
@@ -0,0 +1,129 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use storm_daenerys_common::types::config::Config;
+use storm_daenerys_front::{
+    error::apierror::ApiError,
+    snapshot::{self, Snapshot},
+};
+
+const API_URL: &str = "http://storm.example.org";
+
+const CONFIG: &str = r#"{
+    "admin": "jdoe",
+    "connected_user": "jdoe",
+    "current_admin_restriction": null,
+    "users_dsi_api_group_prefix": "storm",
+    "root_groups": null,
+    "quota": {"total_space": 1024, "available_space": 512}
+}"#;
+
+const DIRECTORIES: &str = r#"[{"name": "project-a", "acls": [], "valid": true, "quota": null}]"#;
+const GROUPS: &str = r#"[{"cn": "storm", "description": "", "owner": null, "member": ["jdoe"]}]"#;
+
+/// Snapshot file unique to the test, removed first.
+fn snapshot_file(test: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("storm-daenerys-{}-{}", std::process::id(), test))
+        .join(snapshot::FILE_NAME);
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn snapshot(api_url: &str) -> Snapshot {
+    let config: Config = serde_json::from_str(CONFIG).unwrap();
+
+    Snapshot::new(
+        api_url,
+        config,
+        Some(serde_json::from_str(DIRECTORIES).unwrap()),
+        Some(serde_json::from_str(GROUPS).unwrap()),
+        HashMap::from([
+            ("jdoe".to_string(), Some("John Doe".to_string())),
+            ("ghost".to_string(), None),
+        ]),
+    )
+}
+
+#[test]
+fn save_and_load() {
+    let path = snapshot_file("save_and_load");
+    snapshot(API_URL).save(&path).unwrap();
+
+    let loaded = Snapshot::load(&path, API_URL).unwrap().unwrap();
+
+    assert_eq!(loaded.config.admin, "jdoe");
+    assert_eq!(loaded.directories.unwrap()[0].name, "project-a");
+    assert_eq!(loaded.groups.unwrap()[0].cn, "storm");
+    assert_eq!(loaded.user_displays["jdoe"], Some("John Doe".to_string()));
+    assert_eq!(loaded.user_displays["ghost"], None);
+    assert!(loaded.saved_at > 0);
+    assert!(!path.with_extension("tmp").exists());
+}
+
+#[test]
+fn replace() {
+    let path = snapshot_file("replace");
+    snapshot(API_URL).save(&path).unwrap();
+
+    let mut newer = snapshot(API_URL);
+    newer.groups = None;
+    newer.save(&path).unwrap();
+
+    assert!(Snapshot::load(&path, API_URL)
+        .unwrap()
+        .unwrap()
+        .groups
+        .is_none());
+}
+
+#[test]
+fn missing_file() {
+    let path = snapshot_file("missing_file");
+
+    assert!(Snapshot::load(&path, API_URL).unwrap().is_none());
+}
+
+#[test]
+fn other_backend() {
+    let path = snapshot_file("other_backend");
+    snapshot("http://other.example.org").save(&path).unwrap();
+
+    assert!(Snapshot::load(&path, API_URL).unwrap().is_none());
+}
+
+#[test]
+fn corrupt_file() {
+    let path = snapshot_file("corrupt_file");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "{not json").unwrap();
+
+    assert!(Snapshot::load(&path, API_URL).is_err());
+}
+
+#[test]
+fn saved_at_label() {
+    let mut snapshot = snapshot(API_URL);
+
+    // Local time, the date depends on the time zone.
+    snapshot.saved_at = 1_700_000_000;
+    let label = snapshot.saved_at_label();
+    assert!(label.starts_with("2023-11-1"), "{}", label);
+    assert_eq!(label.len(), "2023-11-14 22:13".len());
+}
+
+#[test]
+fn unreachable_errors() {
+    let http = |status| ApiError::Http {
+        status,
+        status_text: String::new(),
+        error: None,
+        body: None,
+    };
+
+    assert!(ApiError::Network("connection refused".to_string()).is_unreachable());
+    assert!(http(502).is_unreachable());
+    assert!(http(503).is_unreachable());
+    assert!(http(504).is_unreachable());
+    assert!(!http(500).is_unreachable());
+    assert!(!http(401).is_unreachable());
+}
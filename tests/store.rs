@@ -0,0 +1,189 @@
+use storm_daenerys_common::types::{
+    acl::{AclEntry, Qualifier},
+    directory::Directory,
+    group::Group,
+    user::User,
+};
+use storm_daenerys_front::store::{AclQualifier, EntityKind, Store};
+
+fn acl(qualifier: Qualifier, cn: &str) -> AclEntry {
+    AclEntry {
+        qualifier,
+        qualifier_cn: Some(cn.to_string()),
+        qualifier_display: None,
+        perm: 7,
+    }
+}
+
+fn directory(name: &str, acls: Vec<AclEntry>) -> Directory {
+    Directory {
+        name: name.to_string(),
+        acls,
+        valid: true,
+        quota: None,
+    }
+}
+
+fn group(cn: &str) -> Group {
+    Group {
+        cn: cn.to_string(),
+        description: String::new(),
+        owner: None,
+        member: None,
+    }
+}
+
+fn store() -> Store {
+    let mut store = Store::default();
+
+    store.set_directories(Some(vec![
+        directory(
+            "project-a",
+            vec![
+                acl(Qualifier::User(1000), "jdoe"),
+                acl(Qualifier::Group(2000), "storm"),
+            ],
+        ),
+        directory("project-b", vec![acl(Qualifier::Group(2000), "storm")]),
+        directory("project-c", vec![acl(Qualifier::Mask, "mask")]),
+    ]));
+    store.set_groups(Some(vec![group("storm"), group("storm-invite")]));
+
+    store
+}
+
+fn names<'a>(directories: impl Iterator<Item = &'a Directory>) -> Vec<&'a str> {
+    directories
+        .map(|directory| directory.name.as_str())
+        .collect()
+}
+
+#[test]
+fn lookups() {
+    let store = store();
+
+    assert_eq!(store.directory("project-b").unwrap().name, "project-b");
+    assert!(store.directory("project-z").is_none());
+    assert_eq!(store.group("storm-invite").unwrap().cn, "storm-invite");
+    assert!(store.group("other").is_none());
+}
+
+#[test]
+fn directories_by_qualifier() {
+    let store = store();
+
+    assert_eq!(
+        names(store.directories_with(&AclQualifier::Group("storm".to_string()))),
+        vec!["project-a", "project-b"]
+    );
+    assert_eq!(
+        names(store.directories_with(&AclQualifier::User("jdoe".to_string()))),
+        vec!["project-a"]
+    );
+    // A user and a group of the same name differ.
+    assert!(store
+        .directories_with(&AclQualifier::User("storm".to_string()))
+        .next()
+        .is_none());
+    // Only the user and group entries are indexed.
+    assert_eq!(store.acl_qualifiers().count(), 2);
+}
+
+#[test]
+fn indices_follow_the_lists() {
+    let mut store = store();
+
+    let mut directories = store.take_directories().unwrap();
+    assert!(store.directory("project-a").is_none());
+
+    directories.remove(0);
+    store.set_directories(Some(directories));
+
+    assert!(store.directory("project-a").is_none());
+    assert_eq!(
+        names(store.directories_with(&AclQualifier::Group("storm".to_string()))),
+        vec!["project-b"]
+    );
+    assert!(store
+        .directories_with(&AclQualifier::User("jdoe".to_string()))
+        .next()
+        .is_none());
+}
+
+#[test]
+fn group_gids() {
+    let mut store = store();
+
+    // Known from the ACLs.
+    assert_eq!(store.group_gid("storm"), Some(2000));
+    assert_eq!(store.group_gid("storm-invite"), None);
+
+    store.set_group_gid("storm-invite", 2001);
+    store.set_directories(None);
+    assert_eq!(store.group_gid("storm-invite"), Some(2001));
+    assert_eq!(store.group_gid("storm"), Some(2000));
+}
+
+#[test]
+fn subdirectories() {
+    let mut store = store();
+
+    assert!(store.subdirectories("project-a").is_none());
+    assert!(store.directory("project-a/data").is_none());
+
+    store.set_subdirectories("project-a", Some(vec![directory("project-a/data", vec![])]));
+    store.set_subdirectories(
+        "project-a/data",
+        Some(vec![directory("project-a/data/raw", vec![])]),
+    );
+    store.set_subdirectories("project-b", Some(vec![]));
+
+    assert_eq!(
+        names(store.subdirectories("project-a").unwrap().iter()),
+        vec!["project-a/data"]
+    );
+    assert_eq!(
+        store.directory("project-a/data/raw").unwrap().name,
+        "project-a/data/raw"
+    );
+    assert!(store.subdirectories("project-b").unwrap().is_empty());
+
+    // Forgotten with the ones below.
+    store.set_subdirectories("project-a", None);
+    assert!(store.directory("project-a/data/raw").is_none());
+    assert_eq!(
+        store.subdirectory_parents().collect::<Vec<_>>(),
+        ["project-b"]
+    );
+
+    // And with the directories.
+    store.invalidate(EntityKind::Directories);
+    assert!(store.subdirectories("project-b").is_none());
+}
+
+#[test]
+fn invalidate() {
+    let mut store = store();
+    store.set_users(Some(vec![User {
+        id: "jdoe".to_string(),
+        display: "John Doe".to_string(),
+    }]));
+    assert_eq!(store.user("jdoe").unwrap().display, "John Doe");
+
+    store.invalidate(EntityKind::Users);
+    assert!(!store.is_loaded(EntityKind::Users));
+    assert!(store.user("jdoe").is_none());
+    // The other kinds are kept.
+    assert!(store.is_loaded(EntityKind::Directories));
+    assert!(store.group("storm").is_some());
+
+    store.invalidate(EntityKind::Directories);
+    assert!(store.directories().is_none());
+    assert!(store
+        .directories_with(&AclQualifier::Group("storm".to_string()))
+        .next()
+        .is_none());
+
+    store.clear();
+    assert!(!store.is_loaded(EntityKind::Groups));
+}
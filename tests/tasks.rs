@@ -0,0 +1,220 @@
+mod common;
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{Stub, StubResponse};
+use poll_promise::Promise;
+use storm_daenerys_front::{
+    api::{cancel::CancelToken, client::ApiClient},
+    error::apierror::ApiError,
+    tasks::{Task, TaskManager},
+};
+
+/// State updated by the tasks.
+#[derive(Default)]
+struct State {
+    users: Vec<String>,
+    errors: usize,
+    saved: Option<usize>,
+}
+
+/// Users named after the search, the "slow" search answering late.
+fn users_stub() -> Stub {
+    Stub::start(|request| {
+        let search = request.url.trim_start_matches("/users?q=").to_string();
+        if search == "slow" {
+            thread::sleep(Duration::from_millis(300));
+        }
+
+        StubResponse::new(
+            200,
+            &format!(r#"[{{"id": "{0}", "display": "{0}"}}]"#, search),
+        )
+    })
+}
+
+/// Task searching `search` with `client`, cancellable and superseding the
+/// previous search.
+fn search(client: &ApiClient, search: &str) -> Task<State> {
+    let cancel = CancelToken::default();
+    let promise = client.with_cancel(&cancel).get_users(search.to_string());
+
+    Task::request(
+        "searching users",
+        promise,
+        |state: &mut State, result| match result {
+            Ok(users) => {
+                state.users = users
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|user| user.id)
+                    .collect()
+            }
+            Err(_) => state.errors += 1,
+        },
+    )
+    .with_key("users")
+    .with_cancel(cancel)
+}
+
+/// Apply the completions to `state` until no task is left.
+fn run(tasks: &mut TaskManager<State>, state: &mut State) {
+    let start = Instant::now();
+
+    while tasks.running().next().is_some() {
+        for completion in tasks.poll() {
+            completion(state);
+        }
+
+        assert!(start.elapsed() < Duration::from_secs(5), "tasks not done");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn completion_updates_state() {
+    let stub = users_stub();
+    let mut tasks = TaskManager::default();
+    let mut state = State::default();
+
+    tasks.start(search(&stub.client(), "jd"));
+    assert!(tasks.is_busy());
+    assert!(tasks.is_running("users"));
+
+    let info = tasks.running().next().unwrap().clone();
+    assert_eq!(info.label, "searching users");
+    assert!(info.cancellable);
+    assert_eq!(info.progress, None);
+
+    run(&mut tasks, &mut state);
+
+    assert_eq!(state.users, vec!["jd"]);
+    assert!(!tasks.is_busy());
+}
+
+#[test]
+fn latest_task_wins() {
+    let stub = users_stub();
+    let client = stub.client();
+    let mut tasks = TaskManager::default();
+    let mut state = State::default();
+
+    let slow = tasks.start(search(&client, "slow"));
+    let fast = tasks.start(search(&client, "fast"));
+    assert_ne!(slow, fast);
+    assert_eq!(tasks.running().count(), 1);
+
+    run(&mut tasks, &mut state);
+    assert_eq!(state.users, vec!["fast"]);
+
+    // The superseded response is dropped.
+    thread::sleep(Duration::from_millis(400));
+    assert!(tasks.poll().is_empty());
+    assert_eq!(state.errors, 0);
+}
+
+#[test]
+fn overlapping_tasks() {
+    let stub = users_stub();
+    let client = stub.client();
+    let mut tasks = TaskManager::default();
+    let mut state = State::default();
+
+    // Without key, both run and complete.
+    tasks.start(Task::request(
+        "first",
+        client.get_users("slow".to_string()),
+        |state: &mut State, _| state.errors += 1,
+    ));
+    tasks.start(Task::request(
+        "second",
+        client.get_users("fast".to_string()),
+        |state: &mut State, _| state.errors += 1,
+    ));
+    assert_eq!(tasks.running().count(), 2);
+
+    run(&mut tasks, &mut state);
+    assert_eq!(state.errors, 2);
+}
+
+#[test]
+fn remove_prefixed() {
+    let stub = users_stub();
+    let client = stub.client();
+    let mut tasks = TaskManager::default();
+    let mut state = State::default();
+
+    for (key, search) in [("list:a", "slow"), ("list:b", "fast"), ("other", "fast")] {
+        tasks.start(
+            Task::request(
+                key,
+                client.get_users(search.to_string()),
+                |state: &mut State, _| state.errors += 1,
+            )
+            .with_key(key),
+        );
+    }
+    assert_eq!(tasks.running().count(), 3);
+
+    tasks.remove_prefixed("list:");
+    assert!(!tasks.is_running("list:a"));
+    assert!(tasks.is_running("other"));
+
+    run(&mut tasks, &mut state);
+    assert_eq!(state.errors, 1);
+}
+
+#[test]
+fn cancel() {
+    let stub = users_stub();
+    let mut tasks = TaskManager::default();
+    let mut state = State::default();
+
+    let id = tasks.start(search(&stub.client(), "slow"));
+    let change = tasks.start(Task::request(
+        "saving",
+        Promise::from_ready(Ok(())),
+        |state: &mut State, _: Result<(), ApiError>| state.saved = Some(0),
+    ));
+
+    // A change can not be cancelled.
+    assert!(!tasks.cancel(change));
+    assert!(tasks.cancel(id));
+    assert!(!tasks.cancel(id));
+    assert!(!tasks.is_running("users"));
+
+    run(&mut tasks, &mut state);
+    thread::sleep(Duration::from_millis(400));
+    assert!(tasks.poll().is_empty());
+
+    assert!(state.users.is_empty());
+    assert_eq!(state.saved, Some(0));
+}
+
+#[test]
+fn cancel_all_keeps_changes() {
+    let stub = users_stub();
+    let client = stub.client();
+    let mut tasks = TaskManager::default();
+
+    tasks.start(search(&client, "slow"));
+    tasks.start(
+        Task::request(
+            "background",
+            client.get_users("fast".to_string()),
+            |_: &mut State, _| (),
+        )
+        .in_background(),
+    );
+
+    tasks.cancel_all();
+
+    let running: Vec<_> = tasks.running().collect();
+    assert_eq!(running.len(), 1);
+    assert!(running[0].background);
+    // Background tasks are not listed to the user.
+    assert!(!tasks.is_busy());
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::PathBuf,
+    sync::Arc,
+    thread,
+};
+
+use common::assert_network_error;
+use eframe::egui;
+use rustls::{
+    crypto::ring,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig, ServerConnection, StreamOwned,
+};
+use storm_daenerys_front::{
+    api::{client::ApiClient, retry::RetryPolicy, tls::TlsConfig, transport::Transport},
+    error::tlserror::TlsError,
+};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+/// HTTPS server answering `[]` to every request, with the certificate of
+/// `localhost` signed by the test CA.
+/// Clients must present a certificate signed by the test CA if
+/// `client_auth` is set.
+fn https_server(client_auth: bool) -> String {
+    let provider = Arc::new(ring::default_provider());
+
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+
+    let builder = if client_auth {
+        let mut roots = RootCertStore::empty();
+        for certificate in CertificateDer::pem_file_iter(fixture("ca.pem")).unwrap() {
+            roots.add(certificate.unwrap()).unwrap();
+        }
+        builder.with_client_cert_verifier(
+            WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .unwrap(),
+        )
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let config = Arc::new(
+        builder
+            .with_single_cert(
+                CertificateDer::pem_file_iter(fixture("server.pem"))
+                    .unwrap()
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap(),
+                PrivateKeyDer::from_pem_file(fixture("server.key")).unwrap(),
+            )
+            .unwrap(),
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "https://localhost:{}",
+        listener.local_addr().unwrap().port()
+    );
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let config = config.clone();
+
+            thread::spawn(move || {
+                let connection = ServerConnection::new(config).unwrap();
+                let mut stream = BufReader::new(StreamOwned::new(connection, stream));
+
+                // Skip the request headers, the handshake fails here if the
+                // client rejects the certificate.
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    match stream.read_line(&mut line) {
+                        Ok(0) | Err(_) => return,
+                        Ok(_) if line == "\r\n" => break,
+                        Ok(_) => (),
+                    }
+                }
+
+                let stream = stream.get_mut();
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]",
+                );
+                stream.conn.send_close_notify();
+                let _ = stream.flush();
+            });
+        }
+    });
+
+    url
+}
+
+fn client(url: &str, tls: &TlsConfig) -> ApiClient {
+    ApiClient::new(&egui::Context::default(), url.to_string())
+        .with_transport(Transport::new(tls).unwrap())
+        .with_retry(RetryPolicy::none())
+}
+
+fn trusted() -> TlsConfig {
+    TlsConfig {
+        ca_bundle: Some(fixture("ca.pem")),
+        ..Default::default()
+    }
+}
+
+fn with_client_certificate(tls: TlsConfig) -> TlsConfig {
+    TlsConfig {
+        client_cert: Some(fixture("client.pem")),
+        client_key: Some(fixture("client.key")),
+        ..tls
+    }
+}
+
+#[test]
+fn custom_ca_bundle() {
+    let url = https_server(false);
+
+    client(&url, &trusted())
+        .get_groups()
+        .block_and_take()
+        .unwrap();
+}
+
+#[test]
+fn unknown_ca_rejected() {
+    let url = https_server(false);
+
+    // Forces the custom transport with the default CAs only.
+    let tls = with_client_certificate(TlsConfig::default());
+
+    assert_network_error(client(&url, &tls).get_groups().block_and_take());
+}
+
+#[test]
+fn insecure_mode() {
+    let url = https_server(false);
+    let tls = TlsConfig {
+        insecure: true,
+        ..Default::default()
+    };
+
+    client(&url, &tls).get_groups().block_and_take().unwrap();
+}
+
+#[test]
+fn client_certificate() {
+    let url = https_server(true);
+
+    assert_network_error(client(&url, &trusted()).get_groups().block_and_take());
+
+    client(&url, &with_client_certificate(trusted()))
+        .get_groups()
+        .block_and_take()
+        .unwrap();
+}
+
+#[test]
+fn missing_file() {
+    let tls = TlsConfig {
+        ca_bundle: Some(fixture("missing.pem")),
+        ..Default::default()
+    };
+
+    assert!(matches!(Transport::new(&tls), Err(TlsError::Pem { .. })));
+}
+
+#[test]
+fn no_certificate() {
+    let path =
+        std::env::temp_dir().join(format!("storm-daenerys-{}-empty.pem", std::process::id()));
+    fs::write(&path, "").unwrap();
+
+    let tls = TlsConfig {
+        ca_bundle: Some(path.clone()),
+        ..Default::default()
+    };
+
+    match Transport::new(&tls) {
+        Err(e @ TlsError::NoCertificate { .. }) => {
+            assert_eq!(
+                e.to_string(),
+                format!("no certificate found in {}", path.display())
+            )
+        }
+        Err(e) => panic!("expected NoCertificate, got {}", e),
+        Ok(_) => panic!("expected NoCertificate"),
+    }
+}
+
+#[test]
+fn no_private_key() {
+    let tls = TlsConfig {
+        client_key: Some(fixture("client.pem")),
+        ..with_client_certificate(trusted())
+    };
+
+    assert!(matches!(
+        Transport::new(&tls),
+        Err(TlsError::NoPrivateKey { .. })
+    ));
+}
+
+#[test]
+fn mismatched_private_key() {
+    let tls = TlsConfig {
+        client_key: Some(fixture("server.key")),
+        ..with_client_certificate(trusted())
+    };
+
+    assert!(matches!(Transport::new(&tls), Err(TlsError::Config(_))));
+}
+
+#[test]
+fn summary() {
+    assert_eq!(TlsConfig::default().summary(), "default CAs");
+    assert_eq!(trusted().summary(), "trusted CA ca.pem");
+    assert_eq!(
+        with_client_certificate(TlsConfig {
+            insecure: true,
+            ..trusted()
+        })
+        .summary(),
+        "certificates not verified, client certificate client.pem"
+    );
+}
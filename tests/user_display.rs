@@ -0,0 +1,366 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use common::{RecordedRequest, Stub, StubResponse};
+use storm_daenerys_front::api::userdisplay::{
+    cache::{self, UserDisplayCache},
+    resolver::UserDisplayResolver,
+};
+
+/// Value of the `q` parameter of `request`.
+fn query(request: &RecordedRequest) -> &str {
+    request.url.split_once("?q=").unwrap().1
+}
+
+fn display_name(uid: &str) -> String {
+    format!("User {}", uid)
+}
+
+/// Batch endpoint answering the display name of every uid, except `ghost`
+/// which is unknown.
+fn batch_response(request: &RecordedRequest) -> StubResponse {
+    let displays: HashMap<&str, Option<String>> = query(request)
+        .split(',')
+        .map(|uid| (uid, (uid != "ghost").then(|| display_name(uid))))
+        .collect();
+
+    StubResponse::new(200, &serde_json::to_string(&displays).unwrap())
+}
+
+/// Call `update` until every requested uid is resolved or failed.
+fn resolve_all(resolver: &mut UserDisplayResolver, max_in_flight: usize) {
+    for _ in 0..1000 {
+        resolver.update();
+        assert!(resolver.in_flight() <= max_in_flight);
+
+        if resolver.is_idle() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(2));
+    }
+    panic!("user displays not resolved");
+}
+
+fn uids(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("user{:03}", i)).collect()
+}
+
+#[test]
+fn chunked_requests() {
+    let stub = Stub::start(batch_response);
+    let mut resolver = UserDisplayResolver::new(stub.client()).with_limits(50, 4);
+
+    // A directory with 300 ACL entries, each rendered several times.
+    for _ in 0..3 {
+        for uid in uids(300) {
+            resolver.request(&uid);
+        }
+    }
+    resolver.request("ghost");
+    resolve_all(&mut resolver, 4);
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 7);
+    assert!(requests
+        .iter()
+        .all(|r| r.url.starts_with("/userdisplays?q=")));
+    assert!(requests.iter().all(|r| query(r).split(',').count() <= 50));
+
+    for uid in uids(300) {
+        assert_eq!(resolver.get(&uid), Some(&Some(display_name(&uid))));
+    }
+    assert_eq!(resolver.get("ghost"), Some(&None));
+
+    // Resolved uids are not requested again.
+    resolver.request("user000");
+    resolve_all(&mut resolver, 4);
+    assert_eq!(stub.requests().len(), 7);
+}
+
+#[test]
+fn concurrency_cap() {
+    let stub = Stub::start(batch_response);
+    let mut resolver = UserDisplayResolver::new(stub.client()).with_limits(10, 2);
+
+    for uid in uids(100) {
+        resolver.request(&uid);
+    }
+
+    // Nothing is sent before the first update.
+    assert_eq!(resolver.in_flight(), 0);
+    resolver.update();
+    assert_eq!(resolver.in_flight(), 2);
+
+    resolve_all(&mut resolver, 2);
+    assert_eq!(stub.requests().len(), 10);
+}
+
+#[test]
+fn fallback_without_batch_endpoint() {
+    let stub = Stub::start(|request| {
+        if request.url.starts_with("/userdisplays") {
+            return StubResponse::new(404, "");
+        }
+        StubResponse::new(200, &format!(r#""{}""#, display_name(query(request))))
+    });
+    let mut resolver = UserDisplayResolver::new(stub.client()).with_limits(5, 3);
+
+    for uid in uids(12) {
+        resolver.request(&uid);
+    }
+    resolve_all(&mut resolver, 3);
+
+    for uid in uids(12) {
+        assert_eq!(resolver.get(&uid), Some(&Some(display_name(&uid))));
+    }
+
+    // The batch endpoint is not tried again.
+    resolver.request("late");
+    resolve_all(&mut resolver, 3);
+    assert_eq!(resolver.get("late"), Some(&Some(display_name("late"))));
+
+    let batches = stub
+        .requests()
+        .iter()
+        .filter(|r| r.url.starts_with("/userdisplays"))
+        .count();
+    assert!(batches <= 3);
+}
+
+#[test]
+fn error_isolation() {
+    // The batch fails because of `broken`, which also fails alone.
+    let stub = Stub::start(|request| {
+        if query(request).contains("broken") {
+            return StubResponse::new(500, "ldap error");
+        }
+        if request.url.starts_with("/userdisplays") {
+            return batch_response(request);
+        }
+        StubResponse::new(200, &format!(r#""{}""#, display_name(query(request))))
+    });
+    let mut resolver = UserDisplayResolver::new(stub.client()).with_limits(50, 4);
+
+    for uid in ["user001", "broken", "user002"] {
+        resolver.request(uid);
+    }
+    resolve_all(&mut resolver, 4);
+
+    assert_eq!(
+        resolver.get("user001"),
+        Some(&Some(display_name("user001")))
+    );
+    assert_eq!(
+        resolver.get("user002"),
+        Some(&Some(display_name("user002")))
+    );
+    assert_eq!(resolver.get("broken"), None);
+    assert!(resolver.error("broken").unwrap().contains("ldap error"));
+    assert_eq!(resolver.error("user001"), None);
+
+    // Failed uids are not retried until the errors are cleared.
+    let count = stub.requests().len();
+    resolver.request("broken");
+    resolve_all(&mut resolver, 4);
+    assert_eq!(stub.requests().len(), count);
+
+    resolver.clear_errors();
+    resolver.request("broken");
+    resolve_all(&mut resolver, 4);
+    assert_eq!(stub.requests().len(), count + 1);
+}
+
+/// Cache file unique to the test, removed first.
+fn cache_file(test: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("storm-daenerys-{}-{}", std::process::id(), test))
+        .join(cache::FILE_NAME);
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Write a cache file of `stub` with entries resolved `age` seconds ago.
+fn write_cache(path: &Path, stub: &Stub, entries: &[(&str, Option<&str>, u64)]) {
+    let entries: HashMap<&str, serde_json::Value> = entries
+        .iter()
+        .map(|(uid, display, age)| {
+            (
+                *uid,
+                serde_json::json!({"display": display, "resolved_at": unix_now() - age}),
+            )
+        })
+        .collect();
+
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(
+        path,
+        serde_json::json!({"api_url": stub.url, "entries": entries}).to_string(),
+    )
+    .unwrap();
+}
+
+fn persisted_resolver(stub: &Stub, path: &Path) -> UserDisplayResolver {
+    UserDisplayResolver::new(stub.client())
+        .with_cache(UserDisplayCache::new(&stub.url).with_file(path.to_path_buf()))
+}
+
+#[test]
+fn persisted_cache() {
+    let stub = Stub::start(batch_response);
+    let path = cache_file("persisted");
+
+    let mut resolver = persisted_resolver(&stub, &path);
+    for uid in ["user001", "ghost"] {
+        resolver.request(uid);
+    }
+    resolve_all(&mut resolver, 4);
+    assert!(path.exists());
+
+    // The next launch does not ask the backend.
+    let mut resolver = persisted_resolver(&stub, &path);
+    for uid in ["user001", "ghost"] {
+        resolver.request(uid);
+    }
+    resolve_all(&mut resolver, 4);
+
+    assert_eq!(
+        resolver.get("user001"),
+        Some(&Some(display_name("user001")))
+    );
+    assert_eq!(resolver.get("ghost"), Some(&None));
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[test]
+fn stale_entries_revalidated() {
+    let stub = Stub::start(batch_response);
+    let path = cache_file("stale");
+    let hour = 3600;
+    write_cache(
+        &path,
+        &stub,
+        &[
+            // Fresh: a display name is kept a week, an unknown account an hour.
+            ("fresh", Some("Fresh Name"), 2 * hour),
+            ("user001", Some("Old Name"), 8 * 24 * hour),
+            ("user002", None, 2 * hour),
+        ],
+    );
+
+    let mut resolver = persisted_resolver(&stub, &path);
+
+    // Stale entries are shown until revalidated.
+    assert_eq!(resolver.get("user001"), Some(&Some("Old Name".to_string())));
+    assert_eq!(resolver.get("user002"), Some(&None));
+
+    for uid in ["fresh", "user001", "user002"] {
+        resolver.request(uid);
+    }
+    resolve_all(&mut resolver, 4);
+
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(query(&requests[0]), "user001,user002");
+    assert_eq!(resolver.get("fresh"), Some(&Some("Fresh Name".to_string())));
+    assert_eq!(
+        resolver.get("user001"),
+        Some(&Some(display_name("user001")))
+    );
+    assert_eq!(
+        resolver.get("user002"),
+        Some(&Some(display_name("user002")))
+    );
+}
+
+#[test]
+fn custom_ttl() {
+    let stub = Stub::start(batch_response);
+    let path = cache_file("ttl");
+    write_cache(&path, &stub, &[("user001", Some("Name"), 120)]);
+
+    let mut resolver = UserDisplayResolver::new(stub.client()).with_cache(
+        UserDisplayCache::new(&stub.url)
+            .with_ttl(Duration::from_secs(60), Duration::from_secs(60))
+            .with_file(path),
+    );
+    resolver.request("user001");
+    resolve_all(&mut resolver, 4);
+
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[test]
+fn old_and_foreign_entries_dropped() {
+    let stub = Stub::respond(200, "{}");
+    let path = cache_file("purge");
+
+    write_cache(&path, &stub, &[("gone", None, 24 * 3600)]);
+    assert_eq!(persisted_resolver(&stub, &path).get("gone"), None);
+
+    // Names of another backend.
+    let other = Stub::respond(200, "{}");
+    write_cache(&path, &other, &[("user001", Some("Name"), 0)]);
+    assert_eq!(persisted_resolver(&stub, &path).get("user001"), None);
+
+    // A corrupted file is ignored.
+    fs::write(&path, "{not json").unwrap();
+    assert!(persisted_resolver(&stub, &path).is_empty());
+}
+
+#[test]
+fn flush() {
+    let stub = Stub::start(batch_response);
+    let path = cache_file("flush");
+
+    let mut resolver = persisted_resolver(&stub, &path);
+    resolver.request("user001");
+    resolve_all(&mut resolver, 4);
+    assert_eq!(resolver.len(), 1);
+
+    resolver.flush().unwrap();
+
+    assert!(resolver.is_empty());
+    assert!(!path.exists());
+
+    resolver.request("user001");
+    resolve_all(&mut resolver, 4);
+    assert_eq!(stub.requests().len(), 2);
+}
+
+#[test]
+fn switch_backend() {
+    let first = Stub::start(batch_response);
+    let second = Stub::start(batch_response);
+    let path = cache_file("switch");
+
+    let mut resolver = persisted_resolver(&first, &path);
+    resolver.request("user001");
+    resolve_all(&mut resolver, 4);
+
+    resolver.switch_backend(second.client());
+
+    // The names of the first backend are not shown for the second one.
+    assert_eq!(resolver.get("user001"), None);
+
+    resolver.request("user001");
+    resolve_all(&mut resolver, 4);
+    assert_eq!(second.requests().len(), 1);
+    assert_eq!(
+        resolver.get("user001"),
+        Some(&Some(display_name("user001")))
+    );
+}
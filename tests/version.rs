@@ -0,0 +1,111 @@
+mod common;
+
+use common::{Stub, StubResponse};
+use storm_daenerys_front::api::{
+    conditional::Conditional,
+    userdisplay::resolver::UserDisplayResolver,
+    version::{BackendVersion, Compatibility, Feature, Negotiation, PROTOCOL_MAX},
+};
+
+fn backend(protocol: u32) -> Option<BackendVersion> {
+    Some(BackendVersion {
+        version: "2.1.0".to_string(),
+        protocol,
+    })
+}
+
+#[test]
+fn full() {
+    let negotiation = Negotiation::new(backend(PROTOCOL_MAX));
+
+    assert_eq!(negotiation.compatibility, Compatibility::Full);
+    assert!(Feature::ALL
+        .into_iter()
+        .all(|feature| negotiation.supports(feature)));
+    assert_eq!(negotiation.message(), None);
+}
+
+#[test]
+fn legacy_backend_degraded() {
+    let negotiation = Negotiation::new(None);
+
+    assert_eq!(negotiation.protocol(), 1);
+    assert_eq!(
+        negotiation.compatibility,
+        Compatibility::Degraded(vec![
+            Feature::BatchUserDisplays,
+            Feature::ConditionalGet,
+            Feature::Subdirectories,
+            Feature::GroupGids,
+            Feature::DefaultAcls,
+            Feature::RecursiveAcls
+        ])
+    );
+    assert!(!negotiation.supports(Feature::BatchUserDisplays));
+    assert!(!negotiation.is_incompatible());
+
+    let message = negotiation.message().unwrap();
+    assert!(message.contains("unknown version"), "{}", message);
+    assert!(message.contains("batch user display"), "{}", message);
+}
+
+#[test]
+fn newer_backend_incompatible() {
+    let negotiation = Negotiation::new(backend(PROTOCOL_MAX + 1));
+
+    assert!(negotiation.is_incompatible());
+    assert!(!negotiation.supports(Feature::ConditionalGet));
+    assert!(negotiation
+        .message()
+        .unwrap()
+        .contains("upgrade the application"));
+}
+
+#[test]
+fn older_backend_incompatible() {
+    let negotiation = Negotiation::new(backend(0));
+
+    assert!(negotiation.is_incompatible());
+    assert!(negotiation
+        .message()
+        .unwrap()
+        .contains("upgrade the backend"));
+}
+
+#[test]
+fn conditional_switched_off() {
+    let stub = Stub::start(|_| StubResponse::new(200, "[]").with_header("ETag", "\"v1\""));
+    let client = stub.client();
+
+    client.get_groups().block_and_take().unwrap();
+    client.set_conditional(false);
+
+    let groups = client.get_groups().block_and_take().unwrap();
+    client.get_groups().block_and_take().unwrap();
+
+    assert!(matches!(groups, Conditional::Modified(_)));
+    assert!(stub
+        .requests()
+        .iter()
+        .skip(1)
+        .all(|request| request.header("If-None-Match").is_none()));
+}
+
+#[test]
+fn batch_switched_off() {
+    let stub = Stub::respond(200, "\"John Doe\"");
+    let mut resolver = UserDisplayResolver::new(stub.client());
+
+    resolver.set_batch_supported(false);
+    resolver.request("jdoe");
+    for _ in 0..1000 {
+        resolver.update();
+        if resolver.is_idle() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    assert_eq!(resolver.get("jdoe"), Some(&Some("John Doe".to_string())));
+    assert!(stub.requests()[0].url.starts_with("/userdisplay?"));
+}
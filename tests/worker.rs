@@ -0,0 +1,259 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{RecordedRequest, Stub, StubResponse};
+use eframe::egui;
+use poll_promise::Promise;
+use storm_daenerys_common::types::group::Group;
+use storm_daenerys_front::{
+    api::{
+        client::ApiClient,
+        conditional::Conditional,
+        userdisplay::{
+            cache::{UserDisplayCache, DEFAULT_TTL},
+            resolver::UserDisplayResolver,
+        },
+    },
+    error::{apierror::ApiError, apperror::AppError},
+    tasks::Progress,
+    worker::{
+        builder::Worker,
+        displays::UserDisplays,
+        handle::WorkerHandle,
+        message::{Job, JobReply, ToAppMessage, ToWorkerMessage},
+    },
+};
+
+const DIRECTORIES: &str = r#"[{"name": "project-a", "acls": [], "valid": true, "quota": null}]"#;
+const GROUPS: &str =
+    r#"[{"cn": "storm", "description": "STORM users", "owner": null, "member": ["jdoe"]}]"#;
+
+/// Backend answering the lists and the display name of every uid.
+fn backend(request: &RecordedRequest) -> StubResponse {
+    match request.url.split_once("?q=") {
+        Some(("/userdisplays", uids)) => {
+            let displays: HashMap<&str, String> = uids
+                .split(',')
+                .map(|uid| (uid, format!("User {}", uid)))
+                .collect();
+            StubResponse::new(200, &serde_json::to_string(&displays).unwrap())
+        }
+        _ if request.url == "/directories" => StubResponse::new(200, DIRECTORIES),
+        _ if request.url == "/groups" => StubResponse::new(200, GROUPS),
+        _ => StubResponse::new(200, ""),
+    }
+}
+
+fn spawn(client: ApiClient) -> WorkerHandle {
+    WorkerHandle::spawn(move |sender, receiver| {
+        Worker::new(sender, receiver, egui::Context::default(), client)
+    })
+    .unwrap()
+}
+
+/// The first message of `worker` matching `select`, skipping the others.
+fn recv<T>(worker: &WorkerHandle, mut select: impl FnMut(ToAppMessage) -> Option<T>) -> T {
+    let start = Instant::now();
+
+    while start.elapsed() < Duration::from_secs(5) {
+        match worker.try_recv().unwrap() {
+            Some(message) => {
+                if let Some(selected) = select(message) {
+                    return selected;
+                }
+            }
+            None => thread::sleep(Duration::from_millis(5)),
+        }
+    }
+    panic!("message not received");
+}
+
+fn group(members: &[&str]) -> Group {
+    Group {
+        cn: "storm".to_string(),
+        description: String::new(),
+        owner: None,
+        member: Some(members.iter().map(|m| m.to_string()).collect()),
+    }
+}
+
+#[test]
+fn ping() {
+    let worker = spawn(Stub::start(backend).client());
+
+    worker.send(ToWorkerMessage::Ping).unwrap();
+    recv(&worker, |message| {
+        matches!(message, ToAppMessage::Pong).then_some(())
+    });
+}
+
+#[test]
+fn periodic_refresh() {
+    let stub = Stub::start(backend);
+    let worker = spawn(stub.client());
+
+    worker
+        .send(ToWorkerMessage::SetRefreshInterval(Some(
+            Duration::from_millis(50),
+        )))
+        .unwrap();
+
+    let directories = recv(&worker, |message| match message {
+        ToAppMessage::Directories(Ok(Conditional::Modified(directories))) => directories,
+        _ => None,
+    });
+    assert_eq!(directories[0].name, "project-a");
+
+    recv(&worker, |message| match message {
+        ToAppMessage::Groups(Ok(Conditional::Modified(groups))) => groups,
+        _ => None,
+    });
+
+    // Reloaded again until stopped.
+    recv(&worker, |message| {
+        matches!(message, ToAppMessage::Directories(_)).then_some(())
+    });
+    worker
+        .send(ToWorkerMessage::SetRefreshInterval(None))
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    let count = stub.requests().len();
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(stub.requests().len(), count);
+}
+
+#[test]
+fn user_displays() {
+    let stub = Stub::start(backend);
+    let worker = spawn(stub.client());
+
+    worker
+        .send(ToWorkerMessage::ResolveUserDisplays(vec![
+            "jdoe".to_string(),
+            "asmith".to_string(),
+        ]))
+        .unwrap();
+
+    let mut resolved = HashMap::new();
+    let update = recv(&worker, |message| match message {
+        ToAppMessage::UserDisplays(update) => {
+            resolved.extend(update.resolved.clone());
+            update.idle.then_some(update)
+        }
+        _ => None,
+    });
+    assert_eq!(update.len, 2);
+    assert_eq!(resolved["jdoe"], Some("User jdoe".to_string()));
+    assert_eq!(stub.requests().len(), 1);
+
+    // Flushed names are resolved again.
+    worker.send(ToWorkerMessage::FlushUserDisplays).unwrap();
+    recv(&worker, |message| match message {
+        ToAppMessage::UserDisplays(update) => (update.len == 0).then_some(()),
+        _ => None,
+    });
+
+    worker
+        .send(ToWorkerMessage::ResolveUserDisplays(vec![
+            "jdoe".to_string()
+        ]))
+        .unwrap();
+    let update = recv(&worker, |message| match message {
+        ToAppMessage::UserDisplays(update) => (!update.resolved.is_empty()).then_some(update),
+        _ => None,
+    });
+    assert_eq!(update.resolved["jdoe"], Some("User jdoe".to_string()));
+    assert_eq!(stub.requests().len(), 2);
+}
+
+#[test]
+fn unknown_account_retried() {
+    let stub = Stub::start(|_| StubResponse::new(200, r#"{"ghost": null}"#));
+    let client = stub.client();
+    let cache =
+        UserDisplayCache::new(client.api_url()).with_ttl(DEFAULT_TTL, Duration::from_secs(1));
+    let worker = WorkerHandle::spawn(move |sender, receiver| {
+        let user_displays = UserDisplayResolver::new(client.clone()).with_cache(cache);
+        Worker::new(sender, receiver, egui::Context::default(), client)
+            .with_user_displays(user_displays)
+    })
+    .unwrap();
+
+    let mut displays = UserDisplays::default();
+    let resolve = |displays: &mut UserDisplays| {
+        let uids = displays.take_requests();
+        assert_eq!(uids, ["ghost"]);
+        worker
+            .send(ToWorkerMessage::ResolveUserDisplays(uids))
+            .unwrap();
+
+        let update = recv(&worker, |message| match message {
+            ToAppMessage::UserDisplays(update) => {
+                update.resolved.contains_key("ghost").then_some(update)
+            }
+            _ => None,
+        });
+        displays.apply(update);
+    };
+
+    displays.request("ghost");
+    resolve(&mut displays);
+    assert_eq!(displays.get("ghost"), Some(&None));
+    assert_eq!(stub.requests().len(), 1);
+
+    // Requested again once the negative TTL passed.
+    thread::sleep(Duration::from_millis(1100));
+    displays.request("ghost");
+    resolve(&mut displays);
+    assert_eq!(displays.get("ghost"), Some(&None));
+    assert_eq!(stub.requests().len(), 2);
+}
+
+#[test]
+fn save_group_job() {
+    let stub = Stub::start(backend);
+    let worker = spawn(stub.client());
+
+    let (sender, promise) = Promise::new();
+    let progress = Progress::default();
+    worker
+        .send(ToWorkerMessage::Job(Job::SaveGroup {
+            group_backup: group(&["jdoe"]),
+            group: group(&["asmith", "bwayne"]),
+            progress: progress.clone(),
+            reply: JobReply::new(sender),
+        }))
+        .unwrap();
+
+    assert!(promise.block_and_take().is_ok());
+    assert_eq!(progress.fraction(), Some(1.0));
+    assert_eq!(stub.requests().len(), 3);
+}
+
+#[test]
+fn stopped_worker() {
+    let worker = spawn(Stub::start(backend).client());
+
+    worker.send(ToWorkerMessage::Shutdown).unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    assert!(matches!(worker.try_recv(), Err(AppError::ChannelClosed)));
+
+    // A job sent to a stopped worker is cancelled, not left waiting.
+    let (sender, promise) = Promise::new();
+    let result = worker.send(ToWorkerMessage::Job(Job::SaveGroup {
+        group_backup: group(&[]),
+        group: group(&["jdoe"]),
+        progress: Progress::default(),
+        reply: JobReply::new(sender),
+    }));
+
+    assert!(matches!(result, Err(AppError::ChannelSendError)));
+    assert!(matches!(promise.block_and_take(), Err(ApiError::Cancelled)));
+}